@@ -0,0 +1,54 @@
+/* Measures how many steps a second the gym environments manage, both one at a time and as a batch
+ * spread over every core. Run with `cargo bench --bench gym`.
+ * Every player picks a random action each step, so rounds are full of bombs.
+ */
+use open_bomberman::{
+  gym::{reward::Rewards, Action, Env, EnvBatch, Observations, ACTIONS},
+  model::{rng::Rng, rules::Rules, stage::StageImpl}
+};
+use std::{num::NonZeroUsize, thread, time::Instant};
+
+const PLAYERS: usize = 4;
+const STEPS: usize = 20000;
+const BATCH_SIZE: usize = 1024;
+const BATCH_STEPS: usize = 200;
+
+fn random_actions(rng: &mut Rng) -> Vec<Action> {
+  return (0..PLAYERS).map(|_| ACTIONS[rng.below(ACTIONS.len() as u32) as usize]).collect();
+}
+
+fn main() {
+  let env: Env = Env::new(
+    Rules::default(), Box::new(StageImpl::classic()), PLAYERS, Rewards::default(), 1
+  ).expect("The classic stage fits four players");
+  let mut rng: Rng = Rng::new(0);
+
+  let mut single: Env = env.clone();
+  let start: Instant = Instant::now();
+  for step in 0..STEPS {
+    if single.step(&random_actions(&mut rng)).2 {
+      single.reset(step as u64);
+    }
+  }
+  println!("{:<8} {:>12.0} steps/s", "single", STEPS as f64 / start.elapsed().as_secs_f64());
+
+  let threads: usize = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+  let mut batch: EnvBatch = EnvBatch::new(&env, BATCH_SIZE, threads);
+  batch.reset(0);
+  let start: Instant = Instant::now();
+  for step in 0..BATCH_STEPS {
+    let actions: Vec<Vec<Action>> = (0..BATCH_SIZE).map(|_| random_actions(&mut rng)).collect();
+    let results: Vec<(Observations, Vec<f32>, bool)> = batch.step(&actions);
+    for (i, (_, _, done)) in results.iter().enumerate() {
+      if *done {
+        batch.get_envs_mut()[i].reset((step * BATCH_SIZE + i) as u64);
+      }
+    }
+  }
+  println!(
+    "{:<8} {:>12.0} steps/s on {} threads",
+    "batch",
+    (BATCH_SIZE * BATCH_STEPS) as f64 / start.elapsed().as_secs_f64(),
+    threads
+  );
+}
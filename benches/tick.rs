@@ -0,0 +1,146 @@
+/* Compares ticking a world functionally against ticking it in place, and against ticking it in
+ * place and working out its checksum every tick, the way netplay does. For rollback, it also times
+ * taking a snapshot of the world every tick, and rolling back as far as rollback can go every so
+ * often, playing those ticks again.
+ * Run with `cargo bench`. Alongside the time per tick, this counts heap allocations made while
+ * ticking, since those are what headless simulations end up bound by.
+ */
+use ndarray::{Array, Ix2};
+use open_bomberman::{
+  common::direction::Direction,
+  model::{
+    bomb::{Bomb, BombImpl},
+    checksum,
+    input::{self, InputFrame},
+    player::Player,
+    position,
+    rules::Rules,
+    stage::{StageImpl, Tile},
+    world::{World, WorldImpl}
+  },
+  net::rollback::MAX_PREDICTION
+};
+use std::{
+  alloc::{GlobalAlloc, Layout, System},
+  collections::VecDeque,
+  sync::atomic::{AtomicUsize, Ordering},
+  time::{Duration, Instant}
+};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+  unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    return System.alloc(layout);
+  }
+
+  unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+    System.dealloc(ptr, layout);
+  }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const ROUNDS: usize = 200;
+const TICKS_PER_ROUND: usize = 600;
+
+// A full 15 by 9 stage with pillars and soft walls, and a bomb going off every so often.
+fn make_world() -> WorldImpl {
+  let rules: Rules = Rules::default();
+  let tiles: Array<Tile, Ix2> = Array::from_shape_fn((9, 15), |(y, x)| {
+    if x % 2 == 1 && y % 2 == 1 {
+      return Tile::HardWall;
+    } else if (x + y) % 3 == 0 {
+      return Tile::SoftWall;
+    } else {
+      return Tile::Ground;
+    }
+  });
+  let mut bombs: Vec<Box<dyn Bomb>> = vec!();
+  for i in 0..12 {
+    let position: (i8, i8) = ((i * 2) % 15, (i * 4) % 9);
+    bombs.push(Box::new(BombImpl::new(position, 0, 30 + 20 * i as i16, false, false, 3)));
+  }
+  let players: Vec<Player> = vec!(
+    Player::new(0, position::from_tile(&(0, 0)), Direction::East, rules.get_starting_stats()),
+    Player::new(1, position::from_tile(&(14, 8)), Direction::West, rules.get_starting_stats())
+  );
+  let stage: Box<StageImpl> = Box::new(StageImpl::new(tiles, vec!()));
+  return WorldImpl::new(rules, stage, players, bombs, vec!(), 0);
+}
+
+// Runs every round through the given tick function, returning the time and allocations it took.
+fn run(tick_round: &dyn Fn(WorldImpl)) -> (Duration, usize) {
+  let mut elapsed: Duration = Duration::from_secs(0);
+  let mut allocations: usize = 0;
+  for _ in 0..ROUNDS {
+    let world: WorldImpl = make_world();
+    let start_allocations: usize = ALLOCATIONS.load(Ordering::Relaxed);
+    let start: Instant = Instant::now();
+    tick_round(world);
+    elapsed += start.elapsed();
+    allocations += ALLOCATIONS.load(Ordering::Relaxed) - start_allocations;
+  }
+  return (elapsed, allocations);
+}
+
+fn report(name: &str, (elapsed, allocations): (Duration, usize)) {
+  let ticks: f64 = (ROUNDS * TICKS_PER_ROUND) as f64;
+  println!(
+    "{:<10} {:>10.1} ns/tick {:>8.2} allocations/tick",
+    name,
+    elapsed.as_nanos() as f64 / ticks,
+    allocations as f64 / ticks
+  );
+}
+
+fn main() {
+  let inputs: InputFrame = input::idle_frame();
+  report("tick", run(&|world| {
+    let mut world: Box<dyn World> = Box::new(world);
+    for _ in 0..TICKS_PER_ROUND {
+      world = world.tick(&inputs).0;
+    }
+  }));
+  report("tick_mut", run(&|mut world| {
+    for _ in 0..TICKS_PER_ROUND {
+      world.tick_mut(&inputs);
+    }
+  }));
+  report("checksum", run(&|mut world| {
+    let mut checksums: u64 = 0;
+    for _ in 0..TICKS_PER_ROUND {
+      world.tick_mut(&inputs);
+      checksums ^= checksum::checksum(&world);
+    }
+    std::hint::black_box(checksums);
+  }));
+  report("snapshot", run(&|mut world| {
+    for _ in 0..TICKS_PER_ROUND {
+      std::hint::black_box(world.clone());
+      world.tick_mut(&inputs);
+    }
+  }));
+  report("rollback", run(&|mut world| {
+    let mut history: VecDeque<WorldImpl> = VecDeque::new();
+    for tick in 1..=TICKS_PER_ROUND {
+      history.push_back(world.clone());
+      world.tick_mut(&inputs);
+      if history.len() > MAX_PREDICTION as usize {
+        history.pop_front();
+      }
+      if tick % MAX_PREDICTION as usize == 0 {
+        world = history.pop_front().unwrap();
+        history.clear();
+        while history.len() < MAX_PREDICTION as usize {
+          history.push_back(world.clone());
+          world.tick_mut(&inputs);
+        }
+      }
+    }
+  }));
+}
@@ -0,0 +1,180 @@
+use crate::{
+  common::direction::CARDINALS,
+  model::{
+    input::PlayerId,
+    prediction::BlastPrediction,
+    stage::{Stage, Tile},
+    world::World
+  }
+};
+use ndarray::{Array, Ix2};
+
+/* A struct representing when each tile of a stage is going to be lethal, from a bot's point of
+ * view. Each tile has the earliest and latest tick it could be covered by a blast, counting from
+ * the next tick as 1 (and anything already covered as 0), or nothing if nothing is going to reach
+ * it.
+ *
+ * Most of this comes straight from a BlastPrediction. Remote bombs could go off at any moment
+ * though, so they're treated as going off right away, except for the viewer's own, which only go
+ * off once the viewer is out of the way. Bombs the viewer is only thinking about placing are
+ * added the same way.
+ *
+ * Tiles are stored row by row like a stage's, so a position of (x, y) lives at [y, x].
+ */
+#[derive(Clone, PartialEq, Debug)]
+pub struct DangerMap {
+  tiles: Array<Option<(u32, u32)>, Ix2>,
+  spread_rate: u32,
+  blast_lifetime: u32
+}
+
+/* A struct representing what a bomb's blast would reach.
+ * Covered is every tile it would cover, along with how many tiles each is from the center.
+ * Burned is any soft walls it would burn.
+ */
+pub struct Reach {
+  covered: Vec<((i8, i8), u32)>,
+  burned: Vec<(i8, i8)>
+}
+
+impl Reach {
+  pub fn get_covered(&self) -> &Vec<((i8, i8), u32)> {
+    return &self.covered;
+  }
+
+  pub fn get_burned(&self) -> &Vec<(i8, i8)> {
+    return &self.burned;
+  }
+}
+
+/* Returns what a bomb's blast would reach, following how flames spread through the stage as it is
+ * right now.
+ */
+pub fn blast_reach(stage: &dyn Stage, center: &(i8, i8), range: i8, piercing: bool) -> Reach {
+  let mut reach: Reach = Reach { covered: vec!((*center, 0)), burned: vec!() };
+  for direction in CARDINALS.iter() {
+    let mut tile: (i8, i8) = *center;
+    for distance in 1..=range.max(0) as u32 {
+      tile = direction.step(&tile);
+      match stage.get_tile(&tile) {
+        Ok(Tile::Ground) => reach.covered.push((tile, distance)),
+        Ok(Tile::SoftWall) => {
+          reach.burned.push(tile);
+          if !piercing {
+            break;
+          }
+          reach.covered.push((tile, distance));
+        },
+        _ => break
+      }
+    }
+  }
+  return reach;
+}
+
+impl DangerMap {
+  // Returns a map of a stage with nothing dangerous on it.
+  pub fn empty(world: &dyn World) -> DangerMap {
+    let dimensions: &(i8, i8) = world.get_stage().get_dimensions();
+    return DangerMap {
+      tiles: Array::from_elem((dimensions.1 as usize, dimensions.0 as usize), None),
+      spread_rate: *world.get_rules().get_flame_spread_rate() as u32,
+      blast_lifetime: *world.get_rules().get_blast_lifetime() as u32
+    }
+  }
+
+  /* Works out how dangerous every tile of a world is, as the viewer sees it, from a prediction of
+   * the world's blasts. Remote bombs that the prediction has still sitting there are added on top.
+   */
+  pub fn new(world: &dyn World, prediction: &BlastPrediction, viewer: &PlayerId) -> DangerMap {
+    let mut danger: DangerMap = DangerMap::empty(world);
+    danger.tiles = prediction.get_spans().clone();
+    for bomb in world.get_bombs() {
+      let detonated: bool = prediction.get_detonations().iter()
+        .any(|(position, _)| position == bomb.get_position());
+      if !*bomb.get_remote() || detonated {
+        continue;
+      }
+      let detonation: u32 = match bomb.get_owner() == viewer {
+        true => (*bomb.get_lifetime()).max(1) as u32,
+        false => 1
+      };
+      danger.add_bomb(
+        world.get_stage(), bomb.get_position(), *bomb.get_range(), *bomb.get_piercing(), detonation
+      );
+    }
+    return danger;
+  }
+
+  /* Works out how dangerous every tile of a world is, as the viewer sees it, a number of ticks
+   * after this map was made. This saves predicting the world's blasts all over again, at the cost
+   * of treating bombs that weren't there before as if nothing could set them off early.
+   */
+  pub fn after(&self, ticks: u32, world: &dyn World, viewer: &PlayerId) -> DangerMap {
+    let mut danger: DangerMap = DangerMap {
+      tiles: self.tiles.map(|span| {
+        return span.filter(|(_, until)| *until >= ticks)
+          .map(|(from, until)| (from.saturating_sub(ticks), until - ticks));
+      }),
+      spread_rate: self.spread_rate,
+      blast_lifetime: self.blast_lifetime
+    };
+    for bomb in world.get_bombs() {
+      let detonation: u32 = match *bomb.get_remote() && bomb.get_owner() != viewer {
+        true => 1,
+        false => (*bomb.get_lifetime()).max(1) as u32
+      };
+      danger.add_bomb(
+        world.get_stage(), bomb.get_position(), *bomb.get_range(), *bomb.get_piercing(), detonation
+      );
+    }
+    return danger;
+  }
+
+  // Widens a tile's danger to include a span of ticks. Tiles off the stage are ignored.
+  fn mark(&mut self, tile: &(i8, i8), from: u32, until: u32) {
+    if tile.0 < 0 || tile.1 < 0 {
+      return;
+    }
+    if let Some(danger) = self.tiles.get_mut([tile.1 as usize, tile.0 as usize]) {
+      *danger = match danger {
+        Some((old_from, old_until)) => Some(((*old_from).min(from), (*old_until).max(until))),
+        None => Some((from, until))
+      };
+    }
+  }
+
+  /* Adds a bomb that goes off after a number of ticks, which is lethal from then until its blast
+   * has finished spreading and died down.
+   */
+  pub fn add_bomb(
+    &mut self, stage: &dyn Stage, position: &(i8, i8), range: i8, piercing: bool, detonation: u32
+  ) {
+    let reach: Reach = blast_reach(stage, position, range, piercing);
+    let until: u32 = detonation + range.max(1) as u32 * self.spread_rate + self.blast_lifetime;
+    for (tile, distance) in reach.covered.iter() {
+      self.mark(tile, detonation + distance.saturating_sub(1) * self.spread_rate, until);
+    }
+  }
+
+  // Returns when a tile is lethal, if it ever is.
+  pub fn get(&self, tile: &(i8, i8)) -> Option<(u32, u32)> {
+    if tile.0 < 0 || tile.1 < 0 {
+      return None;
+    }
+    return self.tiles.get([tile.1 as usize, tile.0 as usize]).copied().flatten();
+  }
+
+  // Returns whether nothing is ever going to reach a tile.
+  pub fn is_safe(&self, tile: &(i8, i8)) -> bool {
+    return self.get(tile).is_none();
+  }
+
+  // Returns whether a tile is safe to be on for a span of ticks.
+  pub fn is_safe_during(&self, tile: &(i8, i8), from: u32, until: u32) -> bool {
+    match self.get(tile) {
+      Some((danger_from, danger_until)) => return until < danger_from || from > danger_until,
+      None => return true
+    }
+  }
+}
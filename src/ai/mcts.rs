@@ -0,0 +1,474 @@
+use super::{
+  danger::{self, DangerMap},
+  can_escape_bomb, direction_between, escape, explore, is_walkable, ticks_per_tile, Agent, Paths
+};
+use crate::{
+  common::direction::{Direction, CARDINALS},
+  model::{
+    event::WorldEvent,
+    input::{self, InputFrame, PlayerId, PlayerInput},
+    player::Player,
+    prediction::BlastPrediction,
+    rng::Rng,
+    world::World
+  }
+};
+use std::time::{Duration, Instant};
+
+// How many moves deep the tree goes. Past this, moves are picked at random.
+const TREE_DEPTH: usize = 3;
+
+// How many moves each iteration plays out, all told.
+const PLAYOUT_DEPTH: usize = 5;
+
+/* The chance (out of 100) that someone else places a bomb at the start of each move of a playout.
+ * They always set off any remote bombs they have, since that's the worst they could do.
+ */
+const ENEMY_BOMBING: u32 = 10;
+
+// How many ticks of room the bot leaves itself when running past blasts, like a bot's caution.
+const CAUTION: u32 = 4;
+
+// How much the search favours moves it hasn't tried much over moves that have worked out well.
+const EXPLORATION: f32 = 0.7;
+
+/* How much a playout is worth, between 0 for dying and 1. Surviving is worth a base amount, unless
+ * the bot ended up somewhere a blast is going to reach with no way out. Everything else adds to or
+ * takes away from that.
+ */
+const SURVIVED: f32 = 0.4;
+const TRAPPED: f32 = 0.02;
+const IN_DANGER: f32 = 0.3;
+const KILL: f32 = 0.3;
+const TRAPPED_ENEMY: f32 = 0.2;
+// Per enemy standing somewhere a blast is going to reach.
+const THREATENED_ENEMY: f32 = 0.1;
+/* Per enemy who could catch the bot with a bomb placed right where they're standing, which is much
+ * worse if they could set it off straight away.
+ */
+const EXPOSED: f32 = 0.05;
+const EXPOSED_REMOTE: f32 = 0.3;
+const ITEM: f32 = 0.2;
+// Per soft wall the bot's bombs are going to burn, up to MAX_WALLS of them.
+const WALL: f32 = 0.25;
+const MAX_WALLS: usize = 2;
+// Per tile the bot can walk to, up to MAX_ROOM of them.
+const ROOM: f32 = 0.03;
+const MAX_ROOM: usize = 8;
+/* For being near an item, somewhere a bomb would burn a wall (if the bot has a bomb to spare), or
+ * an enemy. These wear off with every tile further away they are, until SEEK_RANGE.
+ */
+const ITEM_SEEK: f32 = 0.1;
+const WALL_SEEK: f32 = 0.08;
+const HUNT: f32 = 0.05;
+const SEEK_RANGE: f32 = 10.0;
+
+// An enum representing what a bot can do for the length of a move.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Move {
+  Wait,
+  Walk(Direction),
+  Bomb,
+  Detonate
+}
+
+impl Move {
+  // Returns what to press on a tick of this move. Buttons are only pressed on the first tick.
+  fn input(&self, first: bool) -> PlayerInput {
+    match self {
+      Move::Wait => return PlayerInput::default(),
+      Move::Walk(direction) => return PlayerInput::new(Some(*direction), false, false, false),
+      Move::Bomb => return PlayerInput::new(None, first, false, false),
+      Move::Detonate => return PlayerInput::new(None, false, first, false)
+    }
+  }
+}
+
+// Returns every move a player could make in a world, leaving out placing bombs if asked to.
+fn moves(world: &dyn World, player: &Player, bombing: bool) -> Vec<Move> {
+  let mut moves: Vec<Move> = vec!(Move::Wait);
+  moves.extend(CARDINALS.iter().map(|direction| Move::Walk(*direction)));
+  if bombing && world.count_bombs(player.get_id()) < *player.get_bombs() as usize {
+    moves.push(Move::Bomb);
+  }
+  let remote: bool = world.get_bombs().iter()
+    .any(|bomb| bomb.get_owner() == player.get_id() && *bomb.get_remote());
+  if bombing && remote {
+    moves.push(Move::Detonate);
+  }
+  return moves;
+}
+
+// Returns a player in a world, if they're still alive.
+fn find_alive(world: &dyn World, id: &PlayerId) -> Option<Player> {
+  return world.get_players().iter()
+    .find(|player| player.get_id() == id && *player.get_alive())
+    .copied();
+}
+
+/* Leaves out the moves that would take a player somewhere a blast is going to reach while they're
+ * there, a number of ticks from now, unless that's all of them.
+ */
+fn safe_moves(
+  world: &dyn World, danger: &DangerMap, player: &Player, options: Vec<Move>, elapsed: u32,
+  ticks: u32
+) -> Vec<Move> {
+  let tile: (i8, i8) = player.get_tile();
+  let safe: Vec<Move> = options.iter()
+    .filter(|option| {
+      let next: (i8, i8) = match option {
+        Move::Walk(direction) if is_walkable(world, &direction.step(&tile)) => {
+          direction.step(&tile)
+        },
+        _ => tile
+      };
+      return danger.is_safe_during(&next, elapsed, elapsed + 2 * ticks);
+    })
+    .copied()
+    .collect();
+  match safe.is_empty() {
+    true => return options,
+    false => return safe
+  }
+}
+
+/* A struct representing a move in the search tree, which is reached by playing every move on the
+ * way to it. Untried is the moves that don't have a child yet, and total is the sum of what every
+ * playout through this move was worth.
+ */
+struct Node {
+  children: Vec<(Move, usize)>,
+  untried: Vec<Move>,
+  visits: u32,
+  total: f32
+}
+
+impl Node {
+  fn new(untried: Vec<Move>) -> Node {
+    return Node {
+      children: vec!(),
+      untried: untried,
+      visits: 0,
+      total: 0.0
+    }
+  }
+
+  // Returns how promising a child of a node with some number of visits is; see EXPLORATION.
+  fn score(&self, parent_visits: u32) -> f32 {
+    let visits: f32 = self.visits.max(1) as f32;
+    return self.total / visits + EXPLORATION * ((parent_visits.max(1) as f32).ln() / visits).sqrt();
+  }
+}
+
+// A struct representing what happened to a bot over a playout.
+#[derive(Default)]
+struct Playout {
+  dead: bool,
+  kills: u32,
+  items: u32
+}
+
+/* A struct representing a computer player that searches ahead, using Monte Carlo tree search.
+ *
+ * Every iteration of the search takes a snapshot of the world and plays out a handful of moves on
+ * it, then scores where it ended up. A move is whatever it takes to walk a tile, and the first few
+ * moves of each playout are picked by working down a tree of the moves that have worked out well,
+ * while the rest are picked at random. Nobody knows what everyone else is going to do, so they
+ * wander about at random too, which means the same moves can play out differently each time. The
+ * bot then goes with whichever first move it searched the most.
+ *
+ * Playouts are scored by how safe and how aggressive they were. Dying is the worst thing that can
+ * happen, followed by ending up somewhere a blast is going to reach with no way out. Catching
+ * enemies, trapping them, picking up items and having bombs out that are going to burn walls all
+ * count in a playout's favour; see SURVIVED.
+ *
+ * Iterations is the most the bot will search on a single tick. Budget is how long it can think
+ * for, if there's a limit, whichever runs out first. Only bots without a budget always play the
+ * same way for the same seed, since otherwise how far they get depends on how fast the machine is.
+ * Bots only search once their last move is done, or when a bomb or blast comes or goes, so on most
+ * ticks they don't have to think at all.
+ *
+ * Plan is what's left of the move the bot is making, a tick at a time. Seen is how many bombs,
+ * blasts and players still alive the bot saw when it last searched.
+ */
+pub struct MctsBot {
+  id: PlayerId,
+  budget: Option<Duration>,
+  iterations: u32,
+  rng: Rng,
+  plan: Vec<PlayerInput>,
+  seen: (usize, usize, usize)
+}
+
+impl MctsBot {
+  pub fn new(id: PlayerId, budget: Option<Duration>, iterations: u32, seed: u64) -> MctsBot {
+    return MctsBot {
+      id: id,
+      budget: budget,
+      iterations: iterations,
+      rng: Rng::new(seed),
+      plan: vec!(),
+      seen: (0, 0, 0)
+    }
+  }
+
+  pub fn get_budget(&self) -> &Option<Duration> {
+    return &self.budget;
+  }
+
+  pub fn get_iterations(&self) -> &u32 {
+    return &self.iterations;
+  }
+
+  /* Plays a move out on a world, with everyone else heading off in a random direction and maybe
+   * placing a bomb; see ENEMY_BOMBING.
+   */
+  fn play(&mut self, world: &mut dyn World, next: Move, ticks: u32, playout: &mut Playout) {
+    let mut frame: InputFrame = input::idle_frame();
+    let id: PlayerId = self.id;
+    for player in world.get_players().iter().filter(|player| player.get_id() != &id) {
+      let choice: u32 = self.rng.below(CARDINALS.len() as u32 + 1);
+      let direction: Option<Direction> = CARDINALS.get(choice as usize).copied();
+      let bombing: bool = self.rng.below(100) < ENEMY_BOMBING;
+      frame[*player.get_id() as usize] = PlayerInput::new(direction, bombing, true, false);
+    }
+    for tick in 0..ticks {
+      if tick == 1 {
+        frame = input::held_frame(&frame);
+      }
+      frame[self.id as usize] = next.input(tick == 0);
+      for event in world.tick_mut(&frame) {
+        match event {
+          WorldEvent::PlayerKilled { id, .. } if id == self.id => playout.dead = true,
+          WorldEvent::PlayerKilled { killer, .. } if killer == self.id => playout.kills += 1,
+          WorldEvent::ItemCollected { id, .. } if id == self.id => playout.items += 1,
+          _ => {}
+        }
+      }
+      if playout.dead {
+        return;
+      }
+    }
+  }
+
+  /* Returns what a playout that ended up in a world was worth; see SURVIVED. Danger is what was on
+   * its way when the search started, which was a number of ticks ago; see DangerMap::after.
+   */
+  fn evaluate(
+    &self, world: &dyn World, danger: &DangerMap, elapsed: u32, playout: &Playout
+  ) -> f32 {
+    let player: Player = match find_alive(world, &self.id) {
+      Some(player) if !playout.dead => player,
+      _ => return 0.0
+    };
+    let danger: DangerMap = danger.after(elapsed, world, &self.id);
+    let trapped = |player: &Player| -> bool {
+      let tile: (i8, i8) = player.get_tile();
+      return !danger.is_safe(&tile) && escape(world, &danger, player, &tile, 0).is_none();
+    };
+    if trapped(&player) {
+      return TRAPPED;
+    }
+
+    let mut value: f32 = SURVIVED + KILL * playout.kills as f32 + ITEM * playout.items as f32;
+    let tile: (i8, i8) = player.get_tile();
+    if let Some((from, _)) = danger.get(&tile) {
+      let fuse: f32 = *world.get_rules().get_bomb_fuse() as f32;
+      value -= IN_DANGER * (1.0 - from as f32 / fuse).max(0.0);
+    }
+    let enemies: Vec<&Player> = world.get_players().iter()
+      .filter(|other| *other.get_alive() && other.get_id() != &self.id)
+      .collect();
+    value += TRAPPED_ENEMY * enemies.iter().filter(|enemy| trapped(enemy)).count() as f32;
+    value += THREATENED_ENEMY
+      * enemies.iter().filter(|enemy| !danger.is_safe(&enemy.get_tile())).count() as f32;
+    for enemy in enemies.iter() {
+      let exposed: bool = world.count_bombs(enemy.get_id()) < *enemy.get_bombs() as usize
+        && danger::blast_reach(
+          world.get_stage(), &enemy.get_tile(), *enemy.get_range(), *enemy.get_piercing()
+        ).get_covered().iter().any(|(covered, _)| covered == &tile);
+      if exposed && *enemy.get_abilities().get_remote_control() {
+        value -= EXPOSED_REMOTE;
+      } else if exposed {
+        value -= EXPOSED;
+      }
+    }
+    let walls: usize = world.get_bombs().iter()
+      .filter(|bomb| bomb.get_owner() == &self.id)
+      .map(|bomb| {
+        return danger::blast_reach(
+          world.get_stage(), bomb.get_position(), *bomb.get_range(), *bomb.get_piercing()
+        ).get_burned().len();
+      })
+      .sum();
+    value += WALL * walls.min(MAX_WALLS) as f32;
+
+    let room: Paths = explore(world, &tile, &|_, _| true, &|_| false);
+    value += ROOM * room.tiles.len().min(MAX_ROOM) as f32;
+    let closeness = |distance: u32| -> f32 {
+      return (1.0 - distance as f32 / SEEK_RANGE).max(0.0);
+    };
+    let seek = |target: &dyn Fn(&(i8, i8)) -> bool| -> f32 {
+      return room.tiles.iter()
+        .find(|(tile, _)| target(tile))
+        .map_or(0.0, |(_, distance)| closeness(*distance));
+    };
+    value += ITEM_SEEK * seek(&|tile| world.get_items().iter().any(|(item, _)| item == tile));
+    if world.count_bombs(&self.id) < *player.get_bombs() as usize {
+      value += WALL_SEEK * seek(&|tile| {
+        return !danger::blast_reach(
+          world.get_stage(), tile, *player.get_range(), *player.get_piercing()
+        ).get_burned().is_empty();
+      });
+    }
+    let closest: Option<u32> = enemies.iter()
+      .map(|enemy| {
+        let other: (i8, i8) = enemy.get_tile();
+        return (other.0 - tile.0).unsigned_abs() as u32 + (other.1 - tile.1).unsigned_abs() as u32;
+      })
+      .min();
+    value += HUNT * closest.map_or(0.0, closeness);
+    return value.clamp(0.0, 1.0);
+  }
+
+  /* Runs an iteration of the search: works down the tree to a move that hasn't been tried yet,
+   * adds it, plays out the rest of the moves at random, and adds what that was worth to every move
+   * on the way. Danger is what's on its way right now, which rules out walking into blasts anyone
+   * could see coming; see safe_moves.
+   */
+  fn iterate(
+    &mut self, world: &dyn World, danger: &DangerMap, nodes: &mut Vec<Node>, ticks: u32
+  ) {
+    let mut future: Box<dyn World> = world.snapshot();
+    let mut playout: Playout = Playout::default();
+    let mut path: Vec<usize> = vec!(0);
+    while path.len() <= TREE_DEPTH && !playout.dead {
+      let node: usize = path[path.len() - 1];
+      if !nodes[node].untried.is_empty() {
+        let index: usize = self.rng.below(nodes[node].untried.len() as u32) as usize;
+        let next: Move = nodes[node].untried.swap_remove(index);
+        self.play(future.as_mut(), next, ticks, &mut playout);
+        let untried: Vec<Move> = match find_alive(future.as_ref(), &self.id) {
+          Some(player) if path.len() < TREE_DEPTH => {
+            let options: Vec<Move> = moves(future.as_ref(), &player, true);
+            safe_moves(future.as_ref(), danger, &player, options, path.len() as u32 * ticks, ticks)
+          },
+          _ => vec!()
+        };
+        nodes.push(Node::new(untried));
+        let child: usize = nodes.len() - 1;
+        nodes[node].children.push((next, child));
+        path.push(child);
+        break;
+      }
+      let visits: u32 = nodes[node].visits;
+      let best: Option<(Move, usize)> = nodes[node].children.iter()
+        .max_by(|(_, a), (_, b)| nodes[*a].score(visits).total_cmp(&nodes[*b].score(visits)))
+        .copied();
+      match best {
+        Some((next, child)) => {
+          self.play(future.as_mut(), next, ticks, &mut playout);
+          path.push(child);
+        },
+        None => break
+      }
+    }
+    // Random bombs would mostly just be suicide, so playouts finish off by walking around.
+    for played in path.len() - 1..PLAYOUT_DEPTH {
+      let player: Player = match find_alive(future.as_ref(), &self.id) {
+        Some(player) if !playout.dead => player,
+        _ => break
+      };
+      let options: Vec<Move> = safe_moves(
+        future.as_ref(), danger, &player, moves(future.as_ref(), &player, false),
+        played as u32 * ticks, ticks
+      );
+      let next: Move = options[self.rng.below(options.len() as u32) as usize];
+      self.play(future.as_mut(), next, ticks, &mut playout);
+    }
+    let elapsed: u32 = *future.get_time() - *world.get_time();
+    let value: f32 = self.evaluate(future.as_ref(), danger, elapsed, &playout);
+    for node in path {
+      nodes[node].visits += 1;
+      nodes[node].total += value;
+    }
+  }
+
+  /* Searches for the best move a player can make, until the iterations or budget run out. Moves
+   * the player couldn't get away from anything after, going by what's on its way right now, aren't
+   * worth searching.
+   */
+  fn search(&mut self, world: &dyn World, danger: &DangerMap, player: &Player, ticks: u32) -> Move {
+    let start: Instant = Instant::now();
+    let tile: (i8, i8) = player.get_tile();
+    let mut options: Vec<Move> = moves(world, player, true);
+    if !can_escape_bomb(world, danger, player, &tile, CAUTION) {
+      options.retain(|option| option != &Move::Bomb);
+    }
+    let safe: Vec<Move> = options.iter()
+      .filter(|option| {
+        let next: (i8, i8) = match option {
+          Move::Walk(direction) if is_walkable(world, &direction.step(&tile)) => {
+            direction.step(&tile)
+          },
+          _ => tile
+        };
+        return danger.is_safe(&next) || escape(world, danger, player, &next, CAUTION).is_some();
+      })
+      .copied()
+      .collect();
+    if !safe.is_empty() {
+      options = safe;
+    }
+    let mut nodes: Vec<Node> = vec!(Node::new(options));
+    for iteration in 0..self.iterations.max(1) {
+      if iteration > 0 && self.budget.is_some_and(|budget| start.elapsed() >= budget) {
+        break;
+      }
+      self.iterate(world, danger, &mut nodes, ticks);
+    }
+    return nodes[0].children.iter()
+      .max_by_key(|(_, child)| nodes[*child].visits)
+      .map_or(Move::Wait, |(next, _)| *next);
+  }
+}
+
+impl Agent for MctsBot {
+  fn think(&mut self, world: &dyn World, prediction: &BlastPrediction) -> PlayerInput {
+    let player: Player = match find_alive(world, &self.id) {
+      Some(player) => player,
+      None => return PlayerInput::default()
+    };
+    let tile: (i8, i8) = player.get_tile();
+    let danger: DangerMap = DangerMap::new(world, prediction, &self.id);
+    // Getting out of the way of a blast comes before anything else, like it does for bots.
+    if !danger.is_safe(&tile) {
+      if let Some(path) = escape(world, &danger, &player, &tile, CAUTION) {
+        self.plan.clear();
+        let direction: Option<Direction> =
+          path.first().and_then(|next| direction_between(&tile, next));
+        return PlayerInput::new(direction, false, false, false);
+      }
+    }
+
+    let alive: usize = world.get_players().iter().filter(|other| *other.get_alive()).count();
+    let seen: (usize, usize, usize) = (world.get_bombs().len(), world.get_blasts().len(), alive);
+    if self.plan.is_empty() || seen != self.seen {
+      self.seen = seen;
+      let ticks: u32 = ticks_per_tile(&player);
+      let next: Move = self.search(world, &danger, &player, ticks);
+      self.plan = (0..ticks).map(|tick| next.input(tick == 0)).collect();
+    }
+    let input: PlayerInput = self.plan.remove(0);
+    // Remote bombs are set off as soon as the bot is out of their way, like they are by bots.
+    let detonate: bool = danger.is_safe(&tile) && world.get_bombs().iter()
+      .any(|bomb| bomb.get_owner() == &self.id && *bomb.get_remote());
+    return PlayerInput::new(
+      *input.get_direction(), *input.get_place_bomb(), *input.get_detonate() || detonate, false
+    );
+  }
+
+  fn get_id(&self) -> &PlayerId {
+    return &self.id;
+  }
+}
@@ -0,0 +1,538 @@
+use crate::{
+  common::direction::{Direction, CARDINALS},
+  model::{
+    input::{InputFrame, PlayerId, PlayerInput},
+    player::Player,
+    position::SUBTILE,
+    prediction::BlastPrediction,
+    rng::Rng,
+    stage::Tile,
+    timestep::TICK_RATE,
+    world::World
+  }
+};
+use danger::{DangerMap, Reach};
+use mcts::MctsBot;
+use std::time::Duration;
+
+pub mod danger;
+pub mod mcts;
+
+// How much a bot wants to stand somewhere its bomb would burn a soft wall.
+const WALL_VALUE: i32 = 5;
+
+// How many ticks ahead bots look for blasts, which is well past any bomb's fuse.
+const HORIZON: u32 = 10 * TICK_RATE;
+
+/* The most expert bots will search on a single tick, and how long they can think for when someone
+ * is waiting on them; see MctsBot and Bots::interactive.
+ */
+const EXPERT_ITERATIONS: u32 = 400;
+const EXPERT_BUDGET: Duration = Duration::from_millis(4);
+
+/* An enum representing how well a bot plays; see BotParams. Expert bots search ahead instead of
+ * following any params; see MctsBot.
+ */
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Difficulty {
+  Easy,
+  Normal,
+  Hard,
+  Expert
+}
+
+pub const DIFFICULTIES: [Difficulty; 4] =
+  [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard, Difficulty::Expert];
+
+/* A struct representing how well a bot plays.
+ *
+ * Reaction is how many ticks a bot sticks to a plan before looking at the world again, which is
+ * also how long it takes to notice a bomb.
+ * Caution is how many ticks of room a bot leaves itself when running past blasts.
+ * Aggression is the chance (out of 100) that a bot bombs an enemy when it gets the chance.
+ * Item value and enemy value are how much a bot wants to go for items and enemies, next to soft
+ * walls; see WALL_VALUE. Each tile of walking it takes to get somewhere takes 1 off.
+ * Hunt is how much a bot wants to be right next to an enemy, which wears off with every tile
+ * further away it is.
+ * Wander is the chance (out of 100) that a bot just walks somewhere at random instead.
+ */
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct BotParams {
+  reaction: u32,
+  caution: u32,
+  aggression: u32,
+  item_value: i32,
+  enemy_value: i32,
+  hunt: i32,
+  wander: u32
+}
+
+impl Difficulty {
+  pub fn params(&self) -> BotParams {
+    match self {
+      Difficulty::Easy => return BotParams::new(20, 0, 25, 4, 2, 0, 30),
+      Difficulty::Normal => return BotParams::new(8, 4, 60, 8, 6, 2, 10),
+      Difficulty::Hard | Difficulty::Expert => return BotParams::new(1, 4, 100, 10, 10, 3, 0)
+    }
+  }
+}
+
+impl BotParams {
+  pub fn new(
+    reaction: u32, caution: u32, aggression: u32, item_value: i32, enemy_value: i32, hunt: i32,
+    wander: u32
+  ) -> BotParams {
+    return BotParams {
+      reaction: reaction,
+      caution: caution,
+      aggression: aggression,
+      item_value: item_value,
+      enemy_value: enemy_value,
+      hunt: hunt,
+      wander: wander
+    }
+  }
+
+  pub fn get_reaction(&self) -> &u32 {
+    return &self.reaction;
+  }
+
+  pub fn get_caution(&self) -> &u32 {
+    return &self.caution;
+  }
+
+  pub fn get_aggression(&self) -> &u32 {
+    return &self.aggression;
+  }
+
+  pub fn get_item_value(&self) -> &i32 {
+    return &self.item_value;
+  }
+
+  pub fn get_enemy_value(&self) -> &i32 {
+    return &self.enemy_value;
+  }
+
+  pub fn get_hunt(&self) -> &i32 {
+    return &self.hunt;
+  }
+
+  pub fn get_wander(&self) -> &u32 {
+    return &self.wander;
+  }
+}
+
+// Returns whether players can walk onto a tile, going by what's on it right now.
+fn is_walkable(world: &dyn World, tile: &(i8, i8)) -> bool {
+  return world.get_stage().get_tile(tile) == Ok(Tile::Ground)
+    && !world.get_bombs().iter().any(|bomb| bomb.get_position() == tile);
+}
+
+// Returns how many ticks it takes a player to walk from one tile to the next.
+fn ticks_per_tile(player: &Player) -> u32 {
+  let speed: i32 = (*player.get_speed()).max(1);
+  return ((SUBTILE + speed - 1) / speed) as u32;
+}
+
+// Returns the way to walk to get from one tile to the one next to it.
+fn direction_between(from: &(i8, i8), to: &(i8, i8)) -> Option<Direction> {
+  return Direction::from_axes(to.0 - from.0, to.1 - from.1);
+}
+
+/* A struct representing how to walk to every tile a search reached; see explore.
+ * Tiles are in the order they were reached, so nearer tiles always come first.
+ */
+struct Paths {
+  width: usize,
+  previous: Vec<Option<(i8, i8)>>,
+  tiles: Vec<((i8, i8), u32)>
+}
+
+impl Paths {
+  // Returns the tiles to walk along to get to a tile, not including where the search started.
+  fn to(&self, tile: &(i8, i8)) -> Vec<(i8, i8)> {
+    let mut path: Vec<(i8, i8)> = vec!();
+    let mut current: (i8, i8) = *tile;
+    while let Some(before) = self.previous[current.1 as usize * self.width + current.0 as usize] {
+      path.push(current);
+      current = before;
+    }
+    path.reverse();
+    return path;
+  }
+}
+
+/* Searches outwards from a tile, one step at a time, for every tile that can be walked to.
+ * Can enter decides whether a tile can be stepped onto after some number of steps, and the search
+ * stops early at the first tile that's a goal.
+ */
+fn explore(
+  world: &dyn World, start: &(i8, i8), can_enter: &dyn Fn(&(i8, i8), u32) -> bool,
+  goal: &dyn Fn(&(i8, i8)) -> bool
+) -> Paths {
+  let dimensions: &(i8, i8) = world.get_stage().get_dimensions();
+  let width: usize = dimensions.0 as usize;
+  let mut paths: Paths = Paths {
+    width: width,
+    previous: vec!(None; width * dimensions.1 as usize),
+    tiles: vec!((*start, 0))
+  };
+  let mut visited: Vec<bool> = vec!(false; paths.previous.len());
+  visited[start.1 as usize * width + start.0 as usize] = true;
+  let mut next_index: usize = 0;
+  while let Some((tile, steps)) = paths.tiles.get(next_index).copied() {
+    next_index += 1;
+    if goal(&tile) {
+      break;
+    }
+    for direction in CARDINALS.iter() {
+      let next: (i8, i8) = direction.step(&tile);
+      if next.0 < 0 || next.1 < 0 || next.0 >= dimensions.0 || next.1 >= dimensions.1 {
+        continue;
+      }
+      let index: usize = next.1 as usize * width + next.0 as usize;
+      if visited[index] || !is_walkable(world, &next) || !can_enter(&next, steps + 1) {
+        continue;
+      }
+      visited[index] = true;
+      paths.previous[index] = Some(tile);
+      paths.tiles.push((next, steps + 1));
+    }
+  }
+  return paths;
+}
+
+/* Finds the closest tile to a player's that nothing is going to reach, without walking through any
+ * blasts on the way there. From is where the player should be taken to be, if not their own tile,
+ * and caution is how many ticks of room to leave when running past blasts.
+ */
+fn escape(
+  world: &dyn World, danger: &DangerMap, player: &Player, from: &(i8, i8), caution: u32
+) -> Option<Vec<(i8, i8)>> {
+  let step: u32 = ticks_per_tile(player);
+  let can_enter = |tile: &(i8, i8), steps: u32| -> bool {
+    return danger.is_safe_during(tile, (steps - 1) * step, (steps + 1) * step + caution);
+  };
+  let goal = |tile: &(i8, i8)| -> bool {
+    return danger.is_safe(tile);
+  };
+  let paths: Paths = explore(world, from, &can_enter, &goal);
+  return paths.tiles.iter().find(|(tile, _)| goal(tile)).map(|(tile, _)| paths.to(tile));
+}
+
+// Returns whether a player placing a bomb on a tile would leave them a way out.
+fn can_escape_bomb(
+  world: &dyn World, danger: &DangerMap, player: &Player, tile: &(i8, i8), caution: u32
+) -> bool {
+  let mut danger: DangerMap = danger.clone();
+  let fuse: u32 = *world.get_rules().get_bomb_fuse() as u32;
+  danger.add_bomb(world.get_stage(), tile, *player.get_range(), *player.get_piercing(), fuse);
+  return escape(world, &danger, player, tile, caution).is_some();
+}
+
+/* A trait representing anything that plays as a player, by looking at the world each tick and
+ * deciding what to press. Along with the world, agents are given a prediction of its blasts, which
+ * is the same for all of them, so it only has to be worked out once a tick.
+ */
+pub trait Agent: Send {
+  fn think(&mut self, world: &dyn World, prediction: &BlastPrediction) -> PlayerInput;
+
+  // Returns the player this agent plays as.
+  fn get_id(&self) -> &PlayerId;
+}
+
+/* A struct representing a computer player. Bots play through the same inputs people do, by
+ * looking at the world each tick and deciding what to press, so a world can't tell them apart.
+ *
+ * ID is the player the bot plays as.
+ * Rng is the bot's own, for making choices that aren't clear cut. Bots with the same seed and the
+ * same world always play the same way.
+ * Path is the tiles the bot is walking along, and bombing is whether it wants to place a bomb once
+ * it gets to the end of them.
+ * Wait is how many ticks are left until the bot looks at the world again; see BotParams.
+ */
+pub struct Bot {
+  id: PlayerId,
+  params: BotParams,
+  rng: Rng,
+  path: Vec<(i8, i8)>,
+  bombing: bool,
+  wait: u32
+}
+
+impl Bot {
+  pub fn new(id: PlayerId, params: BotParams, seed: u64) -> Bot {
+    return Bot {
+      id: id,
+      params: params,
+      rng: Rng::new(seed),
+      path: vec!(),
+      bombing: false,
+      wait: 0
+    }
+  }
+
+  pub fn get_params(&self) -> &BotParams {
+    return &self.params;
+  }
+
+  /* Returns how much the bot wants to be on a tile, if at all, and whether it'd bomb it. Bots only
+   * ever want to bomb somewhere they could get away from.
+   */
+  fn value(
+    &mut self, world: &dyn World, danger: &DangerMap, player: &Player, tile: &(i8, i8)
+  ) -> (i32, bool) {
+    let mut value: i32 = 0;
+    if world.get_items().iter().any(|(position, _)| position == tile) {
+      value += self.params.item_value;
+    }
+    // Bots that like going after enemies are drawn towards whichever is closest.
+    let closest: Option<i32> = world.get_players().iter()
+      .filter(|other| *other.get_alive() && other.get_id() != player.get_id())
+      .map(|other| {
+        let other: (i8, i8) = other.get_tile();
+        return (other.0 - tile.0).abs() as i32 + (other.1 - tile.1).abs() as i32;
+      })
+      .min();
+    if let Some(closest) = closest {
+      value += (self.params.hunt - closest).max(0);
+    }
+    if world.count_bombs(player.get_id()) >= *player.get_bombs() as usize {
+      return (value, false);
+    }
+    let reach: Reach =
+      danger::blast_reach(world.get_stage(), tile, *player.get_range(), *player.get_piercing());
+    let mut bomb_value: i32 = 0;
+    if !reach.get_burned().is_empty() {
+      bomb_value += WALL_VALUE;
+    }
+    let enemies: bool = world.get_players().iter()
+      .filter(|other| *other.get_alive() && other.get_id() != player.get_id())
+      .any(|other| reach.get_covered().iter().any(|(covered, _)| covered == &other.get_tile()));
+    if enemies && self.rng.below(100) < self.params.aggression {
+      bomb_value += self.params.enemy_value;
+    }
+    if bomb_value == 0 || !can_escape_bomb(world, danger, player, tile, self.params.caution) {
+      return (value, false);
+    }
+    return (value + bomb_value, true);
+  }
+
+  // Works out what to do next: get out of the way of anything dangerous, or go find something.
+  fn plan(&mut self, world: &dyn World, danger: &DangerMap, player: &Player) {
+    self.path.clear();
+    self.bombing = false;
+    let start: (i8, i8) = player.get_tile();
+    if !danger.is_safe(&start) {
+      if let Some(path) = escape(world, danger, player, &start, self.params.caution) {
+        self.path = path;
+      }
+      return;
+    }
+
+    // Tiles are worth however much the bot wants them, less how far away they are.
+    let paths: Paths = explore(world, &start, &|tile, _| danger.is_safe(tile), &|_| false);
+    let mut best: Option<((i8, i8), i32, bool)> = None;
+    for (tile, distance) in paths.tiles.iter() {
+      let (value, bombing): (i32, bool) = self.value(world, danger, player, tile);
+      if value <= 0 {
+        continue;
+      }
+      let score: i32 = value - *distance as i32 + self.rng.below(2) as i32;
+      if best.is_none_or(|(_, best_score, _)| score > best_score) {
+        best = Some((*tile, score, bombing));
+      }
+    }
+    if paths.tiles.len() > 1 && self.rng.below(100) < self.params.wander {
+      let index: usize = 1 + self.rng.below(paths.tiles.len() as u32 - 1) as usize;
+      best = Some((paths.tiles[index].0, 0, false));
+    }
+    if let Some((tile, _, bombing)) = best {
+      self.path = paths.to(&tile);
+      self.bombing = bombing;
+    }
+  }
+}
+
+impl Agent for Bot {
+  fn think(&mut self, world: &dyn World, prediction: &BlastPrediction) -> PlayerInput {
+    let found: Option<&Player> =
+      world.get_players().iter().find(|player| player.get_id() == &self.id);
+    let player: Player = match found {
+      Some(player) if *player.get_alive() => *player,
+      _ => return PlayerInput::default()
+    };
+    let tile: (i8, i8) = player.get_tile();
+    let danger: DangerMap = DangerMap::new(world, prediction, &self.id);
+    if self.path.first() == Some(&tile) {
+      self.path.remove(0);
+    }
+    // Plans go stale, and bots can be knocked off course, so they keep looking at the world again.
+    let off_course: bool = self.path.first()
+      .is_some_and(|next| (next.0 - tile.0).abs() + (next.1 - tile.1).abs() != 1);
+    self.wait = self.wait.saturating_sub(1);
+    if self.wait == 0 || off_course {
+      self.plan(world, &danger, &player);
+      self.wait = self.params.reaction;
+    }
+
+    let mut place_bomb: bool = false;
+    if self.path.is_empty() && self.bombing {
+      self.bombing = false;
+      place_bomb = can_escape_bomb(world, &danger, &player, &tile, self.params.caution);
+      // Once a bomb is down, the first thing to do is get away from it.
+      self.wait = 1;
+    }
+    // Remote bombs are set off as soon as the bot is out of their way.
+    let detonate: bool = danger.is_safe(&tile) && world.get_bombs().iter()
+      .any(|bomb| bomb.get_owner() == &self.id && *bomb.get_remote());
+    let direction: Option<Direction> =
+      self.path.first().and_then(|next| direction_between(&tile, next));
+    return PlayerInput::new(direction, place_bomb, detonate, false);
+  }
+
+  fn get_id(&self) -> &PlayerId {
+    return &self.id;
+  }
+}
+
+/* A struct representing every bot in a match, which fills in their inputs each tick.
+ * Anything else driving the world (like a keyboard) fills in everyone else's.
+ */
+pub struct Bots {
+  bots: Vec<Box<dyn Agent>>
+}
+
+impl Bots {
+  /* Makes a bot for each player ID given, at its difficulty. Each bot gets its own seed from the
+   * one given, so they don't all make the same choices, and bots with the same seed always play
+   * the same way.
+   */
+  pub fn new(players: &[(PlayerId, Difficulty)], seed: u64) -> Bots {
+    return Bots::with_budget(players, seed, None);
+  }
+
+  /* Makes bots for a match people are playing, where expert bots have to keep up with the frame
+   * rate, so they stop searching once they run out of time. This means they can play differently
+   * from one machine to the next; see Bots::new.
+   */
+  pub fn interactive(players: &[(PlayerId, Difficulty)], seed: u64) -> Bots {
+    return Bots::with_budget(players, seed, Some(EXPERT_BUDGET));
+  }
+
+  fn with_budget(players: &[(PlayerId, Difficulty)], seed: u64, budget: Option<Duration>) -> Bots {
+    let mut rng: Rng = Rng::new(seed);
+    return Bots {
+      bots: players.iter()
+        .map(|(id, difficulty)| -> Box<dyn Agent> {
+          match difficulty {
+            Difficulty::Expert => {
+              return Box::new(MctsBot::new(*id, budget, EXPERT_ITERATIONS, rng.next_u64()));
+            },
+            _ => return Box::new(Bot::new(*id, difficulty.params(), rng.next_u64()))
+          }
+        })
+        .collect()
+    }
+  }
+
+  pub fn get_bots(&self) -> &Vec<Box<dyn Agent>> {
+    return &self.bots;
+  }
+
+  // Fills in what every bot presses for the next tick of a world.
+  pub fn fill(&mut self, world: &dyn World, frame: &mut InputFrame) {
+    if self.bots.is_empty() {
+      return;
+    }
+    let prediction: BlastPrediction = world.predict_blasts(HORIZON);
+    for bot in self.bots.iter_mut() {
+      frame[*bot.get_id() as usize] = bot.think(world, &prediction);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::model::{
+    event::WorldEvent,
+    input,
+    rules::Rules,
+    stage::{StageImpl, STAGES},
+    world::WorldImpl
+  };
+
+  /* Plays a bot as player 0 on a stage layout for a number of ticks, while player 1 just places a
+   * bomb if asked to on the first tick. Returns the world and everything that happened.
+   */
+  fn play(
+    layout: &str, difficulty: Difficulty, other_bombs: bool, ticks: u32
+  ) -> (WorldImpl, Vec<WorldEvent>) {
+    let stage: StageImpl = StageImpl::from_layout(layout).unwrap();
+    let mut world: WorldImpl = WorldImpl::start(Rules::default(), Box::new(stage), 2, 0);
+    let mut bots: Bots = Bots::new(&[(0, difficulty)], 0);
+    let mut events: Vec<WorldEvent> = vec!();
+    for tick in 0..ticks {
+      let mut frame: InputFrame = input::idle_frame();
+      frame[1] = PlayerInput::new(None, other_bombs && tick == 0, false, false);
+      bots.fill(&world, &mut frame);
+      events.extend(world.tick_mut(&frame));
+    }
+    return (world, events);
+  }
+
+  // Plays the start of a round between expert bots, returning everything they pressed.
+  fn play_experts(seed: u64) -> Vec<InputFrame> {
+    let stage: StageImpl = StageImpl::from_layout(STAGES[0].1).unwrap();
+    let mut world: WorldImpl = WorldImpl::start(Rules::default(), Box::new(stage), 2, seed);
+    let mut bots: Bots = Bots::new(&[(0, Difficulty::Expert), (1, Difficulty::Expert)], seed);
+    let mut frames: Vec<InputFrame> = vec!();
+    for _ in 0..2 * TICK_RATE {
+      let mut frame: InputFrame = input::idle_frame();
+      bots.fill(&world, &mut frame);
+      world.tick_mut(&frame);
+      frames.push(frame);
+    }
+    return frames;
+  }
+
+  #[test]
+  fn bots_get_out_of_the_way_of_blasts() {
+    let layout: &str = "\
+.....
+.01..
+.....";
+    let fuse: u32 = *Rules::default().get_bomb_fuse() as u32;
+    for difficulty in DIFFICULTIES.iter() {
+      let (world, _): (WorldImpl, Vec<WorldEvent>) = play(layout, *difficulty, true, fuse + 10);
+      assert!(*world.get_players()[0].get_alive(), "{:?} bot was caught", difficulty);
+    }
+  }
+
+  #[test]
+  fn bots_only_bomb_where_they_can_get_away() {
+    let trapped: &str = "\
+...#...
+#####..
+0+###.1";
+    let open: &str = "\
+...#...
+.####..
+0+###.1";
+    for difficulty in DIFFICULTIES.iter() {
+      let (world, events): (WorldImpl, Vec<WorldEvent>) = play(trapped, *difficulty, false, 600);
+      assert!(!events.iter().any(|event| matches!(event, WorldEvent::BombPlaced { .. })));
+      assert!(*world.get_players()[0].get_alive());
+      // With a way out, the same soft wall is worth bombing.
+      let (world, events): (WorldImpl, Vec<WorldEvent>) = play(open, *difficulty, false, 600);
+      assert!(events.iter().any(|event| matches!(event, WorldEvent::BombPlaced { .. })));
+      assert!(*world.get_players()[0].get_alive(), "{:?} bot was caught", difficulty);
+    }
+  }
+
+  #[test]
+  fn seeded_expert_bots_always_play_the_same_way() {
+    assert_eq!(play_experts(7), play_experts(7));
+  }
+}
@@ -0,0 +1,125 @@
+use crate::audio::{Audio, Channel, Sound, Volumes, MUSIC_DIRECTORY, SOUNDS, SOUNDS_DIRECTORY};
+use sdl2::{
+  mixer::{self, Chunk, Music},
+  AudioSubsystem
+};
+use std::{collections::HashMap, path::Path};
+
+// How many sounds can play at once. Past this, new sounds are dropped.
+const MIXER_CHANNELS: i32 = 16;
+
+// How many samples are mixed at a time. Smaller is less laggy, but more likely to crackle.
+const CHUNK_SIZE: i32 = 1024;
+
+// Turns a volume from 0 to 1 into the mixer's own scale.
+fn mixer_volume(volume: f32) -> i32 {
+  return (volume * mixer::MAX_VOLUME as f32).round() as i32;
+}
+
+/* A struct representing audio played through SDL2_mixer.
+ * Chunks are every sound effect, loaded up front so that playing one never waits on the disk.
+ * Loops are the mixer channels each looping sound is playing on.
+ */
+pub struct MixerAudio {
+  _subsystem: AudioSubsystem,
+  chunks: HashMap<Sound, Chunk>,
+  loops: HashMap<Sound, mixer::Channel>,
+  music: Option<Music<'static>>
+}
+
+impl MixerAudio {
+  pub fn new(subsystem: AudioSubsystem, volumes: &Volumes) -> Result<MixerAudio, String> {
+    mixer::open_audio(
+      mixer::DEFAULT_FREQUENCY, mixer::DEFAULT_FORMAT, mixer::DEFAULT_CHANNELS, CHUNK_SIZE
+    )?;
+    mixer::allocate_channels(MIXER_CHANNELS);
+    let mut chunks: HashMap<Sound, Chunk> = HashMap::new();
+    for sound in SOUNDS.iter() {
+      let path = Path::new(SOUNDS_DIRECTORY).join(sound.file_name());
+      let chunk: Chunk = Chunk::from_file(&path)
+        .map_err(|e| format!("Couldn't load {}: {}", path.display(), e))?;
+      chunks.insert(*sound, chunk);
+    }
+    let mut audio: MixerAudio = MixerAudio {
+      _subsystem: subsystem,
+      chunks: chunks,
+      loops: HashMap::new(),
+      music: None
+    };
+    audio.set_volumes(volumes);
+    return Ok(audio);
+  }
+
+  fn get_chunk(&self, sound: &Sound) -> Result<&Chunk, String> {
+    return self.chunks.get(sound).ok_or(format!("{} was never loaded.", sound.file_name()));
+  }
+}
+
+impl Audio for MixerAudio {
+  fn play(&mut self, sound: &Sound) -> Result<(), String> {
+    // This only fails when every channel is busy, which isn't worth stopping the game over.
+    let _ = mixer::Channel::all().play(self.get_chunk(sound)?, 0);
+    return Ok(());
+  }
+
+  fn start_loop(&mut self, sound: &Sound) -> Result<(), String> {
+    if self.loops.contains_key(sound) {
+      return Ok(());
+    }
+    if let Ok(channel) = mixer::Channel::all().play(self.get_chunk(sound)?, -1) {
+      self.loops.insert(*sound, channel);
+    }
+    return Ok(());
+  }
+
+  fn stop_loop(&mut self, sound: &Sound) {
+    if let Some(channel) = self.loops.remove(sound) {
+      channel.halt();
+    }
+  }
+
+  fn play_music(&mut self, track: &str) -> Result<(), String> {
+    let path = Path::new(MUSIC_DIRECTORY).join(track);
+    let music: Music<'static> = Music::from_file(&path)
+      .map_err(|e| format!("Couldn't load {}: {}", path.display(), e))?;
+    music.play(-1)?;
+    self.music = Some(music);
+    return Ok(());
+  }
+
+  fn stop_music(&mut self) {
+    Music::halt();
+    self.music = None;
+  }
+
+  fn set_paused(&mut self, paused: bool) {
+    match paused {
+      true => {
+        mixer::Channel::all().pause();
+        Music::pause();
+      },
+      false => {
+        mixer::Channel::all().resume();
+        Music::resume();
+      }
+    }
+  }
+
+  fn set_volumes(&mut self, volumes: &Volumes) {
+    let effects: i32 = mixer_volume(volumes.get_effective(&Channel::Effects));
+    for chunk in self.chunks.values_mut() {
+      chunk.set_volume(effects);
+    }
+    Music::set_volume(mixer_volume(volumes.get_effective(&Channel::Music)));
+  }
+}
+
+impl Drop for MixerAudio {
+  // Everything the mixer loaded has to go before the mixer itself does.
+  fn drop(&mut self) {
+    self.stop_music();
+    self.loops.clear();
+    self.chunks.clear();
+    mixer::close_audio();
+  }
+}
@@ -0,0 +1,223 @@
+use crate::{common::config, model::event::WorldEvent};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[cfg(feature = "audio")]
+pub mod mixer;
+pub mod null;
+
+// Where sound effects are loaded from, by their file names.
+pub const SOUNDS_DIRECTORY: &str = "assets/sounds";
+
+// Where stage music is loaded from. Each stage has a track named after it; see music_track.
+pub const MUSIC_DIRECTORY: &str = "assets/music";
+
+// An enum representing a sound effect.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Sound {
+  BombPlaced,
+  // Loops for as long as any bomb is ticking.
+  Fuse,
+  Explosion,
+  ItemPickup,
+  Death,
+  RoundStart,
+  RoundEnd,
+  SuddenDeath
+}
+
+pub const SOUNDS: [Sound; 8] = [
+  Sound::BombPlaced, Sound::Fuse, Sound::Explosion, Sound::ItemPickup,
+  Sound::Death, Sound::RoundStart, Sound::RoundEnd, Sound::SuddenDeath
+];
+
+impl Sound {
+  pub fn file_name(&self) -> &'static str {
+    match self {
+      Sound::BombPlaced => return "bomb_placed.wav",
+      Sound::Fuse => return "fuse.wav",
+      Sound::Explosion => return "explosion.wav",
+      Sound::ItemPickup => return "item_pickup.wav",
+      Sound::Death => return "death.wav",
+      Sound::RoundStart => return "round_start.wav",
+      Sound::RoundEnd => return "round_end.wav",
+      Sound::SuddenDeath => return "sudden_death.wav"
+    }
+  }
+}
+
+// Returns the file name of a stage's music, going by the stage's name.
+pub fn music_track(stage_name: &str) -> String {
+  return format!("{}.wav", stage_name.to_lowercase());
+}
+
+// An enum representing something with its own volume setting.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Channel {
+  // Scales every other channel.
+  Master,
+  Effects,
+  Music
+}
+
+pub const CHANNELS: [Channel; 3] = [Channel::Master, Channel::Effects, Channel::Music];
+
+impl Channel {
+  pub fn name(&self) -> &'static str {
+    match self {
+      Channel::Master => return "Master",
+      Channel::Effects => return "Effects",
+      Channel::Music => return "Music"
+    }
+  }
+}
+
+/* A struct representing how loud each channel is, from 0 (silent) to 1 (full volume).
+ * These are kept in a TOML or RON file, like the rest of the settings; see config.
+ */
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Volumes {
+  master: f32,
+  effects: f32,
+  music: f32
+}
+
+impl Default for Volumes {
+  fn default() -> Volumes {
+    return Volumes { master: 1.0, effects: 0.8, music: 0.5 };
+  }
+}
+
+impl Volumes {
+  pub fn from_file(path: &Path) -> Result<Volumes, String> {
+    let volumes: Volumes = config::load(path, "volumes")?;
+    for channel in CHANNELS.iter() {
+      let volume: f32 = volumes.get(channel);
+      if !(0.0..=1.0).contains(&volume) {
+        return Err(
+          format!("{} volume needs to be from 0 to 1, but is {}.", channel.name(), volume)
+        );
+      }
+    }
+    return Ok(volumes);
+  }
+
+  pub fn save(&self, path: &Path) -> Result<(), String> {
+    return config::save(self, path, "volumes");
+  }
+
+  pub fn get(&self, channel: &Channel) -> f32 {
+    match channel {
+      Channel::Master => return self.master,
+      Channel::Effects => return self.effects,
+      Channel::Music => return self.music
+    }
+  }
+
+  // Returns how loud a channel actually plays, once the master volume is taken into account.
+  pub fn get_effective(&self, channel: &Channel) -> f32 {
+    match channel {
+      Channel::Master => return self.master,
+      _ => return self.master * self.get(channel)
+    }
+  }
+
+  // Sets a channel's volume, clamped to somewhere from 0 to 1.
+  pub fn set(&self, channel: &Channel, volume: f32) -> Volumes {
+    let volume: f32 = volume.clamp(0.0, 1.0);
+    match channel {
+      Channel::Master => return Volumes { master: volume, ..*self },
+      Channel::Effects => return Volumes { effects: volume, ..*self },
+      Channel::Music => return Volumes { music: volume, ..*self }
+    }
+  }
+}
+
+/* A trait representing something that can play sounds and music.
+ * Playing a sound can fail, but backends should only fail on things that are actually broken (like
+ * missing files), and not on things like having too many sounds playing at once.
+ */
+pub trait Audio {
+  fn play(&mut self, sound: &Sound) -> Result<(), String>;
+
+  /* Plays a sound over and over until it's stopped. Starting a loop that's already going does
+   * nothing.
+   */
+  fn start_loop(&mut self, sound: &Sound) -> Result<(), String>;
+
+  fn stop_loop(&mut self, sound: &Sound);
+
+  // Plays a music track on repeat, in place of whatever was playing before. See music_track.
+  fn play_music(&mut self, track: &str) -> Result<(), String>;
+
+  fn stop_music(&mut self);
+
+  // Pauses or resumes everything, like while a match is paused.
+  fn set_paused(&mut self, paused: bool);
+
+  fn set_volumes(&mut self, volumes: &Volumes);
+}
+
+/* A struct representing what a match sounds like, going by what its world reports each tick.
+ * Bombs is how many bombs are ticking, for keeping the fuse going.
+ */
+#[derive(Default)]
+pub struct SoundCues {
+  bombs: u32
+}
+
+impl SoundCues {
+  pub fn new() -> SoundCues {
+    return SoundCues { bombs: 0 };
+  }
+
+  /* Plays whatever some events call for. Each sound plays at most once per call, so several bombs
+   * going off on the same tick sound like one big explosion instead of getting deafening.
+   */
+  pub fn hear(&mut self, events: &[WorldEvent], audio: &mut dyn Audio) -> Result<(), String> {
+    let mut sounds: Vec<Sound> = vec!();
+    for event in events {
+      let sound: Option<Sound> = match event {
+        WorldEvent::RoundStarted => {
+          // Bombs don't carry over between rounds.
+          self.stop(audio);
+          Some(Sound::RoundStart)
+        },
+        WorldEvent::BombPlaced { .. } => {
+          self.bombs += 1;
+          audio.start_loop(&Sound::Fuse)?;
+          Some(Sound::BombPlaced)
+        },
+        WorldEvent::BombDetonated { .. } => {
+          self.bombs = self.bombs.saturating_sub(1);
+          if self.bombs == 0 {
+            audio.stop_loop(&Sound::Fuse);
+          }
+          Some(Sound::Explosion)
+        },
+        WorldEvent::ItemCollected { .. } => Some(Sound::ItemPickup),
+        WorldEvent::PlayerKilled { .. } => Some(Sound::Death),
+        WorldEvent::SuddenDeath => Some(Sound::SuddenDeath),
+        WorldEvent::RoundEnded { .. } => Some(Sound::RoundEnd),
+        // These are already heard in the explosions that cause them.
+        WorldEvent::FlameSpread { .. }
+        | WorldEvent::TileDestroyed { .. }
+        | WorldEvent::ItemSpawned { .. } => None
+      };
+      if let Some(sound) = sound.filter(|sound| !sounds.contains(sound)) {
+        sounds.push(sound);
+      }
+    }
+    for sound in sounds.iter() {
+      audio.play(sound)?;
+    }
+    return Ok(());
+  }
+
+  // Stops anything that was left going, like when a match is left or started over.
+  pub fn stop(&mut self, audio: &mut dyn Audio) {
+    self.bombs = 0;
+    audio.stop_loop(&Sound::Fuse);
+  }
+}
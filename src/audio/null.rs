@@ -0,0 +1,95 @@
+use crate::audio::{Audio, Sound, Volumes};
+
+// An enum representing something an audio backend was told to do.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Cue {
+  Played(Sound),
+  Looped(Sound),
+  Stopped(Sound),
+  Music(String),
+  MusicStopped
+}
+
+/* A struct representing audio that doesn't play anything, for headless runs and machines without
+ * a sound device. It keeps track of what it would have played instead, so runs can check that the
+ * right sounds went off.
+ *
+ * Loops are the sounds that would be looping right now.
+ */
+#[derive(Default)]
+pub struct NullAudio {
+  cues: Vec<Cue>,
+  loops: Vec<Sound>,
+  paused: bool,
+  volumes: Volumes
+}
+
+impl NullAudio {
+  pub fn new() -> NullAudio {
+    return NullAudio {
+      cues: vec!(),
+      loops: vec!(),
+      paused: false,
+      volumes: Volumes::default()
+    }
+  }
+
+  pub fn get_cues(&self) -> &Vec<Cue> {
+    return &self.cues;
+  }
+
+  pub fn clear_cues(&mut self) {
+    self.cues.clear();
+  }
+
+  pub fn get_loops(&self) -> &Vec<Sound> {
+    return &self.loops;
+  }
+
+  pub fn get_paused(&self) -> &bool {
+    return &self.paused;
+  }
+
+  pub fn get_volumes(&self) -> &Volumes {
+    return &self.volumes;
+  }
+}
+
+impl Audio for NullAudio {
+  fn play(&mut self, sound: &Sound) -> Result<(), String> {
+    self.cues.push(Cue::Played(*sound));
+    return Ok(());
+  }
+
+  fn start_loop(&mut self, sound: &Sound) -> Result<(), String> {
+    if !self.loops.contains(sound) {
+      self.loops.push(*sound);
+      self.cues.push(Cue::Looped(*sound));
+    }
+    return Ok(());
+  }
+
+  fn stop_loop(&mut self, sound: &Sound) {
+    if let Some(index) = self.loops.iter().position(|looping| looping == sound) {
+      self.loops.remove(index);
+      self.cues.push(Cue::Stopped(*sound));
+    }
+  }
+
+  fn play_music(&mut self, track: &str) -> Result<(), String> {
+    self.cues.push(Cue::Music(String::from(track)));
+    return Ok(());
+  }
+
+  fn stop_music(&mut self) {
+    self.cues.push(Cue::MusicStopped);
+  }
+
+  fn set_paused(&mut self, paused: bool) {
+    self.paused = paused;
+  }
+
+  fn set_volumes(&mut self, volumes: &Volumes) {
+    self.volumes = *volumes;
+  }
+}
@@ -0,0 +1,70 @@
+/* Renders scripted scenarios headlessly and compares them against checked-in golden images, so that
+ * anything changing how worlds are drawn gets caught. Nothing here needs SDL or a display.
+ * `cargo test` runs the same comparisons (see tests/golden.rs); this is the tool for looking at
+ * them by hand and re-blessing them.
+ *
+ * Run with no arguments to check every scenario, exiting with an error if any of them differ. The
+ * frames that were actually rendered are written to target/golden for comparison. Run with --bless
+ * to replace the golden images with the current renders, after checking the change was intended.
+ */
+mod scenarios;
+
+use open_bomberman::view::capture::Frame;
+use scenarios::{GOLDEN_DIRECTORY, OUTPUT_DIRECTORY};
+use std::{
+  fs,
+  path::{Path, PathBuf},
+  process
+};
+
+// Checks a rendered scenario against its golden image, returning whether it matched.
+fn check(name: &str, frame: Result<Frame, String>, bless: bool) -> Result<bool, String> {
+  let frame: Frame = frame?;
+  let file_name: String = format!("{}.png", name);
+  let golden_path: PathBuf = Path::new(GOLDEN_DIRECTORY).join(&file_name);
+  if bless {
+    frame.save_png(&golden_path)?;
+    println!("blessed  {}", name);
+    return Ok(true);
+  }
+
+  frame.save_png(&Path::new(OUTPUT_DIRECTORY).join(&file_name))?;
+  let golden: Frame = Frame::load_png(&golden_path)?;
+  match frame.diff(&golden) {
+    Ok(0) => {
+      println!("ok       {}", name);
+      return Ok(true);
+    },
+    Ok(pixels) => println!("FAILED   {} ({} pixels differ)", name, pixels),
+    Err(e) => println!("FAILED   {} ({})", name, e)
+  }
+  return Ok(false);
+}
+
+fn main() {
+  let bless: bool = std::env::args().any(|arg| arg == "--bless");
+  let directory: &str = match bless {
+    true => GOLDEN_DIRECTORY,
+    false => OUTPUT_DIRECTORY
+  };
+  if let Err(e) = fs::create_dir_all(directory) {
+    eprintln!("Couldn't create {}: {}", directory, e);
+    process::exit(2);
+  }
+
+  let mut failures: usize = 0;
+  for (name, frame) in scenarios::render_all() {
+    match check(&name, frame, bless) {
+      Ok(true) => {},
+      Ok(false) => failures += 1,
+      Err(e) => {
+        println!("ERROR    {} ({})", name, e);
+        failures += 1;
+      }
+    }
+  }
+  if failures > 0 {
+    println!("{} scenarios didn't match their golden images; see {}.", failures, OUTPUT_DIRECTORY);
+    process::exit(1);
+  }
+}
@@ -0,0 +1,286 @@
+/* The scripted scenarios the golden images are rendered from, shared by the golden tool and the
+ * golden test.
+ */
+use open_bomberman::{
+  ai::{Bots, Difficulty},
+  common::direction::Direction,
+  menu::{
+    screens::{PauseScreen, TitleScreen},
+    setup::MatchSetup,
+    MenuInput, MenuStack
+  },
+  model::{
+    event::WorldEvent,
+    input::{self, InputFrame, PlayerId, PlayerInput},
+    rules::Rules,
+    score::Scoreboard,
+    stage::StageImpl,
+    world::{Outcome, World, WorldImpl}
+  },
+  view::{
+    capture::Frame,
+    effects::{EffectSettings, Effects},
+    hud,
+    scene::TILE_SIZE,
+    theme::{Theme, THEMES}
+  }
+};
+
+// Where the golden images are kept, and where what was actually rendered gets written.
+pub const GOLDEN_DIRECTORY: &str = "golden";
+pub const OUTPUT_DIRECTORY: &str = "target/golden";
+
+// A number of ticks to run with the same inputs. Buttons are only pressed on the first of them.
+#[derive(Copy, Clone)]
+struct Step {
+  ticks: u32,
+  inputs: InputFrame
+}
+
+/* Theme is the built in theme to draw the scenario with, if any.
+ * Scoreboard is the tally to show in the HUD. Scenarios without one are drawn without the HUD.
+ * Effects are the settings to draw effects with, if any, which only get drawn along with the HUD.
+ * Bots are the players played by bots, which fill in their own inputs on top of the steps'.
+ */
+struct Scenario {
+  name: String,
+  rules: Rules,
+  player_count: usize,
+  steps: Vec<Step>,
+  alpha: f32,
+  theme: Option<&'static str>,
+  scoreboard: Option<Scoreboard>,
+  effects: Option<EffectSettings>,
+  bots: Vec<(PlayerId, Difficulty)>
+}
+
+/* A scenario for the menus, which get driven by a list of inputs from the title screen.
+ * Paused ones are drawn over a match, with inputs after entering the pause menu going to it.
+ */
+struct MenuScenario {
+  name: &'static str,
+  inputs: Vec<MenuInput>,
+  paused: bool
+}
+
+fn idle(ticks: u32) -> Step {
+  return Step { ticks: ticks, inputs: input::idle_frame() };
+}
+
+fn walk(ticks: u32, id: usize, direction: Direction) -> Step {
+  let mut inputs: InputFrame = input::idle_frame();
+  inputs[id] = PlayerInput::new(Some(direction), false, false, false);
+  return Step { ticks: ticks, inputs: inputs };
+}
+
+fn bomb(id: usize) -> Step {
+  let mut inputs: InputFrame = input::idle_frame();
+  inputs[id] = PlayerInput::new(None, true, false, false);
+  return Step { ticks: 1, inputs: inputs };
+}
+
+/* Player 0 places a bomb next to their spawn and tucks into the corner out of its way, which is
+ * the opening most rounds start with.
+ */
+fn escape() -> Vec<Step> {
+  return vec!(
+    walk(10, 0, Direction::East), bomb(0), walk(10, 0, Direction::West),
+    walk(10, 0, Direction::South)
+  );
+}
+
+fn scenario(
+  name: &str, rules: Rules, player_count: usize, steps: Vec<Step>, alpha: f32
+) -> Scenario {
+  return Scenario {
+    name: String::from(name),
+    rules: rules,
+    player_count: player_count,
+    steps: steps,
+    alpha: alpha,
+    theme: None,
+    scoreboard: None,
+    effects: None,
+    bots: vec!()
+  }
+}
+
+fn scenarios() -> Vec<Scenario> {
+  let slow_flames: Rules = Rules::from_toml("flame_spread_rate = 4\n[starting_stats]\nrange = 4")
+    .unwrap();
+  let mut scenarios: Vec<Scenario> = vec!(
+    scenario("spawn", Rules::default(), 8, vec!(), 1.0),
+    scenario(
+      "moving", Rules::default(), 2,
+      vec!(walk(5, 0, Direction::East), walk(5, 1, Direction::West)), 0.5
+    ),
+    scenario("bomb_fuse", Rules::default(), 1, [escape(), vec!(idle(200))].concat(), 1.0),
+    scenario("blast_spreading", slow_flames, 1, [escape(), vec!(idle(280))].concat(), 1.0),
+    scenario("blast_fading", Rules::default(), 1, [escape(), vec!(idle(335))].concat(), 1.0)
+  );
+
+  /* Every theme gets a scenario with every player, a ticking bomb and a spreading blast in it, so
+   * that every kind of sprite shows up at least once.
+   */
+  for theme in THEMES.iter() {
+    let steps: Vec<Step> =
+      [escape(), vec!(walk(4, 1, Direction::West), idle(266), bomb(1), idle(9))].concat();
+    scenarios.push(
+      Scenario {
+        theme: Some(theme),
+        ..scenario(&format!("theme_{}", theme), slow_flames, 8, steps, 0.5)
+      }
+    );
+  }
+
+  /* A piercing bomb burns through a row of soft walls that all drop items, to show them lying where
+   * the walls were, plain and themed.
+   */
+  let drops: Rules = Rules::from_toml(
+    "[starting_stats]\nrange = 6\npiercing = true\n[item_drop_rates]\nchance = 100"
+  ).unwrap();
+  let steps: Vec<Step> = [escape(), vec!(idle(335))].concat();
+  scenarios.push(scenario("items", drops, 1, steps.clone(), 1.0));
+  scenarios.push(
+    Scenario { theme: Some("classic"), ..scenario("items_classic", drops, 1, steps, 1.0) }
+  );
+
+  /* Effects shortly after the row of soft walls is burned away, and shortly after a player blows
+   * themselves up, with the screen still shaking from the bomb going off.
+   */
+  let effects = |name: &str, rules: Rules, player_count: usize, steps: Vec<Step>| -> Scenario {
+    return Scenario {
+      scoreboard: Some(Scoreboard::new()),
+      effects: Some(EffectSettings::default()),
+      ..scenario(name, rules, player_count, steps, 1.0)
+    }
+  };
+  scenarios.push(effects("effects_debris", drops, 1, [escape(), vec!(idle(286))].concat()));
+  scenarios.push(effects("effects_death", Rules::default(), 2, vec!(bomb(1), idle(309))));
+
+  // Bots of every difficulty, partway through a round against each other.
+  scenarios.push(
+    Scenario {
+      scoreboard: Some(Scoreboard::new()),
+      bots: vec!(
+        (0, Difficulty::Easy), (1, Difficulty::Normal), (2, Difficulty::Hard), (3, Difficulty::Hard)
+      ),
+      ..scenario("bots", Rules::default(), 4, vec!(idle(900)), 1.0)
+    }
+  );
+
+  // The HUD, partway through a match, with a player down in one of them and a round won in another.
+  let mut scoreboard: Scoreboard = Scoreboard::new();
+  for outcome in [Outcome::Win(0), Outcome::Draw, Outcome::Win(3), Outcome::Win(0)].iter() {
+    scoreboard.record(outcome);
+  }
+  let sudden_death: Rules =
+    Rules::from_toml("round_time = 3600\nsudden_death_time = 3600").unwrap();
+  scenarios.push(
+    Scenario {
+      scoreboard: Some(scoreboard),
+      ..scenario("hud_two", Rules::default(), 2, escape(), 1.0)
+    }
+  );
+  scenarios.push(
+    Scenario {
+      scoreboard: Some(scoreboard),
+      ..scenario("hud_eight", sudden_death, 8, vec!(bomb(1), idle(305)), 1.0)
+    }
+  );
+  scenarios.push(
+    Scenario {
+      scoreboard: Some(scoreboard),
+      ..scenario("hud_round_over", Rules::default(), 2, vec!(bomb(1), idle(305)), 1.0)
+    }
+  );
+  return scenarios;
+}
+
+fn menu_scenarios() -> Vec<MenuScenario> {
+  use MenuInput::*;
+  let menu = |name: &'static str, inputs: Vec<MenuInput>| -> MenuScenario {
+    return MenuScenario { name: name, inputs: inputs, paused: false };
+  };
+  return vec!(
+    menu("menu_title", vec!()),
+    // A pad and a bot join, and the second keyboard player leaves.
+    menu(
+      "menu_players",
+      vec!(Confirm, Down, Left, Left, Down, Right, Right, Right, Right, Right, Down, Left, Left)
+    ),
+    // Starting is refused while two players are on the same keys.
+    menu("menu_stage_taken", vec!(Confirm, Down, Left, Up, Up, Confirm, Down, Down, Down, Confirm)),
+    menu("menu_stage", vec!(Confirm, Up, Confirm, Right, Down, Right, Right)),
+    menu("menu_rules", vec!(Confirm, Up, Confirm, Down, Down, Confirm, Right, Down, Down, Left)),
+    // Waiting on a new control for the second keyboard player's down.
+    menu("menu_controls", vec!(Down, Confirm, Right, Down, Down, Confirm)),
+    // Effects turned down and music turned up.
+    menu("menu_audio", vec!(Down, Down, Confirm, Down, Left, Left, Down, Right)),
+    // Screen shake turned off.
+    menu("menu_effects", vec!(Down, Down, Down, Confirm, Down, Right)),
+    MenuScenario { paused: true, ..menu("menu_pause", vec!(Down, Down)) }
+  );
+}
+
+fn render_menu(scenario: &MenuScenario) -> Result<Frame, String> {
+  let size: (u32, u32) = hud::max_screen_size(TILE_SIZE);
+  let mut frame: Frame = Frame::new(size.0, size.1);
+  let mut setup: MatchSetup = MatchSetup::default();
+  let mut menus: MenuStack = match scenario.paused {
+    true => {
+      let world: WorldImpl = setup.start_round(0);
+      frame = Frame::capture_screen(&world, &Scoreboard::new(), 1.0, TILE_SIZE, None, None)?;
+      MenuStack::new(Box::new(PauseScreen::new()))
+    },
+    false => MenuStack::new(Box::new(TitleScreen::new()))
+  };
+  for input in scenario.inputs.iter() {
+    menus.handle(input, &mut setup);
+  }
+  let size: (u32, u32) = (*frame.get_width(), *frame.get_height());
+  menus.draw(&mut frame, &setup, size)?;
+  return Ok(frame);
+}
+
+fn render(scenario: &Scenario) -> Result<Frame, String> {
+  let mut world: WorldImpl =
+    WorldImpl::start(scenario.rules, Box::new(StageImpl::classic()), scenario.player_count, 0);
+  let mut effects: Option<Effects> = scenario.effects.map(Effects::new);
+  let mut bots: Bots = Bots::new(&scenario.bots, 0);
+  for step in scenario.steps.iter() {
+    let mut inputs: InputFrame = step.inputs;
+    for _ in 0..step.ticks {
+      bots.fill(&world, &mut inputs);
+      let events: Vec<WorldEvent> = world.tick_mut(&inputs);
+      if let Some(effects) = &mut effects {
+        effects.hear(&events, &world);
+        effects.tick(1);
+      }
+      inputs = input::held_frame(&inputs);
+    }
+  }
+  let theme: Option<Theme> = match scenario.theme {
+    Some(name) => Some(Theme::load_builtin(name)?),
+    None => None
+  };
+  match &scenario.scoreboard {
+    Some(scoreboard) => {
+      return Frame::capture_screen(
+        &world, scoreboard, scenario.alpha, TILE_SIZE, theme.as_ref(), effects.as_ref()
+      );
+    },
+    None => return Frame::capture(&world, scenario.alpha, TILE_SIZE, theme.as_ref())
+  }
+}
+
+// Renders every scenario, paired with its name.
+pub fn render_all() -> Vec<(String, Result<Frame, String>)> {
+  let mut renders: Vec<(String, Result<Frame, String>)> = scenarios().iter()
+    .map(|scenario| (scenario.name.clone(), render(scenario)))
+    .collect();
+  renders.extend(
+    menu_scenarios().iter().map(|scenario| (String::from(scenario.name), render_menu(scenario)))
+  );
+  return renders;
+}
@@ -0,0 +1,244 @@
+/* Plays a round of netplay between bots on this machine, each on its own peer talking to the others
+ * over UDP on localhost, with the network made as bad as asked for. Afterwards, it checks that
+ * every peer's world was the same as a round played locally with the same inputs at every tick,
+ * exiting with an error if it wasn't. With rollback, that's a round that never had to guess at
+ * anything, so it shows corrections always end up where they should. Nothing here needs SDL or a
+ * display.
+ *
+ * Options are:
+ *   --netcode NAME      lockstep or rollback (lockstep by default)
+ *   --players N         how many peers play (2 by default)
+ *   --ticks N           the most ticks to play, if the round doesn't end first (600 by default)
+ *   --seed N            what the round is seeded with (0 by default)
+ *   --delay TICKS       input delay (3 ticks for lockstep and 1 for rollback by default)
+ *   --loss P            the chance of any packet being lost, from 0 to 1 (0.1 by default)
+ *   --latency MS        how long packets take to arrive, one way (40 by default)
+ *   --jitter MS         how much longer than that any packet can take (20 by default)
+ *   --port N            the port the first peer listens on, counting up from there for the others
+ *                       (47000 by default)
+ */
+use open_bomberman::{
+  ai::{Bots, Difficulty},
+  model::{
+    checksum,
+    input::{self, InputFrame, PlayerId, MAX_PLAYERS},
+    rules::Rules,
+    stage::StageImpl,
+    timestep::FixedTimestep,
+    world::{World, WorldImpl}
+  },
+  net::{
+    transport::{Conditions, ShimTransport, UdpTransport},
+    Netcode, Session
+  }
+};
+use std::{
+  net::SocketAddr,
+  process,
+  thread,
+  time::{Duration, Instant}
+};
+
+// How long peers that are done keep going for the sake of the others, at most.
+const LINGER_TIME: Duration = Duration::from_secs(2);
+
+// How long peers sleep between frames.
+const FRAME_TIME: Duration = Duration::from_millis(1);
+
+struct Options {
+  netcode: Netcode,
+  players: usize,
+  ticks: u32,
+  seed: u64,
+  delay: u32,
+  conditions: Conditions,
+  port: u16
+}
+
+/* What a peer ended up with: the inputs of every tick, its checksums, how often it stalled and
+ * rolled back, and the longest it spent polling, which is where any rolling back happens.
+ */
+struct PeerResult {
+  frames: Vec<InputFrame>,
+  checksums: Vec<u64>,
+  stalls: u32,
+  rollbacks: u32,
+  slowest_poll: Duration
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str, option: &str) -> Result<T, String> {
+  return value.parse::<T>().map_err(|_| format!("{} isn't a valid number for {}.", value, option));
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+  let mut netcode: Netcode = Netcode::Lockstep;
+  let mut players: usize = 2;
+  let mut ticks: u32 = 600;
+  let mut seed: u64 = 0;
+  let mut delay: Option<u32> = None;
+  let (mut loss, mut latency, mut jitter): (f32, u64, u64) = (0.1, 40, 20);
+  let mut port: u16 = 47000;
+  let mut args = args.iter();
+  while let Some(option) = args.next() {
+    let value: &String = args.next().ok_or_else(|| format!("{} needs a value.", option))?;
+    match option.as_str() {
+      "--netcode" => netcode = Netcode::from_name(value)?,
+      "--players" => players = parse_number(value, option)?,
+      "--ticks" => ticks = parse_number(value, option)?,
+      "--seed" => seed = parse_number(value, option)?,
+      "--delay" => delay = Some(parse_number(value, option)?),
+      "--loss" => loss = parse_number(value, option)?,
+      "--latency" => latency = parse_number(value, option)?,
+      "--jitter" => jitter = parse_number(value, option)?,
+      "--port" => port = parse_number(value, option)?,
+      _ => return Err(format!("{} isn't an option.", option))
+    }
+  }
+  if !(2..=MAX_PLAYERS).contains(&players) {
+    return Err(format!("Netplay needs between 2 and {} peers.", MAX_PLAYERS));
+  }
+  return Ok(
+    Options {
+      netcode: netcode,
+      players: players,
+      ticks: ticks,
+      seed: seed,
+      delay: delay.unwrap_or_else(|| netcode.default_delay()),
+      conditions: Conditions::new(
+        loss, Duration::from_millis(latency), Duration::from_millis(jitter)
+      )?,
+      port: port
+    }
+  );
+}
+
+fn start_world(options: &Options) -> WorldImpl {
+  return WorldImpl::start(
+    Rules::default(), Box::new(StageImpl::classic()), options.players, options.seed
+  );
+}
+
+// Plays as one of the peers, with a bot deciding what the local player does, until the round ends.
+fn play_peer(
+  options: &Options, local: PlayerId, peers: Vec<SocketAddr>
+) -> Result<PeerResult, String> {
+  let transport: ShimTransport = ShimTransport::new(
+    Box::new(UdpTransport::new(local, peers)?), options.conditions,
+    options.seed.wrapping_add(local as u64)
+  );
+  let mut session: Box<dyn Session> =
+    options.netcode.start(start_world(options), local, options.delay, Box::new(transport))?;
+  let mut bots: Bots = Bots::new(&[(local, Difficulty::Hard)], options.seed);
+  let mut timestep: FixedTimestep = FixedTimestep::default();
+  let mut last_frame: Instant = Instant::now();
+  let mut done: Option<Instant> = None;
+  let mut slowest_poll: Duration = Duration::ZERO;
+  loop {
+    let now: Instant = Instant::now();
+    session.poll()?;
+    slowest_poll = slowest_poll.max(now.elapsed());
+    for _ in 0..timestep.advance(now - last_frame) {
+      let world: &WorldImpl = session.get_world();
+      if world.get_outcome().is_some() || *world.get_time() >= options.ticks {
+        break;
+      }
+      let mut frame: InputFrame = input::idle_frame();
+      bots.fill(world, &mut frame);
+      session.add_local_input(&frame[local as usize]);
+      session.advance();
+    }
+    last_frame = now;
+
+    // With rollback, the round isn't over until every input up to the end of it is in.
+    let world: &WorldImpl = session.get_world();
+    let over: bool = world.get_outcome().is_some() || *world.get_time() >= options.ticks;
+    if over && session.is_confirmed() {
+      let since: Instant = *done.get_or_insert(now);
+      if session.is_caught_up() || now - since >= LINGER_TIME {
+        break;
+      }
+    }
+    thread::sleep(FRAME_TIME);
+  }
+  let time: u32 = *session.get_world().get_time();
+  return Ok(
+    PeerResult {
+      frames: (0..time).map(|tick| session.get_exchange().get_frame(tick).unwrap()).collect(),
+      checksums: session.get_checksums().clone(),
+      stalls: *session.get_stalls(),
+      rollbacks: *session.get_rollbacks(),
+      slowest_poll: slowest_poll
+    }
+  );
+}
+
+// Plays the round again locally with the inputs one of the peers had, comparing every tick.
+fn check_peer(
+  options: &Options, id: usize, result: &PeerResult, reference: &[u64]
+) -> Result<(), String> {
+  if result.checksums != reference {
+    let tick: usize = result.checksums.iter().zip(reference.iter())
+      .position(|(a, b)| a != b)
+      .unwrap_or(result.checksums.len().min(reference.len()));
+    return Err(format!("Peer {} went differently from peer 0 by tick {}.", id, tick));
+  }
+  let mut world: WorldImpl = start_world(options);
+  for (tick, frame) in result.frames.iter().enumerate() {
+    if checksum::checksum(&world) != result.checksums[tick] {
+      return Err(format!("Peer {} went differently from a local round by tick {}.", id, tick));
+    }
+    world.tick_mut(frame);
+  }
+  if checksum::checksum(&world) != result.checksums[result.frames.len()] {
+    return Err(format!("Peer {} ended differently from a local round.", id));
+  }
+  return Ok(());
+}
+
+fn run(options: &Options) -> Result<(), String> {
+  let peers: Vec<SocketAddr> = (0..options.players)
+    .map(|id| SocketAddr::from(([127, 0, 0, 1], options.port + id as u16)))
+    .collect();
+  let start: Instant = Instant::now();
+  let results: Vec<PeerResult> = thread::scope(|scope| {
+    let handles: Vec<_> = (0..options.players)
+      .map(|id| {
+        let peers: Vec<SocketAddr> = peers.clone();
+        return scope.spawn(move || play_peer(options, id as PlayerId, peers));
+      })
+      .collect();
+    return handles.into_iter()
+      .map(|handle| handle.join().map_err(|_| String::from("A peer panicked."))?)
+      .collect::<Result<Vec<PeerResult>, String>>();
+  })?;
+  let elapsed: Duration = start.elapsed();
+
+  for (id, result) in results.iter().enumerate() {
+    check_peer(options, id, result, &results[0].checksums)?;
+  }
+  let stalls: Vec<String> = results.iter().map(|result| result.stalls.to_string()).collect();
+  let rollbacks: Vec<String> =
+    results.iter().map(|result| result.rollbacks.to_string()).collect();
+  let slowest_poll: Duration =
+    results.iter().map(|result| result.slowest_poll).max().unwrap_or_default();
+  println!(
+    "{} peers played {} ticks of {:?} in {:.1}s with a {} tick delay, {:.0}% loss, {}ms latency \
+    and {}ms jitter, stalling {} times and rolling back {} times.",
+    options.players, results[0].frames.len(), options.netcode, elapsed.as_secs_f32(),
+    options.delay, options.conditions.get_loss() * 100.0,
+    options.conditions.get_latency().as_millis(), options.conditions.get_jitter().as_millis(),
+    stalls.join("/"), rollbacks.join("/")
+  );
+  println!("The slowest poll took {:.2}ms.", slowest_poll.as_secs_f64() * 1000.0);
+  println!("Every peer matched a local round at every tick.");
+  return Ok(());
+}
+
+fn main() {
+  let args: Vec<String> = std::env::args().skip(1).collect();
+  let result: Result<(), String> = parse_options(&args).and_then(|options| run(&options));
+  if let Err(e) = result {
+    eprintln!("{}", e);
+    process::exit(1);
+  }
+}
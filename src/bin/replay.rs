@@ -0,0 +1,147 @@
+/* Plays back a replay in the terminal, for looking into bug reports and going over tournaments.
+ * Nothing here needs SDL or a display.
+ *
+ * Run with the path of a replay to watch it. Space pauses, the left and right arrows step back or
+ * forward a tick at a time, [ and ] skip back or forward 5 seconds, Home and End jump to either
+ * end, the up and down arrows speed it up or slow it down, and Escape or q quits.
+ *
+ * Run with --verify before the path to play it back without watching it instead, exiting with an
+ * error if it doesn't play out the way it was recorded.
+ */
+use crossterm::{
+  cursor::{Hide, Show},
+  event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+  execute,
+  terminal::{self, EnterAlternateScreen, LeaveAlternateScreen}
+};
+use open_bomberman::{
+  model::{score::Scoreboard, timestep::TICK_RATE, world::{Outcome, World, WorldImpl}},
+  replay::{viewer::ReplayViewer, Replay},
+  view::{hud, termview::TermView, View}
+};
+use std::{
+  io::{self, Stdout, Write},
+  path::Path,
+  process,
+  time::{Duration, Instant}
+};
+
+// How long to wait for keys between frames.
+const FRAME_TIME: Duration = Duration::from_millis(16);
+
+// How far [ and ] skip, in ticks.
+const SKIP_TICKS: i64 = 5 * TICK_RATE as i64;
+
+// Puts the terminal into raw mode for as long as this is around, and puts it back afterwards.
+struct RawTerminal {
+  out: Stdout
+}
+
+impl RawTerminal {
+  fn new() -> Result<RawTerminal, String> {
+    let mut out: Stdout = io::stdout();
+    terminal::enable_raw_mode().map_err(|e| e.to_string())?;
+    execute!(out, EnterAlternateScreen, Hide).map_err(|e| e.to_string())?;
+    return Ok(RawTerminal { out: out });
+  }
+}
+
+impl Drop for RawTerminal {
+  fn drop(&mut self) {
+    let _ = execute!(self.out, Show, LeaveAlternateScreen);
+    let _ = terminal::disable_raw_mode();
+  }
+}
+
+// Handles a key press, returning false if it means the viewer should quit.
+fn handle_key(key: &KeyEvent, viewer: &mut ReplayViewer) -> bool {
+  match key.code {
+    KeyCode::Esc | KeyCode::Char('q') => return false,
+    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+    KeyCode::Char(' ') => viewer.toggle_paused(),
+    KeyCode::Right => {
+      viewer.step();
+    },
+    KeyCode::Left => {
+      viewer.set_paused(true);
+      viewer.skip(-1);
+    },
+    KeyCode::Char(']') => viewer.skip(SKIP_TICKS),
+    KeyCode::Char('[') => viewer.skip(-SKIP_TICKS),
+    KeyCode::Home => viewer.seek(0),
+    KeyCode::End => viewer.seek(viewer.get_length()),
+    KeyCode::Up => viewer.change_speed(1),
+    KeyCode::Down => viewer.change_speed(-1),
+    _ => {}
+  }
+  return true;
+}
+
+// Describes where the viewer is in the replay, for under the world.
+fn status(viewer: &ReplayViewer) -> String {
+  let time: u32 = *viewer.get_world().get_time();
+  return format!(
+    "{}  {} / {}  tick {} / {}  {}x",
+    if *viewer.get_paused() { "Paused " } else { "Playing" },
+    hud::format_clock(time), hud::format_clock(viewer.get_length()),
+    time, viewer.get_length(), viewer.get_speed()
+  );
+}
+
+fn watch(replay: Replay) -> Result<(), String> {
+  let mut viewer: ReplayViewer = ReplayViewer::new(replay)?;
+  let mut terminal: RawTerminal = RawTerminal::new()?;
+  let scoreboard: Scoreboard = Scoreboard::new();
+  let mut last_frame: Instant = Instant::now();
+  loop {
+    let mut timeout: Duration = FRAME_TIME;
+    while event::poll(timeout).map_err(|e| e.to_string())? {
+      if let Event::Key(key) = event::read().map_err(|e| e.to_string())? {
+        if !handle_key(&key, &mut viewer) {
+          return Ok(());
+        }
+      }
+      timeout = Duration::from_secs(0);
+    }
+
+    let now: Instant = Instant::now();
+    viewer.update(now - last_frame);
+    last_frame = now;
+
+    TermView::new(&mut terminal.out, true)
+      .render(viewer.get_world(), &scoreboard, viewer.get_alpha())?;
+    write!(terminal.out, "{}\x1b[K\r\n", status(&viewer)).map_err(|e| e.to_string())?;
+    terminal.out.flush().map_err(|e| e.to_string())?;
+  }
+}
+
+fn verify(replay: &Replay) -> Result<(), String> {
+  let world: WorldImpl = replay.verify()?;
+  let ending: String = match world.get_outcome() {
+    Some(Outcome::Win(id)) => format!("player {} winning", id),
+    Some(Outcome::Draw) => String::from("a draw"),
+    None => String::from("the round still going")
+  };
+  match replay.get_end() {
+    Some(_) => {
+      println!("Played back the same, ending on tick {} with {}.", world.get_time(), ending);
+    },
+    None => println!("Replay was never finished, so there's nothing to check it against.")
+  }
+  return Ok(());
+}
+
+fn main() {
+  let args: Vec<String> = std::env::args().skip(1).collect();
+  let result: Result<(), String> = match args.as_slice() {
+    [flag, path] if flag == "--verify" => {
+      Replay::load(Path::new(path)).and_then(|replay| verify(&replay))
+    },
+    [path] => Replay::load(Path::new(path)).and_then(watch),
+    _ => Err(String::from("Usage: open-bomberman-replay [--verify] PATH"))
+  };
+  if let Err(e) = result {
+    eprintln!("{}", e);
+    process::exit(1);
+  }
+}
@@ -0,0 +1,144 @@
+/* Saves, shows and checks world states; see WorldState. Nothing here needs SDL or a display.
+ *
+ * Commands are:
+ *   check [ROUNDS]              plays rounds of bots against each other (3 by default), checking
+ *                               that the world survives going through both forms of its state and
+ *                               carries on exactly the same afterwards, exiting with an error if it
+ *                               doesn't
+ *   save REPLAY TICK PATH       saves the state of a replay's world at a tick, as .json or .state
+ *   show PATH                   prints a saved state out as text
+ *   hash PATH                   prints the checksum of a saved state's world
+ *   diff PATH PATH              prints the first field that's different between two saved states,
+ *                               exiting with an error if there is one
+ */
+use open_bomberman::{
+  ai::{Bots, Difficulty},
+  model::{
+    checksum,
+    input::{self, InputFrame, PlayerId},
+    rules::Rules,
+    stage::{StageImpl, STAGES},
+    state::{self, WorldState},
+    world::{World, WorldImpl}
+  },
+  replay::Replay,
+  view::termview
+};
+use std::{path::Path, process};
+
+const USAGE: &str = "Usage: open-bomberman-state \
+  check [ROUNDS] | save REPLAY TICK PATH | show PATH | hash PATH | diff PATH PATH";
+
+// How many ticks go by between checks of a round.
+const CHECK_INTERVAL: u32 = 30;
+
+// How many bots play in each round that gets checked.
+const CHECK_PLAYERS: usize = 4;
+
+/* Plays a round, round-tripping the world's state every so often and playing on with both the
+ * world and the one rebuilt from its state, to check they stay the same. Returns how many states
+ * were checked.
+ */
+fn check_round(round: u64) -> Result<u32, String> {
+  let stage: StageImpl = StageImpl::from_layout(STAGES[round as usize % STAGES.len()].1)?;
+  let mut world: WorldImpl =
+    WorldImpl::start(Rules::default(), Box::new(stage), CHECK_PLAYERS, round);
+  let lineup: Vec<(PlayerId, Difficulty)> = (0..CHECK_PLAYERS)
+    .map(|id| (id as PlayerId, Difficulty::Hard))
+    .collect();
+  let mut bots: Bots = Bots::new(&lineup, round);
+  let mut restored: WorldImpl = state::check_round_trip(&world)?;
+  let mut checks: u32 = 1;
+  while world.get_outcome().is_none() {
+    let mut frame: InputFrame = input::idle_frame();
+    bots.fill(&world, &mut frame);
+    world.tick_mut(&frame);
+    restored.tick_mut(&frame);
+    if world.get_time().is_multiple_of(CHECK_INTERVAL) || world.get_outcome().is_some() {
+      if let Some(difference) =
+        state::diff(&WorldState::capture(&world), &WorldState::capture(&restored))
+      {
+        return Err(
+          format!(
+            "Round {} played out differently by tick {}, at {}",
+            round, world.get_time(), difference
+          )
+        );
+      }
+      restored = state::check_round_trip(&world)?;
+      checks += 1;
+    }
+  }
+  return Ok(checks);
+}
+
+fn check(rounds: u64) -> Result<(), String> {
+  let mut checks: u32 = 0;
+  for round in 0..rounds {
+    checks += check_round(round)?;
+  }
+  println!("Checked {} states over {} rounds.", checks, rounds);
+  return Ok(());
+}
+
+fn save(replay: &Path, tick: u32, path: &Path) -> Result<(), String> {
+  let replay: Replay = Replay::load(replay)?;
+  let mut world: WorldImpl = replay.start()?;
+  for frame in replay.get_frames().iter().take(tick as usize) {
+    world.tick_mut(frame);
+  }
+  WorldState::capture(&world).save(path)?;
+  println!("Saved tick {} to {}.", world.get_time(), path.display());
+  return Ok(());
+}
+
+fn show(path: &Path) -> Result<(), String> {
+  let world: WorldImpl = WorldImpl::from_state(&WorldState::load(path)?)?;
+  println!("Tick {}", world.get_time());
+  for line in termview::draw(&world, false) {
+    println!("{}", line);
+  }
+  return Ok(());
+}
+
+fn hash(path: &Path) -> Result<(), String> {
+  let world: WorldImpl = WorldImpl::from_state(&WorldState::load(path)?)?;
+  println!("{:016x}", checksum::checksum(&world));
+  return Ok(());
+}
+
+fn diff(a: &Path, b: &Path) -> Result<(), String> {
+  match state::diff(&WorldState::load(a)?, &WorldState::load(b)?) {
+    Some(difference) => {
+      return Err(format!("The states are different, first at {}", difference));
+    },
+    None => {
+      println!("The states are the same.");
+      return Ok(());
+    }
+  }
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+  return value.parse::<T>().map_err(|_| format!("{} isn't a valid number.", value));
+}
+
+fn main() {
+  let args: Vec<String> = std::env::args().skip(1).collect();
+  let args: Vec<&str> = args.iter().map(String::as_str).collect();
+  let result: Result<(), String> = match args.as_slice() {
+    ["check"] => check(3),
+    ["check", rounds] => parse_number(rounds).and_then(check),
+    ["save", replay, tick, path] => {
+      parse_number(tick).and_then(|tick| save(Path::new(replay), tick, Path::new(path)))
+    },
+    ["show", path] => show(Path::new(path)),
+    ["hash", path] => hash(Path::new(path)),
+    ["diff", a, b] => diff(Path::new(a), Path::new(b)),
+    _ => Err(String::from(USAGE))
+  };
+  if let Err(e) = result {
+    eprintln!("{}", e);
+    process::exit(1);
+  }
+}
@@ -0,0 +1,143 @@
+/* Plays bot-vs-bot matches headlessly and reports how every bot did, for tuning bots and balancing
+ * rules. Nothing here needs SDL or a display. Matches with the same seed always play out the same
+ * way, however many threads they're spread over, so any report can be reproduced.
+ *
+ * Options are:
+ *   --matches N         how many matches to play (100 by default)
+ *   --bots LIST         who plays, by difficulty, like easy,normal,hard,expert (four hard bots by
+ *                       default)
+ *   --stages LIST       which stages to take turns between, by name (every stage by default)
+ *   --seed N            what the first match is seeded with, counting up from there (0 by default)
+ *   --threads N         how many matches to play at once (one per core by default)
+ *   --rules PATH        a .toml or .ron rules file to play with (the default rules otherwise)
+ *   --format FORMAT     csv or json (csv by default)
+ *   --output PATH       where to write the report (standard output by default)
+ *   --replays DIR       a directory to save a replay of every match to (none are saved by default)
+ */
+use open_bomberman::{
+  ai::{Difficulty, DIFFICULTIES},
+  model::{rules::Rules, stage::STAGES},
+  tournament::{
+    report::{self, Summary},
+    MatchResult, Tournament
+  }
+};
+use std::{
+  fs,
+  num::NonZeroUsize,
+  path::{Path, PathBuf},
+  process,
+  thread,
+  time::Instant
+};
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Format {
+  Csv,
+  Json
+}
+
+struct Options {
+  tournament: Tournament,
+  threads: usize,
+  format: Format,
+  output: Option<String>
+}
+
+// Finds whatever a comma-separated list names in a list of choices, ignoring case.
+fn parse_list<T: Copy>(list: &str, choices: &[(&str, T)], kind: &str) -> Result<Vec<T>, String> {
+  return list.split(',')
+    .map(|name| {
+      return choices.iter()
+        .find(|(choice, _)| choice.eq_ignore_ascii_case(name.trim()))
+        .map(|(_, value)| *value)
+        .ok_or_else(|| format!("There's no {} called {}.", kind, name.trim()));
+    })
+    .collect();
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str, option: &str) -> Result<T, String> {
+  return value.parse::<T>().map_err(|_| format!("{} isn't a valid number for {}.", value, option));
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+  let difficulties: Vec<(String, Difficulty)> = DIFFICULTIES.iter()
+    .map(|difficulty| (format!("{:?}", difficulty), *difficulty))
+    .collect();
+  let difficulties: Vec<(&str, Difficulty)> = difficulties.iter()
+    .map(|(name, difficulty)| (name.as_str(), *difficulty))
+    .collect();
+  let stages: Vec<(&str, usize)> = STAGES.iter().enumerate()
+    .map(|(index, (name, _))| (*name, index))
+    .collect();
+
+  let mut rules: Rules = Rules::default();
+  let mut bots: Vec<Difficulty> = vec!(Difficulty::Hard; 4);
+  let mut chosen_stages: Vec<usize> = (0..STAGES.len()).collect();
+  let mut matches: usize = 100;
+  let mut seed: u64 = 0;
+  let mut threads: usize = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+  let mut format: Format = Format::Csv;
+  let mut output: Option<String> = None;
+  let mut replays: Option<PathBuf> = None;
+  let mut args = args.iter();
+  while let Some(option) = args.next() {
+    let value: &String = args.next().ok_or_else(|| format!("{} needs a value.", option))?;
+    match option.as_str() {
+      "--matches" => matches = parse_number(value, option)?,
+      "--bots" => bots = parse_list(value, &difficulties, "bot difficulty")?,
+      "--stages" => chosen_stages = parse_list(value, &stages, "stage")?,
+      "--seed" => seed = parse_number(value, option)?,
+      "--threads" => threads = parse_number(value, option)?,
+      "--rules" => rules = Rules::from_file(Path::new(value))?,
+      "--format" => {
+        format = match value.to_lowercase().as_str() {
+          "csv" => Format::Csv,
+          "json" => Format::Json,
+          _ => return Err(format!("{} isn't a format; use csv or json.", value))
+        };
+      },
+      "--output" => output = Some(value.clone()),
+      "--replays" => replays = Some(PathBuf::from(value)),
+      _ => return Err(format!("{} isn't an option.", option))
+    }
+  }
+  return Ok(
+    Options {
+      tournament: Tournament::new(rules, bots, chosen_stages, matches, seed)?.set_replays(replays),
+      threads: threads.max(1),
+      format: format,
+      output: output
+    }
+  );
+}
+
+fn run(options: &Options) -> Result<(), String> {
+  let start: Instant = Instant::now();
+  let results: Vec<MatchResult> = options.tournament.run(options.threads)?;
+  let summary: Summary = report::summarize(options.tournament.get_bots(), &results);
+  eprintln!(
+    "Played {} matches on {} threads in {:.1}s.",
+    results.len(), options.threads, start.elapsed().as_secs_f32()
+  );
+  let contents: String = match options.format {
+    Format::Csv => report::to_csv(&summary, &results),
+    Format::Json => report::to_json(&summary, &results)?
+  };
+  match &options.output {
+    Some(path) => {
+      return fs::write(path, contents).map_err(|e| format!("Couldn't write {}: {}", path, e));
+    },
+    None => print!("{}", contents)
+  }
+  return Ok(());
+}
+
+fn main() {
+  let args: Vec<String> = std::env::args().skip(1).collect();
+  let result: Result<(), String> = parse_options(&args).and_then(|options| run(&options));
+  if let Err(e) = result {
+    eprintln!("{}", e);
+    process::exit(1);
+  }
+}
@@ -0,0 +1,333 @@
+/* A terminal frontend for playing without a display, like over SSH.
+ * Terminals only tell us when keys are pressed (or repeated), never when they're let go, so a
+ * direction counts as held until a little while after its key last came in.
+ *
+ * Run without options for two players on one keyboard, or with --net to play against other peers,
+ * using the first player's keys:
+ *   --net PLAYER ADDRESSES      which player this peer plays as, and the address of every player's
+ *                               peer in order, like 10.0.0.2:7000,10.0.0.3:7000, where this peer
+ *                               listens on its own
+ *   --netcode NAME              lockstep or rollback (lockstep by default)
+ *   --seed N                    what the first round is seeded with, counting up from there (0 by
+ *                               default)
+ *   --delay TICKS               input delay (3 ticks for lockstep and 1 for rollback by default)
+ *   --loss P, --latency MS, --jitter MS
+ *                               make the network worse on purpose, for trying it out on one machine
+ *                               (none by default)
+ * Every peer has to be started with the same addresses, netcode, seed and delay.
+ */
+use crossterm::{
+  cursor::{Hide, Show},
+  event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+  execute,
+  terminal::{self, EnterAlternateScreen, LeaveAlternateScreen}
+};
+use open_bomberman::{
+  common::direction::Direction,
+  model::{
+    input::{self, InputFrame, PlayerId, PlayerInput, MAX_PLAYERS},
+    rng,
+    rules::Rules,
+    score::Scoreboard,
+    stage::StageImpl,
+    timestep::FixedTimestep,
+    world::{World, WorldImpl}
+  },
+  net::{
+    transport::{Conditions, ShimTransport, Transport, UdpTransport},
+    Netcode, Session
+  },
+  view::{termview::TermView, View}
+};
+use std::{
+  io::{self, Stdout, Write},
+  net::SocketAddr,
+  time::{Duration, Instant}
+};
+
+// How long a direction is held for after its key was last pressed or repeated.
+const HOLD_TIME: Duration = Duration::from_millis(500);
+
+// How long a round's outcome is shown for before the next round starts.
+const ROUND_END_TIME: Duration = Duration::from_secs(3);
+
+// How long to wait for keys between frames.
+const FRAME_TIME: Duration = Duration::from_millis(16);
+
+/* The keys each player uses, in the order up, down, left, right, place bomb, detonate and special.
+ * Terminals can't see modifier keys on their own, so everything is a regular key.
+ */
+const KEYS: [[KeyCode; 7]; 2] = [
+  [
+    KeyCode::Char('w'), KeyCode::Char('s'), KeyCode::Char('a'), KeyCode::Char('d'),
+    KeyCode::Char(' '), KeyCode::Char('e'), KeyCode::Char('q')
+  ],
+  [
+    KeyCode::Up, KeyCode::Down, KeyCode::Left, KeyCode::Right,
+    KeyCode::Enter, KeyCode::Char('.'), KeyCode::Char('/')
+  ]
+];
+
+const DIRECTIONS: [Direction; 4] =
+  [Direction::North, Direction::South, Direction::West, Direction::East];
+
+// How to play over the network; see the options above.
+struct NetOptions {
+  local: PlayerId,
+  peers: Vec<SocketAddr>,
+  netcode: Netcode,
+  seed: u64,
+  delay: u32,
+  conditions: Conditions
+}
+
+// Puts the terminal into raw mode for as long as this is around, and puts it back afterwards.
+struct RawTerminal {
+  out: Stdout
+}
+
+impl RawTerminal {
+  fn new() -> Result<RawTerminal, String> {
+    let mut out: Stdout = io::stdout();
+    terminal::enable_raw_mode().map_err(|e| e.to_string())?;
+    execute!(out, EnterAlternateScreen, Hide).map_err(|e| e.to_string())?;
+    return Ok(RawTerminal { out: out });
+  }
+}
+
+impl Drop for RawTerminal {
+  fn drop(&mut self) {
+    let _ = execute!(self.out, Show, LeaveAlternateScreen);
+    let _ = terminal::disable_raw_mode();
+  }
+}
+
+/* Handles a key press, returning false if it means the game should quit.
+ * Directions are held with a timestamp, while buttons are added to the presses for the next tick.
+ */
+fn handle_key(
+  key: &KeyEvent, held: &mut [Option<(Direction, Instant)>; 2], presses: &mut InputFrame
+) -> bool {
+  if key.code == KeyCode::Esc
+    || (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL)) {
+    return false;
+  }
+  for (id, keys) in KEYS.iter().enumerate() {
+    for (i, direction) in DIRECTIONS.iter().enumerate() {
+      if key.code == keys[i] {
+        held[id] = Some((*direction, Instant::now()));
+      }
+    }
+    let input: &PlayerInput = &presses[id];
+    presses[id] = PlayerInput::new(
+      *input.get_direction(),
+      *input.get_place_bomb() || key.code == keys[4],
+      *input.get_detonate() || key.code == keys[5],
+      *input.get_special() || key.code == keys[6]
+    );
+  }
+  return true;
+}
+
+// Combines the presses for the next tick with whichever directions are still being held.
+fn read_inputs(held: &[Option<(Direction, Instant)>; 2], presses: &InputFrame) -> InputFrame {
+  let mut frame: InputFrame = *presses;
+  for (id, hold) in held.iter().enumerate() {
+    let direction: Option<Direction> = match hold {
+      Some((direction, since)) if since.elapsed() < HOLD_TIME => Some(*direction),
+      _ => None
+    };
+    let input: &PlayerInput = &presses[id];
+    frame[id] = PlayerInput::new(
+      direction, *input.get_place_bomb(), *input.get_detonate(), *input.get_special()
+    );
+  }
+  return frame;
+}
+
+fn run(terminal: &mut RawTerminal) -> Result<(), String> {
+  let mut view: TermView<&mut Stdout> = TermView::new(&mut terminal.out, true);
+  let start = || {
+    return WorldImpl::start(
+      Rules::default(), Box::new(StageImpl::classic()), KEYS.len(), rng::clock_seed()
+    );
+  };
+  let mut world: WorldImpl = start();
+  let mut timestep: FixedTimestep = FixedTimestep::default();
+  let mut scoreboard: Scoreboard = Scoreboard::new();
+  let mut round_over: Option<Instant> = None;
+
+  let mut held: [Option<(Direction, Instant)>; 2] = [None; 2];
+  let mut presses: InputFrame = input::idle_frame();
+  let mut last_frame: Instant = Instant::now();
+  loop {
+    let mut timeout: Duration = FRAME_TIME;
+    while event::poll(timeout).map_err(|e| e.to_string())? {
+      if let Event::Key(key) = event::read().map_err(|e| e.to_string())? {
+        if !handle_key(&key, &mut held, &mut presses) {
+          return Ok(());
+        }
+      }
+      timeout = Duration::from_secs(0);
+    }
+
+    let now: Instant = Instant::now();
+    if timestep.update(&mut world, now - last_frame, &read_inputs(&held, &presses)).0 > 0 {
+      presses = input::idle_frame();
+    }
+    last_frame = now;
+
+    // Rounds are scored as soon as they're over, but left on screen for a bit before moving on.
+    match (world.get_outcome(), round_over) {
+      (Some(outcome), None) => {
+        scoreboard.record(&outcome);
+        round_over = Some(now);
+      },
+      (Some(_), Some(since)) if now - since >= ROUND_END_TIME => {
+        world = start();
+        round_over = None;
+      },
+      _ => {}
+    }
+    view.render(&world, &scoreboard, timestep.get_alpha())?;
+  }
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str, option: &str) -> Result<T, String> {
+  return value.parse::<T>().map_err(|_| format!("{} isn't a valid number for {}.", value, option));
+}
+
+fn parse_net_options(args: &[String]) -> Result<NetOptions, String> {
+  let (local, peers): (PlayerId, Vec<SocketAddr>) = match args {
+    [net, local, peers, ..] if net == "--net" => {
+      let peers: Vec<SocketAddr> = peers.split(',')
+        .map(|peer| peer.trim().parse().map_err(|_| format!("{} isn't an address.", peer)))
+        .collect::<Result<Vec<SocketAddr>, String>>()?;
+      (parse_number(local, "--net")?, peers)
+    },
+    _ => return Err(String::from("Usage: open-bomberman-tui [--net PLAYER ADDRESSES [OPTIONS]]"))
+  };
+  if !(2..=MAX_PLAYERS).contains(&peers.len()) {
+    return Err(format!("Netplay needs between 2 and {} peers.", MAX_PLAYERS));
+  }
+  let mut netcode: Netcode = Netcode::Lockstep;
+  let mut seed: u64 = 0;
+  let mut delay: Option<u32> = None;
+  let (mut loss, mut latency, mut jitter): (f32, u64, u64) = (0.0, 0, 0);
+  let mut args = args[3..].iter();
+  while let Some(option) = args.next() {
+    let value: &String = args.next().ok_or_else(|| format!("{} needs a value.", option))?;
+    match option.as_str() {
+      "--netcode" => netcode = Netcode::from_name(value)?,
+      "--seed" => seed = parse_number(value, option)?,
+      "--delay" => delay = Some(parse_number(value, option)?),
+      "--loss" => loss = parse_number(value, option)?,
+      "--latency" => latency = parse_number(value, option)?,
+      "--jitter" => jitter = parse_number(value, option)?,
+      _ => return Err(format!("{} isn't an option.", option))
+    }
+  }
+  let conditions: Conditions =
+    Conditions::new(loss, Duration::from_millis(latency), Duration::from_millis(jitter))?;
+  return Ok(
+    NetOptions {
+      local: local,
+      peers: peers,
+      netcode: netcode,
+      seed: seed,
+      delay: delay.unwrap_or_else(|| netcode.default_delay()),
+      conditions: conditions
+    }
+  );
+}
+
+// Starts a round of netplay, seeded with how many rounds came before it.
+fn start_session(
+  options: &NetOptions, round: u64, transport: Box<dyn Transport>
+) -> Result<Box<dyn Session>, String> {
+  let world: WorldImpl = WorldImpl::start(
+    Rules::default(), Box::new(StageImpl::classic()), options.peers.len(),
+    options.seed.wrapping_add(round)
+  );
+  return options.netcode.start(world, options.local, options.delay, transport);
+}
+
+fn run_net(terminal: &mut RawTerminal, options: &NetOptions) -> Result<(), String> {
+  let udp: UdpTransport = UdpTransport::new(options.local, options.peers.clone())?;
+  let transport: Box<dyn Transport> = match options.conditions == Conditions::default() {
+    true => Box::new(udp),
+    false => Box::new(ShimTransport::new(Box::new(udp), options.conditions, rng::clock_seed()))
+  };
+  let mut round: u64 = 0;
+  let mut session: Box<dyn Session> = start_session(options, round, transport)?;
+  let mut timestep: FixedTimestep = FixedTimestep::default();
+  let mut scoreboard: Scoreboard = Scoreboard::new();
+  let mut round_over: Option<Instant> = None;
+
+  let mut held: [Option<(Direction, Instant)>; 2] = [None; 2];
+  let mut presses: InputFrame = input::idle_frame();
+  let mut last_frame: Instant = Instant::now();
+  loop {
+    let mut timeout: Duration = FRAME_TIME;
+    while event::poll(timeout).map_err(|e| e.to_string())? {
+      if let Event::Key(key) = event::read().map_err(|e| e.to_string())? {
+        if !handle_key(&key, &mut held, &mut presses) {
+          return Ok(());
+        }
+      }
+      timeout = Duration::from_secs(0);
+    }
+
+    let now: Instant = Instant::now();
+    session.poll()?;
+    for _ in 0..timestep.advance(now - last_frame) {
+      if session.get_world().get_outcome().is_some() {
+        break;
+      }
+      if session.add_local_input(&read_inputs(&held, &presses)[0]) {
+        presses = input::idle_frame();
+      }
+      session.advance();
+    }
+    last_frame = now;
+
+    /* Peers keep being polled while the outcome is up, so that any still catching up to it get
+     * what they need from this one. With rollback, an outcome can still be taken back until every
+     * input up to it is in, so it's only scored after that.
+     */
+    match (session.get_world().get_outcome(), round_over) {
+      (Some(outcome), None) if session.is_confirmed() => {
+        scoreboard.record(&outcome);
+        round_over = Some(now);
+      },
+      (Some(_), Some(since)) if now - since >= ROUND_END_TIME => {
+        round += 1;
+        session = start_session(options, round, session.into_transport())?;
+        round_over = None;
+      },
+      _ => {}
+    }
+    TermView::new(&mut terminal.out, true)
+      .render(&session.get_display_world(), &scoreboard, timestep.get_alpha())?;
+    let status: &str = match session.is_ready() || round_over.is_some() {
+      true => "",
+      false => "  Waiting for peers..."
+    };
+    write!(terminal.out, "Player {}{}\x1b[K\r\n", options.local, status)
+      .map_err(|e| e.to_string())?;
+    terminal.out.flush().map_err(|e| e.to_string())?;
+  }
+}
+
+fn main() -> Result<(), String> {
+  let args: Vec<String> = std::env::args().skip(1).collect();
+  let net: Option<NetOptions> = match args.is_empty() {
+    true => None,
+    false => Some(parse_net_options(&args)?)
+  };
+  let mut terminal: RawTerminal = RawTerminal::new()?;
+  match net {
+    Some(options) => return run_net(&mut terminal, &options),
+    None => return run(&mut terminal)
+  }
+}
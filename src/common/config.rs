@@ -0,0 +1,77 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::{fs, path::Path};
+
+/* Settings files (rules, bindings, volumes, effects and rewards) can be written in either TOML or
+ * RON, and which one a file is in goes by its extension. Kind is what the file holds, like
+ * "bindings", which is only used to say what went wrong.
+ */
+enum Format {
+  Toml,
+  Ron
+}
+
+fn format_of(path: &Path) -> Result<Format, String> {
+  match path.extension().and_then(|extension| extension.to_str()) {
+    Some("toml") => return Ok(Format::Toml),
+    Some("ron") => return Ok(Format::Ron),
+    _ => return Err(format!("{} is neither a .toml nor a .ron file.", path.display()))
+  }
+}
+
+pub fn from_toml<T: DeserializeOwned>(contents: &str, kind: &str) -> Result<T, String> {
+  return toml::from_str::<T>(contents).map_err(|e| format!("Invalid TOML {}: {}", kind, e));
+}
+
+pub fn from_ron<T: DeserializeOwned>(contents: &str, kind: &str) -> Result<T, String> {
+  return ron::de::from_str::<T>(contents).map_err(|e| format!("Invalid RON {}: {}", kind, e));
+}
+
+// Loads something from a file, in whichever format its extension says.
+pub fn load<T: DeserializeOwned>(path: &Path, kind: &str) -> Result<T, String> {
+  let format: Format = format_of(path)?;
+  let contents: String = fs::read_to_string(path)
+    .map_err(|e| format!("Couldn't read {} from {}: {}", kind, path.display(), e))?;
+  match format {
+    Format::Toml => return from_toml(&contents, kind),
+    Format::Ron => return from_ron(&contents, kind)
+  }
+}
+
+// Saves something to a file, in whichever format its extension says.
+pub fn save<T: Serialize>(value: &T, path: &Path, kind: &str) -> Result<(), String> {
+  let contents: String = match format_of(path)? {
+    Format::Toml => toml::to_string_pretty(value).map_err(|e| e.to_string())?,
+    Format::Ron => {
+      ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+        .map_err(|e| e.to_string())?
+    }
+  };
+  return fs::write(path, contents)
+    .map_err(|e| format!("Couldn't save {} to {}: {}", kind, path.display(), e));
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::model::rules::{Rules, StartingStats};
+  use std::{env, path::PathBuf, process};
+
+  #[test]
+  fn files_round_trip_in_either_format() {
+    let stats: StartingStats = StartingStats::default().set_speed(30).set_piercing(true);
+    for extension in ["ron", "toml"] {
+      let path: PathBuf =
+        env::temp_dir().join(format!("open-bomberman-config-{}.{}", process::id(), extension));
+      let loaded: Result<StartingStats, String> =
+        save(&stats, &path, "stats").and_then(|_| load(&path, "stats"));
+      let _ = fs::remove_file(&path);
+      assert_eq!(loaded, Ok(stats));
+    }
+  }
+
+  #[test]
+  fn other_extensions_are_rejected() {
+    assert!(save(&Rules::default(), Path::new("rules.json"), "rules").is_err());
+    assert!(load::<Rules>(Path::new("rules"), "rules").is_err());
+  }
+}
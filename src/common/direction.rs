@@ -1,6 +1,45 @@
-#[derive(Copy, Clone, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
 // A simple enumeration representing the four cardinal directions.
 pub enum Direction {
   North, South, West, East,
   Northwest, Northeast, Southwest, Southeast
 }
+
+// The directions a blast's flames spread in, in the order blasts keep their flames.
+pub const CARDINALS: [Direction; 4] =
+  [Direction::North, Direction::South, Direction::West, Direction::East];
+
+impl Direction {
+  /* Returns the direction pointing along a pair of axes, where each axis is -1, 0 or 1 and positive
+   * means north or east. Returns none if both axes are 0.
+   */
+  pub fn from_axes(x: i8, y: i8) -> Option<Direction> {
+    match (x.signum(), y.signum()) {
+      (0, 1) => return Some(Direction::North),
+      (0, -1) => return Some(Direction::South),
+      (-1, 0) => return Some(Direction::West),
+      (1, 0) => return Some(Direction::East),
+      (-1, 1) => return Some(Direction::Northwest),
+      (1, 1) => return Some(Direction::Northeast),
+      (-1, -1) => return Some(Direction::Southwest),
+      (1, -1) => return Some(Direction::Southeast),
+      _ => return None
+    }
+  }
+
+  // Returns the tile one step away from a position in this direction.
+  pub fn step(&self, position: &(i8, i8)) -> (i8, i8) {
+    match self {
+      Direction::North => return (position.0, position.1 + 1),
+      Direction::South => return (position.0, position.1 - 1),
+      Direction::West => return (position.0 - 1, position.1),
+      Direction::East => return (position.0 + 1, position.1),
+      Direction::Northwest => return (position.0 - 1, position.1 + 1),
+      Direction::Northeast => return (position.0 + 1, position.1 + 1),
+      Direction::Southwest => return (position.0 - 1, position.1 - 1),
+      Direction::Southeast => return (position.0 + 1, position.1 - 1)
+    }
+  }
+}
@@ -0,0 +1,348 @@
+use crate::{
+  common::{config, direction::Direction},
+  menu::setup::Slot
+};
+use serde::{Deserialize, Serialize};
+use std::{f32::consts::PI, path::Path};
+
+// How many players can share a keyboard, each with their own keys.
+pub const KEYBOARD_LAYOUTS: u8 = 4;
+
+// How many game controllers can be plugged in at once.
+pub const GAMEPADS: u8 = 4;
+
+// An enum representing something a player can do, which a control can be bound to.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Action {
+  Up,
+  Down,
+  Left,
+  Right,
+  PlaceBomb,
+  Detonate,
+  Special
+}
+
+pub const ACTIONS: [Action; 7] = [
+  Action::Up, Action::Down, Action::Left, Action::Right,
+  Action::PlaceBomb, Action::Detonate, Action::Special
+];
+
+impl Action {
+  pub fn name(&self) -> &'static str {
+    match self {
+      Action::Up => return "Up",
+      Action::Down => return "Down",
+      Action::Left => return "Left",
+      Action::Right => return "Right",
+      Action::PlaceBomb => return "Bomb",
+      Action::Detonate => return "Detonate",
+      Action::Special => return "Special"
+    }
+  }
+}
+
+/* An enum representing a key, button or axis that can be bound to an action.
+ * Everything goes by SDL's own names for it, so that any key or button SDL knows about can be
+ * bound without listing them all here. Keys go by their key names (like "W", "Left Shift" or
+ * "Keypad 8"), and buttons and axes by their controller mapping names (like "a", "dpup" or
+ * "lefttrigger"). Axes count as pressed once they're pushed past the deadzone in the direction
+ * they're bound to.
+ * Files tell them apart by their fields, so a key is written as (key: "W") in RON, and a button as
+ * { button = "a" } in TOML.
+ */
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Control {
+  Key { key: String },
+  Button { button: String },
+  Axis { axis: String, positive: bool }
+}
+
+impl Control {
+  pub fn name(&self) -> String {
+    match self {
+      Control::Key { key } => return key.clone(),
+      Control::Button { button } => return button.clone(),
+      Control::Axis { axis, positive: true } => return format!("{}+", axis),
+      Control::Axis { axis, positive: false } => return format!("{}-", axis)
+    }
+  }
+
+  pub fn is_key(&self) -> bool {
+    return matches!(self, Control::Key { .. });
+  }
+}
+
+// A struct representing the control bound to each action for a single player.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Layout {
+  up: Control,
+  down: Control,
+  left: Control,
+  right: Control,
+  place_bomb: Control,
+  detonate: Control,
+  special: Control
+}
+
+impl Layout {
+  // Makes a layout out of keys, given in the same order as the actions.
+  pub fn keys(keys: [&str; 7]) -> Layout {
+    let key = |i: usize| Control::Key { key: String::from(keys[i]) };
+    return Layout {
+      up: key(0),
+      down: key(1),
+      left: key(2),
+      right: key(3),
+      place_bomb: key(4),
+      detonate: key(5),
+      special: key(6)
+    }
+  }
+
+  // Makes a layout out of controller buttons, given in the same order as the actions.
+  pub fn buttons(buttons: [&str; 7]) -> Layout {
+    let button = |i: usize| Control::Button { button: String::from(buttons[i]) };
+    return Layout {
+      up: button(0),
+      down: button(1),
+      left: button(2),
+      right: button(3),
+      place_bomb: button(4),
+      detonate: button(5),
+      special: button(6)
+    }
+  }
+
+  pub fn get(&self, action: &Action) -> &Control {
+    match action {
+      Action::Up => return &self.up,
+      Action::Down => return &self.down,
+      Action::Left => return &self.left,
+      Action::Right => return &self.right,
+      Action::PlaceBomb => return &self.place_bomb,
+      Action::Detonate => return &self.detonate,
+      Action::Special => return &self.special
+    }
+  }
+
+  pub fn set(&self, action: &Action, control: Control) -> Layout {
+    let mut layout: Layout = self.clone();
+    match action {
+      Action::Up => layout.up = control,
+      Action::Down => layout.down = control,
+      Action::Left => layout.left = control,
+      Action::Right => layout.right = control,
+      Action::PlaceBomb => layout.place_bomb = control,
+      Action::Detonate => layout.detonate = control,
+      Action::Special => layout.special = control
+    }
+    return layout;
+  }
+
+  // Returns the action a control is bound to in this layout, if any.
+  pub fn find(&self, control: &Control) -> Option<Action> {
+    return ACTIONS.iter().find(|action| self.get(action) == control).copied();
+  }
+}
+
+/* A struct representing the controls everyone plays with.
+ *
+ * Keyboards are the layouts for each keyboard player slot. They all share the one keyboard, so no
+ * two of them can use the same key.
+ * Gamepads are the layouts for each controller player slot, going by the order the controllers
+ * were plugged in. A controller's left stick always moves its player too, on top of whatever the
+ * directions are bound to.
+ * Deadzone is how far sticks and triggers have to be pushed before they count, from 0 to 1.
+ *
+ * Like rules, anything missing from a bindings file falls back to its default.
+ */
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Bindings {
+  // TOML needs plain values before tables, so the deadzone has to come first.
+  deadzone: f32,
+  keyboards: Vec<Layout>,
+  gamepads: Vec<Layout>
+}
+
+impl Default for Bindings {
+  fn default() -> Bindings {
+    let pad: Layout = Layout::buttons(["dpup", "dpdown", "dpleft", "dpright", "a", "b", "x"]);
+    return Bindings {
+      deadzone: 0.25,
+      keyboards: vec!(
+        Layout::keys(["W", "S", "A", "D", "Space", "Left Shift", "Left Ctrl"]),
+        Layout::keys(["Up", "Down", "Left", "Right", "Return", "Right Shift", "Right Ctrl"]),
+        Layout::keys(["I", "K", "J", "L", "U", "O", "P"]),
+        Layout::keys([
+          "Keypad 8", "Keypad 5", "Keypad 4", "Keypad 6", "Keypad 0", "Keypad Enter", "Keypad +"
+        ])
+      ),
+      gamepads: vec!(pad; GAMEPADS as usize)
+    }
+  }
+}
+
+impl Bindings {
+  // Loads bindings from a TOML or RON file; see config.
+  pub fn from_file(path: &Path) -> Result<Bindings, String> {
+    return config::load::<Bindings>(path, "bindings").and_then(Bindings::validate);
+  }
+
+  pub fn from_toml(contents: &str) -> Result<Bindings, String> {
+    return config::from_toml::<Bindings>(contents, "bindings").and_then(Bindings::validate);
+  }
+
+  pub fn from_ron(contents: &str) -> Result<Bindings, String> {
+    return config::from_ron::<Bindings>(contents, "bindings").and_then(Bindings::validate);
+  }
+
+  pub fn save(&self, path: &Path) -> Result<(), String> {
+    return config::save(self, path, "bindings");
+  }
+
+  // Rejects bindings that would leave a slot without controls, or players fighting over them.
+  fn validate(self) -> Result<Bindings, String> {
+    if self.keyboards.len() != KEYBOARD_LAYOUTS as usize {
+      return Err(format!("There must be exactly {} keyboard layouts.", KEYBOARD_LAYOUTS));
+    } else if self.gamepads.len() != GAMEPADS as usize {
+      return Err(format!("There must be exactly {} gamepad layouts.", GAMEPADS));
+    } else if !(0.0..1.0).contains(&self.deadzone) {
+      return Err(String::from("Deadzone must be at least 0 and below 1."));
+    }
+
+    let keys: Vec<&Control> = self.keyboards.iter()
+      .flat_map(|layout| ACTIONS.iter().map(move |action| layout.get(action)))
+      .collect();
+    if let Some(control) = keys.iter().find(|control| !control.is_key()) {
+      return Err(format!("Keyboard layouts can only use keys, not {}.", control.name()));
+    } else if let Some(key) = keys.iter().enumerate().find(|(i, key)| keys[..*i].contains(key)) {
+      return Err(format!("{} is bound more than once across the keyboard layouts.", key.1.name()));
+    }
+    for layout in self.gamepads.iter() {
+      if let Some(action) = ACTIONS.iter().find(|action| layout.get(action).is_key()) {
+        return Err(format!("Gamepad layouts can't use keys, like {}.", layout.get(action).name()));
+      }
+    }
+    return Ok(self);
+  }
+
+  // Returns the layout a player slot uses, if it's a keyboard or gamepad one.
+  pub fn get_layout(&self, slot: &Slot) -> Option<&Layout> {
+    match slot {
+      Slot::Keyboard(layout) => return self.keyboards.get(*layout as usize),
+      Slot::Gamepad(pad) => return self.gamepads.get(*pad as usize),
+      Slot::Off | Slot::Bot(_) => return None
+    }
+  }
+
+  pub fn get_keyboards(&self) -> &Vec<Layout> {
+    return &self.keyboards;
+  }
+
+  pub fn get_gamepads(&self) -> &Vec<Layout> {
+    return &self.gamepads;
+  }
+
+  pub fn get_deadzone(&self) -> &f32 {
+    return &self.deadzone;
+  }
+
+  /* Binds a control to an action in a player slot's layout. Keyboard slots have to be bound to keys
+   * and gamepad slots to buttons or axes, so anything else is ignored.
+   * Whatever the control was already bound to that it would now clash with gets the action's old
+   * control instead, so rebinding swaps controls rather than leaving two actions on the same one.
+   */
+  pub fn bind(&self, slot: &Slot, action: &Action, control: Control) -> Bindings {
+    let old: Control = match self.get_layout(slot) {
+      Some(layout) => layout.get(action).clone(),
+      None => return self.clone()
+    };
+    let mut bindings: Bindings = self.clone();
+    let layouts: &mut Vec<Layout> = match slot {
+      Slot::Keyboard(_) if control.is_key() => &mut bindings.keyboards,
+      Slot::Gamepad(_) if !control.is_key() => &mut bindings.gamepads,
+      _ => return bindings
+    };
+    // Keyboard layouts clash with each other, but gamepads only clash with themselves.
+    let (index, shared): (usize, bool) = match slot {
+      Slot::Keyboard(layout) => (*layout as usize, true),
+      Slot::Gamepad(pad) => (*pad as usize, false),
+      Slot::Off | Slot::Bot(_) => return bindings
+    };
+    for (i, layout) in layouts.iter_mut().enumerate() {
+      if i != index && !shared {
+        continue;
+      }
+      if let Some(clash) = layout.find(&control) {
+        *layout = layout.set(&clash, old.clone());
+      }
+    }
+    layouts[index] = layouts[index].set(action, control);
+    return bindings;
+  }
+
+  pub fn set_deadzone(&self, deadzone: f32) -> Bindings {
+    return Bindings {
+      deadzone: deadzone,
+      ..self.clone()
+    }
+  }
+}
+
+/* Returns the direction a stick is pushed in, snapped to the nearest of the eight, or none if it's
+ * still within the deadzone. Axes go from -1 to 1, with positive y pointing north.
+ */
+pub fn stick_direction(x: f32, y: f32, deadzone: f32) -> Option<Direction> {
+  let distance: f32 = x.hypot(y);
+  if distance <= deadzone || distance == 0.0 {
+    return None;
+  }
+  /* Each direction covers 45 degrees, so an axis only counts once the stick is more than half of
+   * that away from the other axis.
+   */
+  let threshold: f32 = distance * (PI / 8.0).sin();
+  let axis = |value: f32| -> i8 {
+    return (value > threshold) as i8 - (value < -threshold) as i8;
+  };
+  return Direction::from_axes(axis(x), axis(y));
+}
+
+// Returns whether an axis is pushed past the deadzone in a direction, going from -1 to 1.
+pub fn axis_pushed(value: f32, positive: bool, deadzone: f32) -> bool {
+  match positive {
+    true => return value > deadzone,
+    false => return value < -deadzone
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn sticks_in_the_deadzone_point_nowhere() {
+    assert_eq!(stick_direction(0.0, 0.0, 0.0), None);
+    assert_eq!(stick_direction(0.2, -0.1, 0.3), None);
+  }
+
+  #[test]
+  fn sticks_snap_to_the_nearest_direction() {
+    assert_eq!(stick_direction(0.0, 1.0, 0.3), Some(Direction::North));
+    assert_eq!(stick_direction(0.3, -0.9, 0.3), Some(Direction::South));
+    assert_eq!(stick_direction(-1.0, 0.2, 0.3), Some(Direction::West));
+    assert_eq!(stick_direction(0.7, 0.7, 0.3), Some(Direction::Northeast));
+    assert_eq!(stick_direction(-0.6, -0.8, 0.3), Some(Direction::Southwest));
+  }
+
+  #[test]
+  fn axes_only_count_past_the_deadzone() {
+    assert!(axis_pushed(0.5, true, 0.3));
+    assert!(!axis_pushed(0.2, true, 0.3));
+    assert!(!axis_pushed(0.5, false, 0.3));
+    assert!(axis_pushed(-0.5, false, 0.3));
+  }
+}
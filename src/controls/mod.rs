@@ -0,0 +1,3 @@
+pub mod bindings;
+#[cfg(feature = "sdl")]
+pub mod sdlcontrols;
@@ -0,0 +1,274 @@
+use crate::{
+  common::direction::Direction,
+  controls::bindings::{self, Bindings, Control, Layout, ACTIONS},
+  menu::setup::Slot,
+  model::input::{self, InputFrame, PlayerInput}
+};
+use sdl2::{
+  controller::{Axis, Button, GameController},
+  event::Event,
+  keyboard::{KeyboardState, Keycode, Scancode},
+  GameControllerSubsystem
+};
+use std::collections::HashMap;
+
+// How far an axis has to be pushed to get bound while rebinding, which is well past any deadzone.
+const BIND_THRESHOLD: f32 = 0.5;
+
+// An enum representing a control once SDL has made sense of its name.
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum SdlControl {
+  Key(Keycode),
+  Button(Button),
+  Axis(Axis, bool)
+}
+
+impl SdlControl {
+  fn resolve(control: &Control) -> Result<SdlControl, String> {
+    match control {
+      Control::Key { key } => {
+        return Keycode::from_name(key).map(SdlControl::Key)
+          .ok_or(format!("{} isn't a key SDL knows about.", key));
+      },
+      Control::Button { button } => {
+        return Button::from_string(button).map(SdlControl::Button)
+          .ok_or(format!("{} isn't a controller button SDL knows about.", button));
+      },
+      Control::Axis { axis, positive } => {
+        return Axis::from_string(axis).map(|axis| SdlControl::Axis(axis, *positive))
+          .ok_or(format!("{} isn't a controller axis SDL knows about.", axis));
+      }
+    }
+  }
+}
+
+// Returns an axis's value from -1 to 1, with positive being right or down like SDL has it.
+fn axis_value(value: i16) -> f32 {
+  return (value as f32 / i16::MAX as f32).max(-1.0);
+}
+
+/* A struct representing the bindings as SDL sees them, along with everything needed to turn SDL's
+ * keyboard and controllers into player inputs.
+ *
+ * Pads are the controllers that are plugged in, in the order they were plugged in, which is what
+ * gamepad slots go by.
+ * Players is what controls each player in the current match, indexed by player ID.
+ * Presses are the buttons pressed since the last tick, which should be cleared once a tick has seen
+ * them. Axes are the last known value of every controller axis, so that pushing one past the
+ * deadzone counts as a single press.
+ */
+pub struct SdlControls {
+  controllers: GameControllerSubsystem,
+  pads: Vec<GameController>,
+  keyboards: Vec<[SdlControl; 7]>,
+  gamepads: Vec<[SdlControl; 7]>,
+  deadzone: f32,
+  players: Vec<Slot>,
+  presses: InputFrame,
+  axes: HashMap<(u32, Axis), i16>
+}
+
+/* Resolves every control in some layouts, failing on the first one SDL doesn't know about. Each
+ * layout's controls are kept in the same order as the actions.
+ */
+fn resolve_layouts(layouts: &[Layout]) -> Result<Vec<[SdlControl; 7]>, String> {
+  return layouts.iter()
+    .map(|layout| {
+      // Every one of these gets replaced, so it doesn't matter what they start as.
+      let mut controls: [SdlControl; 7] = [SdlControl::Button(Button::A); 7];
+      for (control, action) in controls.iter_mut().zip(ACTIONS.iter()) {
+        *control = SdlControl::resolve(layout.get(action))?;
+      }
+      return Ok(controls);
+    })
+    .collect();
+}
+
+impl SdlControls {
+  pub fn new(
+    controllers: GameControllerSubsystem, bindings: &Bindings
+  ) -> Result<SdlControls, String> {
+    let mut controls: SdlControls = SdlControls {
+      controllers: controllers,
+      pads: vec!(),
+      keyboards: vec!(),
+      gamepads: vec!(),
+      deadzone: 0.0,
+      players: vec!(),
+      presses: input::idle_frame(),
+      axes: HashMap::new()
+    };
+    controls.set_bindings(bindings)?;
+    return Ok(controls);
+  }
+
+  // Switches to new bindings, which fails without changing anything if SDL doesn't know a control.
+  pub fn set_bindings(&mut self, bindings: &Bindings) -> Result<(), String> {
+    let keyboards: Vec<[SdlControl; 7]> = resolve_layouts(bindings.get_keyboards())?;
+    self.gamepads = resolve_layouts(bindings.get_gamepads())?;
+    self.keyboards = keyboards;
+    self.deadzone = *bindings.get_deadzone();
+    return Ok(());
+  }
+
+  // Starts reading inputs for a new set of players, forgetting any presses from before.
+  pub fn set_players(&mut self, players: Vec<Slot>) {
+    self.players = players;
+    self.presses = input::idle_frame();
+  }
+
+  pub fn clear_presses(&mut self) {
+    self.presses = input::idle_frame();
+  }
+
+  // Returns the index of a pad from its instance ID.
+  fn pad_index(&self, instance: u32) -> Option<usize> {
+    return self.pads.iter().position(|pad| pad.instance_id() == instance);
+  }
+
+  // Returns the controls of whichever players a key or pad button belongs to.
+  fn layouts(&self, pad: Option<usize>) -> Vec<(usize, [SdlControl; 7])> {
+    return self.players.iter().enumerate()
+      .filter_map(|(id, slot)| {
+        match (slot, pad) {
+          (Slot::Keyboard(layout), None) => return Some((id, self.keyboards[*layout as usize])),
+          (Slot::Gamepad(index), Some(pad)) if *index as usize == pad => {
+            return Some((id, self.gamepads[pad]));
+          },
+          _ => return None
+        }
+      })
+      .collect();
+  }
+
+  // Records a control being pressed as a button press for whichever players have it bound.
+  fn press(&mut self, pad: Option<usize>, pressed: &SdlControl) {
+    for (id, controls) in self.layouts(pad) {
+      let input: &PlayerInput = &self.presses[id];
+      self.presses[id] = PlayerInput::new(
+        *input.get_direction(),
+        *input.get_place_bomb() || controls[4] == *pressed,
+        *input.get_detonate() || controls[5] == *pressed,
+        *input.get_special() || controls[6] == *pressed
+      );
+    }
+  }
+
+  /* Keeps track of controllers being plugged in and out, and records presses from the keyboard and
+   * controllers.
+   */
+  pub fn handle(&mut self, event: &Event) {
+    match event {
+      Event::ControllerDeviceAdded { which, .. } => {
+        // Pads that don't open are left out, and can be tried again by plugging them back in.
+        if let Ok(pad) = self.controllers.open(*which) {
+          if self.pad_index(pad.instance_id()).is_none() {
+            self.pads.push(pad);
+          }
+        }
+      },
+      Event::ControllerDeviceRemoved { which, .. } => {
+        if let Some(index) = self.pad_index(*which) {
+          self.pads.remove(index);
+        }
+      },
+      Event::KeyDown { keycode: Some(keycode), repeat: false, .. } => {
+        self.press(None, &SdlControl::Key(*keycode));
+      },
+      Event::ControllerButtonDown { which, button, .. } => {
+        let pad: Option<usize> = self.pad_index(*which);
+        if pad.is_some() {
+          self.press(pad, &SdlControl::Button(*button));
+        }
+      },
+      Event::ControllerAxisMotion { which, axis, value, .. } => {
+        let last: i16 = self.axes.insert((*which, *axis), *value).unwrap_or(0);
+        let pad: Option<usize> = self.pad_index(*which);
+        for positive in [true, false] {
+          let pushed = |value: i16| -> bool {
+            return bindings::axis_pushed(axis_value(value), positive, self.deadzone);
+          };
+          if pad.is_some() && pushed(*value) && !pushed(last) {
+            self.press(pad, &SdlControl::Axis(*axis, positive));
+          }
+        }
+      },
+      _ => {}
+    }
+  }
+
+  // Returns whether a control is being held down right now.
+  fn is_held(
+    &self, keyboard: &KeyboardState, pad: Option<&GameController>, control: &SdlControl
+  ) -> bool {
+    match (control, pad) {
+      (SdlControl::Key(keycode), _) => {
+        return Scancode::from_keycode(*keycode)
+          .is_some_and(|scancode| keyboard.is_scancode_pressed(scancode));
+      },
+      (SdlControl::Button(button), Some(pad)) => return pad.button(*button),
+      (SdlControl::Axis(axis, positive), Some(pad)) => {
+        return bindings::axis_pushed(axis_value(pad.axis(*axis)), *positive, self.deadzone);
+      },
+      _ => return false
+    }
+  }
+
+  /* Works out which way each player is holding, keeping any buttons that have been pressed.
+   * Controllers go by their bound directions first, and their left stick otherwise. Anyone that
+   * isn't on a keyboard or controller, like bots, is left idle.
+   */
+  pub fn read_inputs(&self, keyboard: &KeyboardState) -> InputFrame {
+    let mut frame: InputFrame = self.presses;
+    for (id, slot) in self.players.iter().enumerate() {
+      let (controls, pad): ([SdlControl; 7], Option<&GameController>) = match slot {
+        Slot::Keyboard(layout) => (self.keyboards[*layout as usize], None),
+        Slot::Gamepad(index) => {
+          match self.pads.get(*index as usize) {
+            Some(pad) => (self.gamepads[*index as usize], Some(pad)),
+            None => continue
+          }
+        },
+        Slot::Off | Slot::Bot(_) => continue
+      };
+      let axis = |positive: &SdlControl, negative: &SdlControl| -> i8 {
+        return self.is_held(keyboard, pad, positive) as i8
+          - self.is_held(keyboard, pad, negative) as i8;
+      };
+      let mut direction: Option<Direction> =
+        Direction::from_axes(axis(&controls[3], &controls[2]), axis(&controls[0], &controls[1]));
+      if let (None, Some(pad)) = (direction, pad) {
+        // Sticks point down as they go positive, which is the other way round from the world.
+        let x: f32 = axis_value(pad.axis(Axis::LeftX));
+        let y: f32 = -axis_value(pad.axis(Axis::LeftY));
+        direction = bindings::stick_direction(x, y, self.deadzone);
+      }
+      let input: &PlayerInput = &self.presses[id];
+      frame[id] = PlayerInput::new(
+        direction, *input.get_place_bomb(), *input.get_detonate(), *input.get_special()
+      );
+    }
+    return frame;
+  }
+
+  /* Returns the control an event pressed, for binding it. Axes only count once they're pushed well
+   * past the deadzone, so that a stick resting a little off center doesn't get bound.
+   */
+  pub fn pressed(event: &Event) -> Option<Control> {
+    match event {
+      Event::KeyDown { keycode: Some(keycode), repeat: false, .. } => {
+        return Some(Control::Key { key: keycode.name() });
+      },
+      Event::ControllerButtonDown { button, .. } => {
+        return Some(Control::Button { button: button.string() });
+      },
+      Event::ControllerAxisMotion { axis, value, .. } => {
+        if axis_value(*value).abs() <= BIND_THRESHOLD {
+          return None;
+        }
+        return Some(Control::Axis { axis: axis.string(), positive: *value > 0 });
+      },
+      _ => return None
+    }
+  }
+}
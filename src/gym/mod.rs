@@ -0,0 +1,278 @@
+use crate::{
+  common::direction::Direction,
+  model::{
+    event::WorldEvent,
+    input::{self, InputFrame, PlayerInput, MAX_PLAYERS},
+    rules::Rules,
+    stage::Stage,
+    world::{World, WorldImpl}
+  }
+};
+use ndarray::{Array, Axis, Ix4};
+use reward::Rewards;
+use std::thread;
+
+pub mod observation;
+pub mod reward;
+
+/* An enum representing everything an agent can do on a step. Moves are held for the whole step,
+ * while buttons are only pressed on its first tick.
+ */
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Action {
+  Idle,
+  North,
+  South,
+  West,
+  East,
+  PlaceBomb,
+  Detonate,
+  Special
+}
+
+// Every action, so that agents can pick one by index.
+pub const ACTIONS: [Action; 8] = [
+  Action::Idle, Action::North, Action::South, Action::West, Action::East,
+  Action::PlaceBomb, Action::Detonate, Action::Special
+];
+
+impl Action {
+  pub fn to_input(&self) -> PlayerInput {
+    match self {
+      Action::Idle => return PlayerInput::default(),
+      Action::North => return PlayerInput::new(Some(Direction::North), false, false, false),
+      Action::South => return PlayerInput::new(Some(Direction::South), false, false, false),
+      Action::West => return PlayerInput::new(Some(Direction::West), false, false, false),
+      Action::East => return PlayerInput::new(Some(Direction::East), false, false, false),
+      Action::PlaceBomb => return PlayerInput::new(None, true, false, false),
+      Action::Detonate => return PlayerInput::new(None, false, true, false),
+      Action::Special => return PlayerInput::new(None, false, false, true)
+    }
+  }
+}
+
+/* What every player sees after a step, stacked by player ID, so that a player's observation lives
+ * at [id]; see observation.
+ */
+pub type Observations = Array<f32, Ix4>;
+
+/* A struct representing a gym-style environment over a world, for training agents. It never touches
+ * SDL, and everything it does happens in place, so lots of them can be stepped at once; see
+ * EnvBatch.
+ *
+ * Every player in the world is an agent, and a step takes an action from each of them. Ticks per
+ * step is how many ticks of the world each step runs for, which lets agents act less often than the
+ * world ticks.
+ *
+ * Steps stop early once the round is over, and stepping an environment whose round is over does
+ * nothing until it's reset.
+ */
+pub struct Env {
+  rules: Rules,
+  stage: Box<dyn Stage>,
+  player_count: usize,
+  rewards: Rewards,
+  ticks_per_step: u32,
+  world: WorldImpl
+}
+
+impl Env {
+  // Makes an environment, which starts off with a round seeded with 0.
+  pub fn new(
+    rules: Rules, stage: Box<dyn Stage>, player_count: usize, rewards: Rewards, ticks_per_step: u32
+  ) -> Result<Env, String> {
+    if player_count == 0 || player_count > MAX_PLAYERS {
+      return Err(format!("Environments need between 1 and {} players.", MAX_PLAYERS));
+    } else if player_count > stage.get_spawns().len() {
+      return Err(format!("The stage only fits {} players.", stage.get_spawns().len()));
+    } else if ticks_per_step == 0 {
+      return Err(String::from("Each step has to run for at least 1 tick."));
+    }
+    let world: WorldImpl = WorldImpl::start(rules, stage.copy(), player_count, 0);
+    return Ok(
+      Env {
+        rules: rules,
+        stage: stage,
+        player_count: player_count,
+        rewards: rewards,
+        ticks_per_step: ticks_per_step,
+        world: world
+      }
+    );
+  }
+
+  // Starts a new round with a seed, returning what everyone sees at the start of it.
+  pub fn reset(&mut self, seed: u64) -> Observations {
+    self.world = WorldImpl::start(self.rules, self.stage.copy(), self.player_count, seed);
+    return self.observe();
+  }
+
+  /* Runs a step, where each player takes the action at their ID. Players without an action don't
+   * do anything. Returns what everyone sees afterwards, what each of them earned during the step,
+   * and whether the round is over.
+   */
+  pub fn step(&mut self, actions: &[Action]) -> (Observations, Vec<f32>, bool) {
+    let mut rewards: Vec<f32> = vec!(0.0; self.player_count);
+    let mut frame: InputFrame = input::idle_frame();
+    for (input, action) in frame.iter_mut().zip(actions.iter()) {
+      *input = action.to_input();
+    }
+    for _ in 0..self.ticks_per_step {
+      if self.is_done() {
+        break;
+      }
+      let events: Vec<WorldEvent> = self.world.tick_mut(&frame);
+      self.rewards.add(&self.world, &events, &mut rewards);
+      frame = input::held_frame(&frame);
+    }
+    return (self.observe(), rewards, self.is_done());
+  }
+
+  // Returns what every player sees right now.
+  pub fn observe(&self) -> Observations {
+    let (planes, height, width): (usize, usize, usize) = observation::shape(&self.world);
+    let mut observations: Observations =
+      Array::zeros((self.player_count, planes, height, width));
+    for (id, mut view) in observations.axis_iter_mut(Axis(0)).enumerate() {
+      observation::encode_into(&self.world, &(id as u8), &mut view);
+    }
+    return observations;
+  }
+
+  pub fn is_done(&self) -> bool {
+    return self.world.get_outcome().is_some();
+  }
+
+  pub fn get_world(&self) -> &WorldImpl {
+    return &self.world;
+  }
+
+  pub fn get_rules(&self) -> &Rules {
+    return &self.rules;
+  }
+
+  pub fn get_player_count(&self) -> &usize {
+    return &self.player_count;
+  }
+
+  pub fn get_rewards(&self) -> &Rewards {
+    return &self.rewards;
+  }
+
+  pub fn get_ticks_per_step(&self) -> &u32 {
+    return &self.ticks_per_step;
+  }
+}
+
+impl Clone for Env {
+  fn clone(&self) -> Env {
+    return Env {
+      rules: self.rules,
+      stage: self.stage.copy(),
+      player_count: self.player_count,
+      rewards: self.rewards,
+      ticks_per_step: self.ticks_per_step,
+      world: self.world.clone()
+    }
+  }
+}
+
+/* A struct representing a batch of environments that are stepped together, spread out over a
+ * number of threads. Anything that wants a thread for each core the machine has can ask
+ * thread::available_parallelism for how many that is.
+ */
+pub struct EnvBatch {
+  envs: Vec<Env>,
+  threads: usize
+}
+
+impl EnvBatch {
+  // Makes a batch of copies of an environment, which are stepped on up to a number of threads.
+  pub fn new(env: &Env, count: usize, threads: usize) -> EnvBatch {
+    return EnvBatch {
+      envs: vec!(env.clone(); count),
+      threads: threads.max(1)
+    }
+  }
+
+  // Runs something on every environment, spreading them out over the batch's threads.
+  fn run<T: Send>(&mut self, f: impl Fn(usize, &mut Env) -> T + Sync) -> Vec<T> {
+    let chunk_size: usize = self.envs.len().div_ceil(self.threads).max(1);
+    let f: &(dyn Fn(usize, &mut Env) -> T + Sync) = &f;
+    return thread::scope(|scope| {
+      let handles: Vec<thread::ScopedJoinHandle<Vec<T>>> = self.envs.chunks_mut(chunk_size)
+        .enumerate()
+        .map(|(chunk, envs)| {
+          return scope.spawn(move || {
+            return envs.iter_mut().enumerate()
+              .map(|(i, env)| f(chunk * chunk_size + i, env))
+              .collect::<Vec<T>>();
+          });
+        })
+        .collect();
+      return handles.into_iter()
+        .flat_map(|handle| handle.join().expect("An environment panicked"))
+        .collect();
+    });
+  }
+
+  // Resets every environment, seeding each one with the seed plus its index in the batch.
+  pub fn reset(&mut self, seed: u64) -> Vec<Observations> {
+    return self.run(|i, env| env.reset(seed.wrapping_add(i as u64)));
+  }
+
+  /* Steps every environment, where each one takes the actions at its index in the batch. Results
+   * are in the same order as the environments.
+   */
+  pub fn step(&mut self, actions: &[Vec<Action>]) -> Vec<(Observations, Vec<f32>, bool)> {
+    return self.run(|i, env| env.step(actions.get(i).map_or(&[], Vec::as_slice)));
+  }
+
+  pub fn get_envs(&self) -> &Vec<Env> {
+    return &self.envs;
+  }
+
+  pub fn get_envs_mut(&mut self) -> &mut Vec<Env> {
+    return &mut self.envs;
+  }
+
+  pub fn get_threads(&self) -> &usize {
+    return &self.threads;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::model::stage::{StageImpl, STAGES};
+
+  type Episode = (Observations, Vec<(Observations, Vec<f32>, bool)>);
+
+  // Plays an episode from a seed, with every player cycling through the actions.
+  fn play(env: &mut Env, seed: u64) -> Episode {
+    let start: Observations = env.reset(seed);
+    let steps: Vec<(Observations, Vec<f32>, bool)> = (0..150)
+      .map(|step| {
+        let actions: Vec<Action> = (0..*env.get_player_count())
+          .map(|id| ACTIONS[(step * 3 + id * 5) % ACTIONS.len()])
+          .collect();
+        return env.step(&actions);
+      })
+      .collect();
+    return (start, steps);
+  }
+
+  #[test]
+  fn resetting_with_the_same_seed_plays_the_same_episode() {
+    let stage: StageImpl = StageImpl::from_layout(STAGES[0].1).unwrap();
+    let mut env: Env =
+      Env::new(Rules::default(), Box::new(stage), 4, Rewards::default(), 4).unwrap();
+    let first: Episode = play(&mut env, 7);
+    let other: Episode = play(&mut env, 3);
+    let again: Episode = play(&mut env, 7);
+    // Somebody has to have earned something, or there's nothing for the seed to change.
+    assert!(first.1.iter().any(|(_, rewards, _)| rewards.iter().any(|reward| *reward != 0.0)));
+    assert!(first == again);
+    assert!(first != other);
+  }
+}
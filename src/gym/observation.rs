@@ -0,0 +1,181 @@
+use crate::model::{
+  input::PlayerId,
+  item::{ITEMS, MAX_RANGE},
+  stage::Tile,
+  world::World
+};
+use ndarray::{Array, ArrayViewMut3, Ix3};
+
+/* Observations are a stack of planes the size of the stage, each holding one thing about every
+ * tile, with every value between 0 and 1. They're seen from one player's point of view, so that the
+ * same agent can play as anyone.
+ *
+ * Planes are indexed by the constants below, and tiles are stored row by row like a stage's, so a
+ * position of (x, y) on a plane lives at [plane, y, x].
+ */
+
+pub const HARD_WALLS: usize = 0;
+pub const SOFT_WALLS: usize = 1;
+// Where bombs are, as how far each one reaches out of MAX_RANGE.
+pub const BOMBS: usize = 2;
+// How much of each bomb's fuse is left, out of a full fuse. Remote bombs always have all of theirs.
+pub const FUSES: usize = 3;
+// Every tile covered by a blast.
+pub const FLAMES: usize = 4;
+// The first of the item planes, which hold where each item is in the same order as ITEMS.
+pub const ITEM_PLANES: usize = 5;
+// Where the player being observed is, if they're still alive.
+pub const SELF: usize = ITEM_PLANES + ITEMS.len();
+// Where everyone else who's still alive is.
+pub const ENEMIES: usize = SELF + 1;
+
+// How many planes an observation has.
+pub const PLANES: usize = ENEMIES + 1;
+
+// Returns the shape of an observation of a world.
+pub fn shape(world: &dyn World) -> (usize, usize, usize) {
+  let dimensions: &(i8, i8) = world.get_stage().get_dimensions();
+  return (PLANES, dimensions.1 as usize, dimensions.0 as usize);
+}
+
+// Returns what a world looks like to a player.
+pub fn encode(world: &dyn World, viewer: &PlayerId) -> Array<f32, Ix3> {
+  let mut observation: Array<f32, Ix3> = Array::zeros(shape(world));
+  encode_into(world, viewer, &mut observation.view_mut());
+  return observation;
+}
+
+/* Writes what a world looks like to a player into an observation that's already the right shape,
+ * which saves allocating a new one every step.
+ */
+pub fn encode_into(world: &dyn World, viewer: &PlayerId, observation: &mut ArrayViewMut3<f32>) {
+  observation.fill(0.0);
+  for ((y, x), tile) in world.get_stage().get_tiles().indexed_iter() {
+    match tile {
+      Tile::HardWall => observation[[HARD_WALLS, y, x]] = 1.0,
+      Tile::SoftWall => observation[[SOFT_WALLS, y, x]] = 1.0,
+      Tile::Ground => {}
+    }
+  }
+
+  let fuse: f32 = *world.get_rules().get_bomb_fuse() as f32;
+  for bomb in world.get_bombs() {
+    let (x, y): (usize, usize) = (bomb.get_position().0 as usize, bomb.get_position().1 as usize);
+    observation[[BOMBS, y, x]] = *bomb.get_range() as f32 / MAX_RANGE as f32;
+    observation[[FUSES, y, x]] = (*bomb.get_lifetime()).max(0) as f32 / fuse;
+  }
+
+  for blast in world.get_blasts() {
+    let center: &(i8, i8) = blast.get_center();
+    observation[[FLAMES, center.1 as usize, center.0 as usize]] = 1.0;
+    for flame in blast.get_flames() {
+      let mut tile: (i8, i8) = *flame.get_start();
+      observation[[FLAMES, tile.1 as usize, tile.0 as usize]] = 1.0;
+      while &tile != flame.get_end() {
+        tile = flame.get_direction().step(&tile);
+        observation[[FLAMES, tile.1 as usize, tile.0 as usize]] = 1.0;
+      }
+    }
+  }
+
+  for (position, item) in world.get_items() {
+    let plane: usize = ITEM_PLANES + ITEMS.iter().position(|other| other == item).unwrap_or(0);
+    observation[[plane, position.1 as usize, position.0 as usize]] = 1.0;
+  }
+
+  for player in world.get_players().iter().filter(|player| *player.get_alive()) {
+    let tile: (i8, i8) = player.get_tile();
+    let plane: usize = match player.get_id() == viewer {
+      true => SELF,
+      false => ENEMIES
+    };
+    observation[[plane, tile.1 as usize, tile.0 as usize]] = 1.0;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    common::direction::Direction,
+    model::{
+      bomb::{Bomb, BombImpl},
+      input,
+      player::Player,
+      position,
+      rules::Rules,
+      stage::{Stage, StageImpl},
+      world::WorldImpl
+    }
+  };
+  use ndarray::Axis;
+
+  // Starts a round on a stage layout with a player on each spawn, and any bombs given.
+  fn start(layout: &str, bombs: Vec<BombImpl>) -> WorldImpl {
+    let rules: Rules = Rules::default();
+    let stage: StageImpl = StageImpl::from_layout(layout).unwrap();
+    let players: Vec<Player> = stage.get_spawns().iter().enumerate()
+      .map(|(id, spawn)| {
+        return Player::new(
+          id as PlayerId, position::from_tile(spawn), Direction::South, rules.get_starting_stats()
+        );
+      })
+      .collect();
+    let bombs: Vec<Box<dyn Bomb>> =
+      bombs.into_iter().map(|bomb| Box::new(bomb) as Box<dyn Bomb>).collect();
+    return WorldImpl::new(rules, Box::new(stage), players, bombs, vec!(), 0);
+  }
+
+  // Returns every tile that's set on a plane of an observation.
+  fn lit(observation: &Array<f32, Ix3>, plane: usize) -> Vec<(i8, i8)> {
+    let mut tiles: Vec<(i8, i8)> = observation.index_axis(Axis(0), plane)
+      .indexed_iter()
+      .filter(|(_, value)| **value != 0.0)
+      .map(|((y, x), _)| (x as i8, y as i8))
+      .collect();
+    tiles.sort();
+    return tiles;
+  }
+
+  #[test]
+  fn observations_hold_everything_on_a_known_stage() {
+    let fuse: i16 = *Rules::default().get_bomb_fuse();
+    let bomb: BombImpl = BombImpl::new((1, 0), 1, fuse, false, false, 2);
+    let world: WorldImpl = start("0.+.\n.#..\n...1", vec!(bomb));
+    let observation: Array<f32, Ix3> = encode(&world, &0);
+    assert_eq!(observation.shape(), &[PLANES, 3, 4]);
+
+    assert_eq!(lit(&observation, HARD_WALLS), vec!((1, 1)));
+    assert_eq!(lit(&observation, SOFT_WALLS), vec!((2, 2)));
+    assert_eq!(lit(&observation, BOMBS), vec!((1, 0)));
+    assert_eq!(observation[[BOMBS, 0, 1]], 2.0 / MAX_RANGE as f32);
+    assert_eq!(observation[[FUSES, 0, 1]], 1.0);
+    assert_eq!(lit(&observation, FLAMES), vec!());
+    for plane in ITEM_PLANES..SELF {
+      assert_eq!(lit(&observation, plane), vec!());
+    }
+    assert_eq!(lit(&observation, SELF), vec!((0, 2)));
+    assert_eq!(lit(&observation, ENEMIES), vec!((3, 0)));
+    assert!(observation.iter().all(|value| (0.0..=1.0).contains(value)));
+
+    // Everyone sees the same stage, but from their own point of view.
+    let other: Array<f32, Ix3> = encode(&world, &1);
+    assert_eq!(lit(&other, SELF), vec!((3, 0)));
+    assert_eq!(lit(&other, ENEMIES), vec!((0, 2)));
+    for plane in HARD_WALLS..SELF {
+      assert_eq!(other.index_axis(Axis(0), plane), observation.index_axis(Axis(0), plane));
+    }
+  }
+
+  #[test]
+  fn observations_show_blasts_and_leave_out_the_dead() {
+    let bomb: BombImpl = BombImpl::new((3, 1), 0, 1, false, false, 1);
+    let mut world: WorldImpl = start("....\n0...\n...1", vec!(bomb));
+    world.tick_mut(&input::idle_frame());
+    let observation: Array<f32, Ix3> = encode(&world, &0);
+    assert_eq!(lit(&observation, FLAMES), vec!((2, 1), (3, 0), (3, 1), (3, 2)));
+    assert_eq!(lit(&observation, BOMBS), vec!());
+    assert_eq!(lit(&observation, SELF), vec!((0, 1)));
+    assert_eq!(lit(&observation, ENEMIES), vec!());
+  }
+}
@@ -0,0 +1,234 @@
+use crate::{
+  common::config,
+  model::{
+    event::WorldEvent,
+    world::{Outcome, World}
+  }
+};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/* A struct representing how much everything that can happen to a player is worth to an agent.
+ *
+ * Win is for being the last player standing, and draw is for anyone still standing when a round
+ * ends in a draw.
+ * Death is for dying, whoever's blast it was, and kill is for catching someone else in a blast.
+ * Item is for picking up an item, and bomb is for placing a bomb.
+ * Alive is for every tick a player is still alive at the end of.
+ *
+ * Like rules, any field missing from a rewards file falls back to its default, so a file only has
+ * to mention what it changes.
+ */
+#[derive(Copy, Clone, PartialEq, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Rewards {
+  win: f32,
+  draw: f32,
+  death: f32,
+  kill: f32,
+  item: f32,
+  bomb: f32,
+  alive: f32
+}
+
+impl Default for Rewards {
+  fn default() -> Rewards {
+    return Rewards {
+      win: 1.0,
+      draw: 0.0,
+      death: -1.0,
+      kill: 0.5,
+      item: 0.1,
+      bomb: 0.0,
+      alive: 0.0
+    }
+  }
+}
+
+impl Rewards {
+  // Loads rewards from a TOML or RON file; see config.
+  pub fn from_file(path: &Path) -> Result<Rewards, String> {
+    return config::load(path, "rewards");
+  }
+
+  pub fn from_toml(contents: &str) -> Result<Rewards, String> {
+    return config::from_toml(contents, "rewards");
+  }
+
+  pub fn from_ron(contents: &str) -> Result<Rewards, String> {
+    return config::from_ron(contents, "rewards");
+  }
+
+  /* Adds up what each player earned during a tick of a world, given everything that happened
+   * during it. Totals are indexed by player ID, and anyone past the end of them is left out.
+   */
+  pub fn add(&self, world: &dyn World, events: &[WorldEvent], totals: &mut [f32]) {
+    let mut earn = |id: u8, reward: f32| {
+      if let Some(total) = totals.get_mut(id as usize) {
+        *total += reward;
+      }
+    };
+    for event in events {
+      match event {
+        WorldEvent::BombPlaced { owner, .. } => earn(*owner, self.bomb),
+        WorldEvent::ItemCollected { id, .. } => earn(*id, self.item),
+        WorldEvent::PlayerKilled { id, killer } => {
+          earn(*id, self.death);
+          if killer != id {
+            earn(*killer, self.kill);
+          }
+        },
+        WorldEvent::RoundEnded { outcome: Outcome::Win(id) } => earn(*id, self.win),
+        WorldEvent::RoundEnded { outcome: Outcome::Draw } => {
+          for player in world.get_players().iter().filter(|player| *player.get_alive()) {
+            earn(*player.get_id(), self.draw);
+          }
+        },
+        _ => {}
+      }
+    }
+    for player in world.get_players().iter().filter(|player| *player.get_alive()) {
+      earn(*player.get_id(), self.alive);
+    }
+  }
+
+  pub fn get_win(&self) -> &f32 {
+    return &self.win;
+  }
+
+  pub fn get_draw(&self) -> &f32 {
+    return &self.draw;
+  }
+
+  pub fn get_death(&self) -> &f32 {
+    return &self.death;
+  }
+
+  pub fn get_kill(&self) -> &f32 {
+    return &self.kill;
+  }
+
+  pub fn get_item(&self) -> &f32 {
+    return &self.item;
+  }
+
+  pub fn get_bomb(&self) -> &f32 {
+    return &self.bomb;
+  }
+
+  pub fn get_alive(&self) -> &f32 {
+    return &self.alive;
+  }
+
+  pub fn set_win(&self, win: f32) -> Rewards {
+    return Rewards {
+      win: win,
+      ..*self
+    }
+  }
+
+  pub fn set_draw(&self, draw: f32) -> Rewards {
+    return Rewards {
+      draw: draw,
+      ..*self
+    }
+  }
+
+  pub fn set_death(&self, death: f32) -> Rewards {
+    return Rewards {
+      death: death,
+      ..*self
+    }
+  }
+
+  pub fn set_kill(&self, kill: f32) -> Rewards {
+    return Rewards {
+      kill: kill,
+      ..*self
+    }
+  }
+
+  pub fn set_item(&self, item: f32) -> Rewards {
+    return Rewards {
+      item: item,
+      ..*self
+    }
+  }
+
+  pub fn set_bomb(&self, bomb: f32) -> Rewards {
+    return Rewards {
+      bomb: bomb,
+      ..*self
+    }
+  }
+
+  pub fn set_alive(&self, alive: f32) -> Rewards {
+    return Rewards {
+      alive: alive,
+      ..*self
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    common::direction::Direction,
+    model::{
+      bomb::{Bomb, BombImpl},
+      input::{self, PlayerId},
+      player::Player,
+      position,
+      rules::Rules,
+      stage::{Stage, StageImpl},
+      world::WorldImpl
+    }
+  };
+
+  /* Sets off a bomb owned by a player next to player 1, on a stage where players 0 and 2 are out
+   * of its reach, and returns what everyone earned on the tick it went off.
+   */
+  fn blow_up_player_1(rewards: &Rewards, owner: PlayerId) -> Vec<f32> {
+    let rules: Rules = Rules::default();
+    let stage: StageImpl = StageImpl::from_layout("0..1.#2").unwrap();
+    let players: Vec<Player> = stage.get_spawns().iter().enumerate()
+      .map(|(id, spawn)| {
+        return Player::new(
+          id as PlayerId, position::from_tile(spawn), Direction::South, rules.get_starting_stats()
+        );
+      })
+      .collect();
+    let bomb: Box<dyn Bomb> = Box::new(BombImpl::new((4, 0), owner, 1, false, false, 1));
+    let mut world: WorldImpl =
+      WorldImpl::new(rules, Box::new(stage), players, vec!(bomb), vec!(), 0);
+    let events: Vec<WorldEvent> = world.tick_mut(&input::idle_frame());
+    let mut totals: Vec<f32> = vec!(0.0; 3);
+    rewards.add(&world, &events, &mut totals);
+    return totals;
+  }
+
+  #[test]
+  fn kills_deaths_and_survival_are_rewarded() {
+    let rewards: Rewards = Rewards::default().set_kill(0.5).set_death(-1.0).set_alive(0.25);
+    assert_eq!(blow_up_player_1(&rewards, 0), vec!(0.5 + 0.25, -1.0, 0.25));
+    // Blowing yourself up is only a death.
+    assert_eq!(blow_up_player_1(&rewards, 1), vec!(0.25, -1.0, 0.25));
+  }
+
+  #[test]
+  fn winning_and_drawing_are_rewarded_once_the_round_ends() {
+    let rewards: Rewards = Rewards::default().set_win(2.0).set_draw(0.5).set_alive(0.0);
+    let win: Vec<WorldEvent> = vec!(WorldEvent::RoundEnded { outcome: Outcome::Win(2) });
+    let stage: StageImpl = StageImpl::from_layout("0.1.2").unwrap();
+    let world: WorldImpl = WorldImpl::start(Rules::default(), Box::new(stage), 3, 0);
+    let mut totals: Vec<f32> = vec!(0.0; 3);
+    rewards.add(&world, &win, &mut totals);
+    assert_eq!(totals, vec!(0.0, 0.0, 2.0));
+
+    let draw: Vec<WorldEvent> = vec!(WorldEvent::RoundEnded { outcome: Outcome::Draw });
+    let mut totals: Vec<f32> = vec!(0.0; 2);
+    rewards.add(&world, &draw, &mut totals);
+    assert_eq!(totals, vec!(0.5, 0.5));
+  }
+}
@@ -0,0 +1,11 @@
+pub mod ai;
+pub mod audio;
+pub mod common;
+pub mod controls;
+pub mod gym;
+pub mod menu;
+pub mod model;
+pub mod net;
+pub mod replay;
+pub mod tournament;
+pub mod view;
@@ -1,11 +1,410 @@
-pub mod common;
-pub mod model;
+#[cfg(feature = "audio")]
+use open_bomberman::audio::mixer::MixerAudio;
+use open_bomberman::{
+  ai::Bots,
+  audio::{self, null::NullAudio, Audio, SoundCues, Volumes},
+  controls::{bindings::Bindings, sdlcontrols::SdlControls},
+  menu::{
+    screens::{PauseScreen, TitleScreen},
+    setup::MatchSetup,
+    Command, MenuInput, MenuStack
+  },
+  model::{
+    event::WorldEvent,
+    input::InputFrame,
+    rng,
+    score::Scoreboard,
+    timestep::FixedTimestep,
+    world::{World, WorldImpl}
+  },
+  replay::{Replay, REPLAY_EXTENSION},
+  view::{
+    appview::AppView,
+    effects::{EffectSettings, Effects},
+    hud,
+    scene::{Offset, TILE_SIZE},
+    theme::Theme
+  }
+};
+use sdl2::{
+  controller::{Axis, Button},
+  event::Event,
+  keyboard::Keycode
+};
+use std::{
+  collections::HashMap,
+  fs,
+  path::Path,
+  time::{Duration, Instant}
+};
 
-use sdl2::pixels::Color;
-use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
-use std::time::Duration;
+// How long a round's outcome is shown for before the next round starts.
+const ROUND_END_TIME: Duration = Duration::from_secs(3);
 
-fn main() {
-  
+// How far a stick has to be pushed to move through menus, out of 32767.
+const STICK_THRESHOLD: i16 = 16384;
+
+// Where bindings are loaded from, and saved to whenever they're changed.
+const BINDINGS_FILE: &str = "bindings.ron";
+
+// Where volumes are loaded from, and saved to whenever they're changed.
+const AUDIO_FILE: &str = "audio.ron";
+
+// Where effect settings are loaded from, and saved to whenever they're changed.
+const EFFECTS_FILE: &str = "effects.ron";
+
+// Where rounds are saved to when they're being recorded.
+const REPLAY_DIRECTORY: &str = "replays";
+
+/* A struct representing a match being played.
+ * Bots play for every player slot set to a bot, and get made afresh each round.
+ * Replay is the recording of the current round, if rounds are being recorded. Every round that
+ * finishes is saved to the replay directory, named after its seed.
+ * Cues are what the match sounds like, and effects are what's drawn over it.
+ * Round over is when the current round ended, if it has.
+ */
+struct Match {
+  setup: MatchSetup,
+  world: WorldImpl,
+  replay: Option<Replay>,
+  scoreboard: Scoreboard,
+  timestep: FixedTimestep,
+  bots: Bots,
+  cues: SoundCues,
+  effects: Effects,
+  round_over: Option<Instant>
+}
+
+impl Match {
+  // Starts a match, along with the stage's music.
+  fn new(setup: &MatchSetup, record: bool, audio: &mut dyn Audio) -> Result<Match, String> {
+    audio.set_paused(false);
+    audio.play_music(&audio::music_track(setup.get_stage_name()))?;
+    let world: WorldImpl = setup.start_round(rng::clock_seed());
+    return Ok(
+      Match {
+        setup: setup.clone(),
+        replay: record_round(&world, record)?,
+        world: world,
+        scoreboard: Scoreboard::new(),
+        timestep: FixedTimestep::default(),
+        bots: Bots::interactive(&setup.get_bots(), rng::clock_seed()),
+        cues: SoundCues::new(),
+        effects: Effects::new(*setup.get_effects()),
+        round_over: None
+      }
+    );
+  }
+
+  /* Runs however many ticks are due and plays and shows whatever they called for, then moves on to
+   * the next round if this one is done. Returns whether any ticks were run.
+   */
+  fn update(
+    &mut self, inputs: &InputFrame, elapsed: Duration, now: Instant, audio: &mut dyn Audio
+  ) -> Result<bool, String> {
+    let bots: &mut Bots = &mut self.bots;
+    let replay: &mut Option<Replay> = &mut self.replay;
+    let (ticks, events): (u32, Vec<WorldEvent>) = self.timestep.update_with(
+      &mut self.world, elapsed, inputs, &mut |world, frame| {
+        bots.fill(world, frame);
+        if let Some(replay) = replay {
+          replay.record(world, frame);
+        }
+      }
+    );
+    self.cues.hear(&events, audio)?;
+    self.effects.hear(&events, &self.world);
+    self.effects.tick(ticks);
+
+    // Rounds are scored as soon as they're over, but left on screen for a bit before moving on.
+    match (self.world.get_outcome(), self.round_over) {
+      (Some(outcome), None) => {
+        self.scoreboard.record(&outcome);
+        self.round_over = Some(now);
+        if let Some(replay) = &mut self.replay {
+          replay.finish(&self.world);
+          save_replay(replay)?;
+        }
+      },
+      (Some(_), Some(since)) if now - since >= ROUND_END_TIME => {
+        self.world = self.setup.start_round(rng::clock_seed());
+        self.replay = record_round(&self.world, self.replay.is_some())?;
+        self.bots = Bots::interactive(&self.setup.get_bots(), rng::clock_seed());
+        self.round_over = None;
+      },
+      _ => {}
+    }
+    return Ok(ticks > 0);
+  }
+
+  // Stops everything the match was playing, once it's been left.
+  fn end(&mut self, audio: &mut dyn Audio) {
+    self.cues.stop(audio);
+    audio.stop_music();
+    audio.set_paused(false);
+  }
+}
+
+// Starts recording a round that's just been started, if rounds are being recorded.
+fn record_round(world: &dyn World, record: bool) -> Result<Option<Replay>, String> {
+  match record {
+    true => return Replay::new(world).map(Some),
+    false => return Ok(None)
+  }
+}
+
+fn save_replay(replay: &Replay) -> Result<(), String> {
+  fs::create_dir_all(REPLAY_DIRECTORY)
+    .map_err(|e| format!("Couldn't make {}: {}", REPLAY_DIRECTORY, e))?;
+  let name: String = format!("{}.{}", replay.get_seed(), REPLAY_EXTENSION);
+  return replay.save(&Path::new(REPLAY_DIRECTORY).join(name));
+}
+
+fn key_input(keycode: Keycode) -> Option<MenuInput> {
+  match keycode {
+    Keycode::Up | Keycode::W => return Some(MenuInput::Up),
+    Keycode::Down | Keycode::S => return Some(MenuInput::Down),
+    Keycode::Left | Keycode::A => return Some(MenuInput::Left),
+    Keycode::Right | Keycode::D => return Some(MenuInput::Right),
+    Keycode::Return | Keycode::Space => return Some(MenuInput::Confirm),
+    Keycode::Escape | Keycode::Backspace => return Some(MenuInput::Back),
+    _ => return None
+  }
+}
+
+fn button_input(button: Button) -> Option<MenuInput> {
+  match button {
+    Button::DPadUp => return Some(MenuInput::Up),
+    Button::DPadDown => return Some(MenuInput::Down),
+    Button::DPadLeft => return Some(MenuInput::Left),
+    Button::DPadRight => return Some(MenuInput::Right),
+    Button::A | Button::Start => return Some(MenuInput::Confirm),
+    Button::B | Button::Back => return Some(MenuInput::Back),
+    _ => return None
+  }
+}
+
+/* Turns a stick moving into a menu input, but only as it gets pushed past the threshold, so that
+ * holding it doesn't scroll through everything. Held is which way the stick was already pushed.
+ */
+fn stick_input(axis: Axis, value: i16, held: &mut i8) -> Option<MenuInput> {
+  let pushed: i8 = (value >= STICK_THRESHOLD) as i8 - (value <= -STICK_THRESHOLD) as i8;
+  if pushed == *held {
+    return None;
+  }
+  *held = pushed;
+  match (axis, pushed) {
+    (Axis::LeftX, -1) => return Some(MenuInput::Left),
+    (Axis::LeftX, 1) => return Some(MenuInput::Right),
+    (Axis::LeftY, -1) => return Some(MenuInput::Up),
+    (Axis::LeftY, 1) => return Some(MenuInput::Down),
+    _ => return None
+  }
+}
+
+// Loads the bindings file if there is one, and the default bindings otherwise.
+fn load_bindings() -> Result<Bindings, String> {
+  let path: &Path = Path::new(BINDINGS_FILE);
+  match path.exists() {
+    true => return Bindings::from_file(path),
+    false => return Ok(Bindings::default())
+  }
+}
+
+// Loads the effects file if there is one, and the default effect settings otherwise.
+fn load_effects() -> Result<EffectSettings, String> {
+  let path: &Path = Path::new(EFFECTS_FILE);
+  match path.exists() {
+    true => return EffectSettings::from_file(path),
+    false => return Ok(EffectSettings::default())
+  }
+}
+
+// Loads the volumes file if there is one, and the default volumes otherwise.
+fn load_volumes() -> Result<Volumes, String> {
+  let path: &Path = Path::new(AUDIO_FILE);
+  match path.exists() {
+    true => return Volumes::from_file(path),
+    false => return Ok(Volumes::default())
+  }
+}
+
+/* Opens SDL2_mixer for playing sound. Machines without a sound device (or without the sounds) still
+ * get to play, just silently.
+ */
+#[cfg(feature = "audio")]
+fn open_audio(sdl: &sdl2::Sdl, volumes: &Volumes) -> Box<dyn Audio> {
+  match sdl.audio().and_then(|subsystem| MixerAudio::new(subsystem, volumes)) {
+    Ok(audio) => return Box::new(audio),
+    Err(e) => {
+      eprintln!("Couldn't open audio, so there won't be any sound: {}", e);
+      return Box::new(NullAudio::new());
+    }
+  }
+}
+
+#[cfg(not(feature = "audio"))]
+fn open_audio(_sdl: &sdl2::Sdl, _volumes: &Volumes) -> Box<dyn Audio> {
+  return Box::new(NullAudio::new());
+}
+
+fn main() -> Result<(), String> {
+  let args: Vec<String> = std::env::args().collect();
+  // SDL's software renderer works on machines without a GPU.
+  let software: bool = args.iter().any(|arg| arg == "--software");
+  // Recording saves every round that gets played to the replay directory.
+  let record: bool = args.iter().any(|arg| arg == "--record");
+  // Settings are loaded before anything opens, so a broken file fails up front.
+  let mut setup: MatchSetup = MatchSetup::default()
+    .set_bindings(load_bindings()?)
+    .set_volumes(load_volumes()?)
+    .set_effects(load_effects()?);
+
+  let sdl = sdl2::init()?;
+  let video = sdl.video()?;
+  let mut controls: SdlControls = SdlControls::new(sdl.game_controller()?, setup.get_bindings())?;
+  // The window fits the biggest stage there is, so matches never need it resized.
+  let size: (u32, u32) = hud::max_screen_size(TILE_SIZE);
+  let mut view: AppView = AppView::new(&video, "Open Bomberman", size, software, None)?;
+  let mut events = sdl.event_pump()?;
+  let mut audio: Box<dyn Audio> = open_audio(&sdl, setup.get_volumes());
+
+  let mut menus: MenuStack = MenuStack::new(Box::new(TitleScreen::new()));
+  let mut game: Option<Match> = None;
+  let mut pause: Option<MenuStack> = None;
+  let mut sticks: HashMap<(u32, Axis), i8> = HashMap::new();
+
+  let mut last_frame: Instant = Instant::now();
+  'running: loop {
+    for event in events.poll_iter() {
+      if let Event::Quit { .. } = event {
+        break 'running;
+      }
+      controls.handle(&event);
+      let menu_input: Option<MenuInput> = match &event {
+        Event::KeyDown { keycode: Some(keycode), .. } => key_input(*keycode),
+        Event::ControllerButtonDown { button, .. } => button_input(*button),
+        Event::ControllerAxisMotion { which, axis, value, .. } => {
+          stick_input(*axis, *value, sticks.entry((*which, *axis)).or_insert(0))
+        },
+        _ => None
+      };
+
+      // While a match is going, only pausing goes to the menus and everything else is played with.
+      if let (Some(_), None) = (&game, &pause) {
+        match event {
+          Event::KeyDown { keycode: Some(Keycode::Escape), .. }
+          | Event::ControllerButtonDown { button: Button::Start, .. } => {
+            pause = Some(MenuStack::new(Box::new(PauseScreen::new())));
+            audio.set_paused(true);
+          },
+          _ => {}
+        }
+        continue;
+      }
+
+      let stack: &mut MenuStack = match &mut pause {
+        Some(pause) => pause,
+        None => &mut menus
+      };
+      /* Menus waiting on a control to bind get the next one pressed, unless it's Escape or a pad's
+       * back button, which back out instead. Anything else can be bound, even if it's usually used
+       * for backing out of menus.
+       */
+      let cancel: bool = matches!(
+        event,
+        Event::KeyDown { keycode: Some(Keycode::Escape), .. }
+          | Event::ControllerButtonDown { button: Button::Back, .. }
+      );
+      let command: Option<Command> = match (menu_input, stack.is_listening()) {
+        (_, true) if cancel => stack.handle(&MenuInput::Back, &mut setup),
+        (_, true) => {
+          match SdlControls::pressed(&event) {
+            Some(control) => stack.bind(control, &mut setup),
+            None => None
+          }
+        },
+        (Some(input), false) => stack.handle(&input, &mut setup),
+        (None, false) => None
+      };
+      match command {
+        Some(Command::StartMatch) => {
+          view.set_theme(Some(Theme::load_builtin(setup.get_theme_name())?))?;
+          controls.set_players(setup.get_players());
+          game = Some(Match::new(&setup, record, audio.as_mut())?);
+        },
+        Some(Command::Resume) => {
+          pause = None;
+          audio.set_paused(false);
+        },
+        Some(Command::Restart) => {
+          if let Some(game) = &mut game {
+            game.end(audio.as_mut());
+          }
+          controls.set_players(setup.get_players());
+          game = Some(Match::new(&setup, record, audio.as_mut())?);
+          pause = None;
+        },
+        Some(Command::EndMatch) => {
+          if let Some(game) = &mut game {
+            game.end(audio.as_mut());
+          }
+          game = None;
+          pause = None;
+        },
+        Some(Command::SaveBindings) => {
+          controls.set_bindings(setup.get_bindings())?;
+          setup.get_bindings().save(Path::new(BINDINGS_FILE))?;
+        },
+        Some(Command::SaveVolumes) => {
+          audio.set_volumes(setup.get_volumes());
+          setup.get_volumes().save(Path::new(AUDIO_FILE))?;
+        },
+        Some(Command::SaveEffects) => {
+          if let Some(game) = &mut game {
+            game.effects.set_settings(*setup.get_effects());
+          }
+          setup.get_effects().save(Path::new(EFFECTS_FILE))?;
+        },
+        Some(Command::Quit) => break 'running,
+        None => {}
+      }
+    }
+
+    let now: Instant = Instant::now();
+    if let (Some(game), None) = (&mut game, &pause) {
+      let inputs: InputFrame = controls.read_inputs(&events.keyboard_state());
+      if game.update(&inputs, now - last_frame, now, audio.as_mut())? {
+        controls.clear_presses();
+      }
+    }
+    last_frame = now;
+
+    let tile_size: u32 = *view.get_tile_size();
+    view.draw(|painter, theme| {
+      let game: &Match = match (&game, &pause) {
+        (Some(game), None) => game,
+        (Some(game), Some(pause)) if pause.is_overlay() => game,
+        (_, Some(pause)) => return pause.draw(painter, &setup, size),
+        (None, None) => return menus.draw(painter, &setup, size)
+      };
+      // Matches on smaller stages sit in the middle of the window.
+      let screen: (u32, u32) = hud::screen_size(&game.world, tile_size);
+      let offset: (i32, i32) =
+        ((size.0 as i32 - screen.0 as i32) / 2, (size.1 as i32 - screen.1 as i32) / 2);
+      hud::draw_screen(
+        &mut Offset::new(painter, offset), &game.world, &game.scoreboard,
+        game.timestep.get_alpha(), tile_size, theme, Some(&game.effects)
+      )?;
+      match &pause {
+        Some(pause) => return pause.draw(painter, &setup, size),
+        None => return Ok(())
+      }
+    })?;
+    if software {
+      // The software renderer can't wait for vsync, so don't let it spin flat out.
+      std::thread::sleep(Duration::from_millis(1));
+    }
+  }
+  return Ok(());
 }
@@ -0,0 +1,221 @@
+use crate::{
+  controls::bindings::Control,
+  view::{
+    font,
+    scene::{Painter, Rgba, ScreenRect}
+  }
+};
+use setup::MatchSetup;
+
+pub mod screens;
+pub mod setup;
+
+/* An enum representing a single press for navigating menus.
+ * Frontends turn keys, buttons and sticks into these, so menus work the same whatever they're
+ * being driven by.
+ */
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum MenuInput {
+  Up,
+  Down,
+  Left,
+  Right,
+  Confirm,
+  Back
+}
+
+/* An enum representing something a menu wants done that's outside of the menus themselves.
+ * Frontends act on these, since only they know how to run a match.
+ */
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Command {
+  // Start a match with the current setup.
+  StartMatch,
+  // Close the pause menu and carry on with the match.
+  Resume,
+  // Start the match over, scoreboard and all.
+  Restart,
+  // Leave the match and go back to the menus it was started from.
+  EndMatch,
+  // The bindings in the match setup were changed, and should be saved so they stick around.
+  SaveBindings,
+  // The volumes in the match setup were changed, and should be saved and heard right away.
+  SaveVolumes,
+  // The effect settings in the match setup were changed, and should be saved and seen right away.
+  SaveEffects,
+  Quit
+}
+
+// An enum representing what should happen after a screen handles an input.
+pub enum Action {
+  Stay,
+  Push(Box<dyn Screen>),
+  Pop,
+  Run(Command)
+}
+
+/* A trait representing a single screen of the menus, like the title screen or the pause menu.
+ * Screens can change the match setup as they like, but anything else they want done has to go
+ * through the action they return.
+ */
+pub trait Screen {
+  fn handle(&mut self, input: &MenuInput, setup: &mut MatchSetup) -> Action;
+
+  // Draws the screen onto a screen of a certain size, in pixels.
+  fn draw(
+    &self, painter: &mut dyn Painter, setup: &MatchSetup, size: (u32, u32)
+  ) -> Result<(), String>;
+
+  // Returns whether whatever is under this screen should be drawn first, like a paused match.
+  fn is_overlay(&self) -> bool;
+
+  /* Returns whether the screen is waiting for a control to bind, in which case the frontend should
+   * pass it the next key or button pressed instead of menu inputs. Backing out still goes through
+   * handle, so it can be cancelled.
+   */
+  fn is_listening(&self) -> bool {
+    return false;
+  }
+
+  fn bind(&mut self, _control: Control, _setup: &mut MatchSetup) -> Action {
+    return Action::Stay;
+  }
+}
+
+/* A struct representing the menus as a stack of screens, where only the top one gets inputs.
+ * Going into a menu pushes its screen, and backing out of it pops it again, so backing out always
+ * goes back to wherever the player came from. The bottom screen can't be popped.
+ */
+pub struct MenuStack {
+  screens: Vec<Box<dyn Screen>>
+}
+
+impl MenuStack {
+  pub fn new(root: Box<dyn Screen>) -> MenuStack {
+    return MenuStack {
+      screens: vec!(root)
+    }
+  }
+
+  /* Has the top screen handle an input, and returns whatever it wants the frontend to do.
+   * Pushing and popping screens is taken care of here.
+   */
+  pub fn handle(&mut self, input: &MenuInput, setup: &mut MatchSetup) -> Option<Command> {
+    let action: Action = match self.screens.last_mut() {
+      Some(screen) => screen.handle(input, setup),
+      None => return None
+    };
+    return self.apply(action);
+  }
+
+  // Has the top screen bind a control, if it's listening for one; see Screen::is_listening.
+  pub fn bind(&mut self, control: Control, setup: &mut MatchSetup) -> Option<Command> {
+    let action: Action = match self.screens.last_mut() {
+      Some(screen) if screen.is_listening() => screen.bind(control, setup),
+      _ => return None
+    };
+    return self.apply(action);
+  }
+
+  fn apply(&mut self, action: Action) -> Option<Command> {
+    match action {
+      Action::Stay => return None,
+      Action::Push(screen) => self.screens.push(screen),
+      Action::Pop => self.pop(),
+      Action::Run(command) => return Some(command)
+    }
+    return None;
+  }
+
+  pub fn push(&mut self, screen: Box<dyn Screen>) {
+    self.screens.push(screen);
+  }
+
+  pub fn pop(&mut self) {
+    if self.screens.len() > 1 {
+      self.screens.pop();
+    }
+  }
+
+  // Draws the top screen, along with every overlay under it down to the first screen that isn't.
+  pub fn draw(
+    &self, painter: &mut dyn Painter, setup: &MatchSetup, size: (u32, u32)
+  ) -> Result<(), String> {
+    let bottom: usize =
+      self.screens.iter().rposition(|screen| !screen.is_overlay()).unwrap_or(0);
+    for screen in self.screens[bottom..].iter() {
+      screen.draw(painter, setup, size)?;
+    }
+    return Ok(());
+  }
+
+  // Returns whether whatever is under the menus should be drawn first, as they're all overlays.
+  pub fn is_overlay(&self) -> bool {
+    return self.screens.iter().all(|screen| screen.is_overlay());
+  }
+
+  pub fn is_listening(&self) -> bool {
+    return self.screens.last().is_some_and(|screen| screen.is_listening());
+  }
+}
+
+pub const BACKGROUND: Rgba = Rgba::rgb(24, 24, 32);
+pub const TEXT: Rgba = Rgba::rgb(230, 230, 230);
+pub const SELECTED_TEXT: Rgba = Rgba::rgb(250, 200, 60);
+pub const DIM_TEXT: Rgba = Rgba::rgb(120, 120, 130);
+
+// How many screen pixels each pixel of the font takes up, for titles and everything else.
+pub const TITLE_SCALE: u32 = 6;
+pub const TEXT_SCALE: u32 = 3;
+
+// How far apart the items of a menu are, in pixels.
+pub const ITEM_SPACING: u32 = 32;
+
+// Draws a line of text centered across the screen.
+pub fn draw_centered(
+  painter: &mut dyn Painter, text: &str, y: i32, width: u32, scale: u32, color: Rgba
+) -> Result<(), String> {
+  let x: i32 = (width as i32 - font::text_width(text, scale) as i32) / 2;
+  return font::draw_text(painter, text, (x, y), scale, color);
+}
+
+/* Draws a menu's title at the top of the screen, and its items down the middle of it starting at
+ * a point, with the selected one highlighted.
+ */
+pub fn draw_menu(
+  painter: &mut dyn Painter, title: &str, items: &[String], selected: usize, top: i32,
+  size: (u32, u32)
+) -> Result<(), String> {
+  draw_centered(painter, title, 24, size.0, TITLE_SCALE, TEXT)?;
+  for (i, item) in items.iter().enumerate() {
+    let y: i32 = top + (i as u32 * ITEM_SPACING) as i32;
+    match i == selected {
+      true => {
+        let item: String = format!("> {} <", item);
+        draw_centered(painter, &item, y, size.0, TEXT_SCALE, SELECTED_TEXT)?;
+      },
+      false => draw_centered(painter, item, y, size.0, TEXT_SCALE, TEXT)?
+    }
+  }
+  return Ok(());
+}
+
+// Fills the whole screen with a color, which can be see-through for overlays.
+pub fn fill_screen(
+  painter: &mut dyn Painter, size: (u32, u32), color: Rgba
+) -> Result<(), String> {
+  let rect: ScreenRect = (0, 0, size.0, size.1);
+  return painter.fill_rect(rect, color);
+}
+
+/* Moves a selection up or down a list, wrapping around at either end, and returns whether the input
+ * was one that moves it.
+ */
+pub fn navigate(selected: &mut usize, count: usize, input: &MenuInput) -> bool {
+  match input {
+    MenuInput::Up => *selected = (*selected + count - 1) % count,
+    MenuInput::Down => *selected = (*selected + 1) % count,
+    _ => return false
+  }
+  return true;
+}
@@ -0,0 +1,641 @@
+use crate::{
+  audio::{Channel, CHANNELS},
+  controls::bindings::{self, Bindings, Control, ACTIONS, GAMEPADS, KEYBOARD_LAYOUTS},
+  menu::{
+    self,
+    setup::{MatchSetup, Slot},
+    Action, Command, MenuInput, Screen
+  },
+  model::{
+    input::MAX_PLAYERS,
+    item::MAX_SPEED,
+    player::Abilities,
+    rules::{Rules, StartingStats},
+    timestep::TICK_RATE,
+    world::{World, WorldImpl}
+  },
+  view::{
+    effects::EffectSettings,
+    hud,
+    scene::{self, Offset, Painter, Rgba}
+  }
+};
+
+// How big tiles are in the stage preview, in pixels.
+const PREVIEW_TILE_SIZE: u32 = 16;
+
+// Returns how many steps a left or right input moves a setting, if it's one of those at all.
+fn steps(input: &MenuInput) -> Option<i32> {
+  match input {
+    MenuInput::Left => return Some(-1),
+    MenuInput::Right => return Some(1),
+    _ => return None
+  }
+}
+
+// The first screen, which leads to setting up a match.
+#[derive(Default)]
+pub struct TitleScreen {
+  selected: usize
+}
+
+impl TitleScreen {
+  pub fn new() -> TitleScreen {
+    return TitleScreen { selected: 0 };
+  }
+}
+
+impl Screen for TitleScreen {
+  fn handle(&mut self, input: &MenuInput, _setup: &mut MatchSetup) -> Action {
+    if menu::navigate(&mut self.selected, 5, input) {
+      return Action::Stay;
+    }
+    match (input, self.selected) {
+      (MenuInput::Confirm, 0) => return Action::Push(Box::new(PlayersScreen::new())),
+      (MenuInput::Confirm, 1) => return Action::Push(Box::new(ControlsScreen::new())),
+      (MenuInput::Confirm, 2) => return Action::Push(Box::new(AudioScreen::new())),
+      (MenuInput::Confirm, 3) => return Action::Push(Box::new(EffectsScreen::new())),
+      (MenuInput::Confirm, _) => return Action::Run(Command::Quit),
+      _ => return Action::Stay
+    }
+  }
+
+  fn draw(
+    &self, painter: &mut dyn Painter, _setup: &MatchSetup, size: (u32, u32)
+  ) -> Result<(), String> {
+    menu::fill_screen(painter, size, menu::BACKGROUND)?;
+    let items: [String; 5] =
+      ["Play", "Controls", "Audio", "Effects", "Quit"].map(String::from);
+    return menu::draw_menu(
+      painter, "Open Bomberman", &items, self.selected, (size.1 / 2) as i32, size
+    );
+  }
+
+  fn is_overlay(&self) -> bool {
+    return false;
+  }
+}
+
+/* The screen for picking what controls each player slot. Left and right (or confirm) cycle a slot
+ * through its choices.
+ */
+#[derive(Default)]
+pub struct PlayersScreen {
+  selected: usize
+}
+
+impl PlayersScreen {
+  pub fn new() -> PlayersScreen {
+    return PlayersScreen { selected: 0 };
+  }
+}
+
+impl Screen for PlayersScreen {
+  fn handle(&mut self, input: &MenuInput, setup: &mut MatchSetup) -> Action {
+    if menu::navigate(&mut self.selected, MAX_PLAYERS + 1, input) {
+      return Action::Stay;
+    }
+    let slot_steps: Option<i32> = match input {
+      MenuInput::Confirm if self.selected < MAX_PLAYERS => Some(1),
+      _ => steps(input)
+    };
+    match (input, slot_steps) {
+      (MenuInput::Back, _) => return Action::Pop,
+      (_, Some(slot_steps)) if self.selected < MAX_PLAYERS => {
+        let slot: Slot = setup.get_slots()[self.selected].cycle(slot_steps);
+        *setup = setup.set_slot(self.selected, slot);
+        return Action::Stay;
+      },
+      (MenuInput::Confirm, _) if !setup.get_players().is_empty() => {
+        return Action::Push(Box::new(StageScreen::new()));
+      },
+      _ => return Action::Stay
+    }
+  }
+
+  fn draw(
+    &self, painter: &mut dyn Painter, setup: &MatchSetup, size: (u32, u32)
+  ) -> Result<(), String> {
+    menu::fill_screen(painter, size, menu::BACKGROUND)?;
+    let mut items: Vec<String> = setup.get_slots().iter().enumerate()
+      .map(|(i, slot)| format!("Slot {}: {}", i + 1, slot.name()))
+      .collect();
+    items.push(String::from("Next"));
+    return menu::draw_menu(painter, "Players", &items, self.selected, 96, size);
+  }
+
+  fn is_overlay(&self) -> bool {
+    return false;
+  }
+}
+
+/* The screen for picking the stage and theme, with a preview of the stage underneath. It also
+ * leads to the rules editor, and is where the match gets started from.
+ */
+#[derive(Default)]
+pub struct StageScreen {
+  selected: usize
+}
+
+impl StageScreen {
+  pub fn new() -> StageScreen {
+    return StageScreen { selected: 0 };
+  }
+}
+
+impl Screen for StageScreen {
+  fn handle(&mut self, input: &MenuInput, setup: &mut MatchSetup) -> Action {
+    if menu::navigate(&mut self.selected, 4, input) {
+      return Action::Stay;
+    }
+    match (input, self.selected, steps(input)) {
+      (MenuInput::Back, _, _) => return Action::Pop,
+      (_, 0, Some(steps)) => *setup = setup.cycle_stage(steps),
+      (_, 1, Some(steps)) => *setup = setup.cycle_theme(steps),
+      (MenuInput::Confirm, 2, _) => return Action::Push(Box::new(RulesScreen::new())),
+      (MenuInput::Confirm, 3, _) if setup.check().is_ok() => {
+        return Action::Run(Command::StartMatch);
+      },
+      _ => {}
+    }
+    return Action::Stay;
+  }
+
+  fn draw(
+    &self, painter: &mut dyn Painter, setup: &MatchSetup, size: (u32, u32)
+  ) -> Result<(), String> {
+    menu::fill_screen(painter, size, menu::BACKGROUND)?;
+    let items: [String; 4] = [
+      format!("Stage: {}", setup.get_stage_name()),
+      format!("Theme: {}", setup.get_theme_name()),
+      String::from("Rules"),
+      String::from("Start")
+    ];
+    menu::draw_menu(painter, "Stage", &items, self.selected, 96, size)?;
+
+    // The preview shows where everyone would start, as well as the stage itself.
+    let world: WorldImpl = setup.start_round(0);
+    let preview: (u32, u32) =
+      scene::scene_size(world.get_stage().get_dimensions(), PREVIEW_TILE_SIZE);
+    let top: i32 = 96 + 4 * menu::ITEM_SPACING as i32 + 16;
+    let offset: (i32, i32) = ((size.0 as i32 - preview.0 as i32) / 2, top);
+    scene::draw_world(&mut Offset::new(painter, offset), &world, 1.0, PREVIEW_TILE_SIZE, None)?;
+
+    if let Err(problem) = setup.check() {
+      let y: i32 = top + preview.1 as i32 + 16;
+      menu::draw_centered(painter, &problem, y, size.0, menu::TEXT_SCALE, menu::SELECTED_TEXT)?;
+    }
+    return Ok(());
+  }
+
+  fn is_overlay(&self) -> bool {
+    return false;
+  }
+}
+
+// A rule the rules editor can change.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum RuleField {
+  RoundTime,
+  SuddenDeathTime,
+  BombFuse,
+  BlastLifetime,
+  FlameSpreadRate,
+  Speed,
+  Bombs,
+  Range,
+  Piercing,
+  RemoteControl,
+  LineBomb
+}
+
+const RULE_FIELDS: [RuleField; 11] = [
+  RuleField::RoundTime, RuleField::SuddenDeathTime, RuleField::BombFuse, RuleField::BlastLifetime,
+  RuleField::FlameSpreadRate, RuleField::Speed, RuleField::Bombs, RuleField::Range,
+  RuleField::Piercing, RuleField::RemoteControl, RuleField::LineBomb
+];
+
+// Returns a number of ticks as seconds, to two decimal places.
+fn format_seconds(ticks: i32) -> String {
+  return format!("{:.2}s", ticks as f32 / TICK_RATE as f32);
+}
+
+fn format_switch(on: bool) -> String {
+  match on {
+    true => return String::from("On"),
+    false => return String::from("Off")
+  }
+}
+
+// Moves a value by a number of steps of a certain size, keeping it within a range.
+fn adjust(value: i32, steps: i32, step: i32, min: i32, max: i32) -> i32 {
+  return (value + steps * step).clamp(min, max);
+}
+
+impl RuleField {
+  fn format(&self, rules: &Rules) -> String {
+    let stats: &StartingStats = rules.get_starting_stats();
+    let abilities: &Abilities = stats.get_abilities();
+    match self {
+      RuleField::RoundTime => {
+        return format!("Round time: {}", hud::format_clock(*rules.get_round_time()));
+      },
+      RuleField::SuddenDeathTime => {
+        return format!("Sudden death at: {}", hud::format_clock(*rules.get_sudden_death_time()));
+      },
+      RuleField::BombFuse => {
+        return format!("Bomb fuse: {}", format_seconds(*rules.get_bomb_fuse() as i32));
+      },
+      RuleField::BlastLifetime => {
+        return format!("Blast time: {}", format_seconds(*rules.get_blast_lifetime() as i32));
+      },
+      RuleField::FlameSpreadRate => {
+        return format!("Flame spread: {} per tile", rules.get_flame_spread_rate());
+      },
+      RuleField::Speed => return format!("Speed: {}", stats.get_speed()),
+      RuleField::Bombs => return format!("Bombs: {}", stats.get_bombs()),
+      RuleField::Range => return format!("Fire: {}", stats.get_range()),
+      RuleField::Piercing => {
+        return format!("Pierce bombs: {}", format_switch(*stats.get_piercing()));
+      },
+      RuleField::RemoteControl => {
+        return format!("Remote control: {}", format_switch(*abilities.get_remote_control()));
+      },
+      RuleField::LineBomb => {
+        return format!("Line bombs: {}", format_switch(*abilities.get_line_bomb()));
+      }
+    }
+  }
+
+  /* Returns the rules with this field moved by a number of steps. Every field is kept within a
+   * range that leaves the rules valid, and switches just flip.
+   */
+  fn adjust(&self, rules: &Rules, steps: i32) -> Rules {
+    let stats: &StartingStats = rules.get_starting_stats();
+    let abilities: &Abilities = stats.get_abilities();
+    let second: i32 = TICK_RATE as i32;
+    match self {
+      RuleField::RoundTime => {
+        let round_time: u32 =
+          adjust(*rules.get_round_time() as i32, steps, 30 * second, 30 * second, 600 * second)
+            as u32;
+        return rules.set_round_time(round_time)
+          .set_sudden_death_time((*rules.get_sudden_death_time()).min(round_time));
+      },
+      RuleField::SuddenDeathTime => {
+        let round_time: i32 = *rules.get_round_time() as i32;
+        let time: i32 =
+          adjust(*rules.get_sudden_death_time() as i32, steps, 15 * second, 0, round_time);
+        return rules.set_sudden_death_time(time as u32);
+      },
+      RuleField::BombFuse => {
+        let fuse: i32 =
+          adjust(*rules.get_bomb_fuse() as i32, steps, second / 4, second / 2, 10 * second);
+        return rules.set_bomb_fuse(fuse as i16);
+      },
+      RuleField::BlastLifetime => {
+        let lifetime: i32 = adjust(*rules.get_blast_lifetime() as i32, steps, 5, 5, 120);
+        return rules.set_blast_lifetime(lifetime as i8);
+      },
+      RuleField::FlameSpreadRate => {
+        let rate: i32 = adjust(*rules.get_flame_spread_rate() as i32, steps, 1, 1, 8);
+        return rules.set_flame_spread_rate(rate as i8);
+      },
+      RuleField::Speed => {
+        let speed: i32 = adjust(*stats.get_speed(), steps, 2, 8, MAX_SPEED);
+        return rules.set_starting_stats(stats.set_speed(speed));
+      },
+      RuleField::Bombs => {
+        let bombs: i32 = adjust(*stats.get_bombs() as i32, steps, 1, 1, 8);
+        return rules.set_starting_stats(stats.set_bombs(bombs as u8));
+      },
+      RuleField::Range => {
+        let range: i32 = adjust(*stats.get_range() as i32, steps, 1, 1, 9);
+        return rules.set_starting_stats(stats.set_range(range as i8));
+      },
+      RuleField::Piercing => {
+        return rules.set_starting_stats(stats.set_piercing(!stats.get_piercing()));
+      },
+      RuleField::RemoteControl => {
+        let toggled: Abilities =
+          Abilities::new(!abilities.get_remote_control(), *abilities.get_line_bomb());
+        return rules.set_starting_stats(stats.set_abilities(toggled));
+      },
+      RuleField::LineBomb => {
+        let toggled: Abilities =
+          Abilities::new(*abilities.get_remote_control(), !abilities.get_line_bomb());
+        return rules.set_starting_stats(stats.set_abilities(toggled));
+      }
+    }
+  }
+}
+
+// The rules editor, where left and right change the selected rule.
+#[derive(Default)]
+pub struct RulesScreen {
+  selected: usize
+}
+
+impl RulesScreen {
+  pub fn new() -> RulesScreen {
+    return RulesScreen { selected: 0 };
+  }
+}
+
+impl Screen for RulesScreen {
+  fn handle(&mut self, input: &MenuInput, setup: &mut MatchSetup) -> Action {
+    if menu::navigate(&mut self.selected, RULE_FIELDS.len() + 1, input) {
+      return Action::Stay;
+    }
+    let field: Option<&RuleField> = RULE_FIELDS.get(self.selected);
+    match (input, field, steps(input)) {
+      (MenuInput::Back, _, _) | (MenuInput::Confirm, None, _) => return Action::Pop,
+      (_, Some(field), Some(steps)) => {
+        *setup = setup.set_rules(field.adjust(setup.get_rules(), steps));
+      },
+      (MenuInput::Confirm, Some(field), _) => {
+        *setup = setup.set_rules(field.adjust(setup.get_rules(), 1));
+      },
+      _ => {}
+    }
+    return Action::Stay;
+  }
+
+  fn draw(
+    &self, painter: &mut dyn Painter, setup: &MatchSetup, size: (u32, u32)
+  ) -> Result<(), String> {
+    menu::fill_screen(painter, size, menu::BACKGROUND)?;
+    let mut items: Vec<String> =
+      RULE_FIELDS.iter().map(|field| field.format(setup.get_rules())).collect();
+    items.push(String::from("Done"));
+    return menu::draw_menu(painter, "Rules", &items, self.selected, 88, size);
+  }
+
+  fn is_overlay(&self) -> bool {
+    return false;
+  }
+}
+
+// The menu over a paused match.
+#[derive(Default)]
+pub struct PauseScreen {
+  selected: usize
+}
+
+impl PauseScreen {
+  pub fn new() -> PauseScreen {
+    return PauseScreen { selected: 0 };
+  }
+}
+
+impl Screen for PauseScreen {
+  fn handle(&mut self, input: &MenuInput, _setup: &mut MatchSetup) -> Action {
+    if menu::navigate(&mut self.selected, 6, input) {
+      return Action::Stay;
+    }
+    match (input, self.selected) {
+      (MenuInput::Back, _) | (MenuInput::Confirm, 0) => return Action::Run(Command::Resume),
+      (MenuInput::Confirm, 1) => return Action::Push(Box::new(ControlsScreen::new())),
+      (MenuInput::Confirm, 2) => return Action::Push(Box::new(AudioScreen::new())),
+      (MenuInput::Confirm, 3) => return Action::Push(Box::new(EffectsScreen::new())),
+      (MenuInput::Confirm, 4) => return Action::Run(Command::Restart),
+      (MenuInput::Confirm, _) => return Action::Run(Command::EndMatch),
+      _ => return Action::Stay
+    }
+  }
+
+  fn draw(
+    &self, painter: &mut dyn Painter, _setup: &MatchSetup, size: (u32, u32)
+  ) -> Result<(), String> {
+    menu::fill_screen(painter, size, Rgba::new(0, 0, 0, 170))?;
+    let items: [String; 6] =
+      ["Resume", "Controls", "Audio", "Effects", "Restart", "Quit"].map(String::from);
+    return menu::draw_menu(painter, "Paused", &items, self.selected, (size.1 / 2) as i32, size);
+  }
+
+  fn is_overlay(&self) -> bool {
+    return true;
+  }
+}
+
+// Returns every player slot that has controls to bind, keyboards first.
+fn bindable_slots() -> Vec<Slot> {
+  let mut slots: Vec<Slot> = (0..KEYBOARD_LAYOUTS).map(Slot::Keyboard).collect();
+  slots.extend((0..GAMEPADS).map(Slot::Gamepad));
+  return slots;
+}
+
+/* The screen for rebinding controls. The first row picks whose controls are being changed, and
+ * confirming on an action waits for the next key or button to bind to it. Changes get saved as
+ * soon as they're made.
+ */
+#[derive(Default)]
+pub struct ControlsScreen {
+  selected: usize,
+  slot: usize,
+  listening: bool
+}
+
+impl ControlsScreen {
+  pub fn new() -> ControlsScreen {
+    return ControlsScreen { selected: 0, slot: 0, listening: false };
+  }
+
+  fn get_slot(&self) -> Slot {
+    return bindable_slots()[self.slot];
+  }
+}
+
+impl Screen for ControlsScreen {
+  fn handle(&mut self, input: &MenuInput, setup: &mut MatchSetup) -> Action {
+    if self.listening {
+      self.listening = *input != MenuInput::Back;
+      return Action::Stay;
+    }
+    if menu::navigate(&mut self.selected, ACTIONS.len() + 3, input) {
+      return Action::Stay;
+    }
+    let deadzone_row: usize = ACTIONS.len() + 1;
+    match (input, self.selected, steps(input)) {
+      (MenuInput::Back, _, _) => return Action::Pop,
+      (_, 0, Some(steps)) => {
+        let count: i32 = bindable_slots().len() as i32;
+        self.slot = (self.slot as i32 + steps).rem_euclid(count) as usize;
+      },
+      (MenuInput::Confirm, row, _) if row > 0 && row < deadzone_row => self.listening = true,
+      (_, row, Some(steps)) if row == deadzone_row => {
+        // Deadzones go in steps of 5%, up to 90%.
+        let bindings: &Bindings = setup.get_bindings();
+        let twentieths: i32 =
+          ((bindings.get_deadzone() * 20.0).round() as i32 + steps).clamp(0, 18);
+        *setup = setup.set_bindings(bindings.set_deadzone(twentieths as f32 / 20.0));
+        return Action::Run(Command::SaveBindings);
+      },
+      (MenuInput::Confirm, row, _) if row > deadzone_row => return Action::Pop,
+      _ => {}
+    }
+    return Action::Stay;
+  }
+
+  fn draw(
+    &self, painter: &mut dyn Painter, setup: &MatchSetup, size: (u32, u32)
+  ) -> Result<(), String> {
+    menu::fill_screen(painter, size, menu::BACKGROUND)?;
+    let slot: Slot = self.get_slot();
+    let bindings: &Bindings = setup.get_bindings();
+    let mut items: Vec<String> = vec!(format!("Player: {}", slot.name()));
+    for (i, action) in ACTIONS.iter().enumerate() {
+      let listening: bool = self.listening && self.selected == i + 1;
+      let control: String = match (listening, bindings.get_layout(&slot)) {
+        (true, _) => String::from("..."),
+        (false, Some(layout)) => layout.get(action).name(),
+        (false, None) => String::from("None")
+      };
+      items.push(format!("{}: {}", action.name(), control));
+    }
+    items.push(format!("Deadzone: {:.0}%", bindings.get_deadzone() * 100.0));
+    items.push(String::from("Done"));
+    menu::draw_menu(painter, "Controls", &items, self.selected, 88, size)?;
+
+    if self.listening {
+      let hint: &str = match slot {
+        Slot::Keyboard(_) => "Press a key, or Escape to cancel",
+        _ => "Press a button, or Back to cancel"
+      };
+      let y: i32 = 88 + (items.len() as u32 * menu::ITEM_SPACING) as i32 + 16;
+      menu::draw_centered(painter, hint, y, size.0, menu::TEXT_SCALE, menu::SELECTED_TEXT)?;
+    }
+    return Ok(());
+  }
+
+  fn is_overlay(&self) -> bool {
+    return false;
+  }
+
+  fn is_listening(&self) -> bool {
+    return self.listening;
+  }
+
+  // Keys only go to keyboard players and buttons to gamepad ones, so anything else is ignored.
+  fn bind(&mut self, control: Control, setup: &mut MatchSetup) -> Action {
+    let slot: Slot = self.get_slot();
+    if control.is_key() != matches!(slot, Slot::Keyboard(_)) {
+      return Action::Stay;
+    }
+    let action: &bindings::Action = &ACTIONS[self.selected - 1];
+    *setup = setup.set_bindings(setup.get_bindings().bind(&slot, action, control));
+    self.listening = false;
+    return Action::Run(Command::SaveBindings);
+  }
+}
+
+/* The screen for setting how loud each channel is. Left and right change the selected channel's
+ * volume in steps of 10%, which is saved and heard as soon as it's changed.
+ */
+#[derive(Default)]
+pub struct AudioScreen {
+  selected: usize
+}
+
+impl AudioScreen {
+  pub fn new() -> AudioScreen {
+    return AudioScreen { selected: 0 };
+  }
+}
+
+impl Screen for AudioScreen {
+  fn handle(&mut self, input: &MenuInput, setup: &mut MatchSetup) -> Action {
+    if menu::navigate(&mut self.selected, CHANNELS.len() + 1, input) {
+      return Action::Stay;
+    }
+    match (input, CHANNELS.get(self.selected), steps(input)) {
+      (MenuInput::Back, _, _) | (MenuInput::Confirm, None, _) => return Action::Pop,
+      (_, Some(channel), Some(steps)) => {
+        let channel: &Channel = channel;
+        let tenths: i32 =
+          ((setup.get_volumes().get(channel) * 10.0).round() as i32 + steps).clamp(0, 10);
+        *setup = setup.set_volumes(setup.get_volumes().set(channel, tenths as f32 / 10.0));
+        return Action::Run(Command::SaveVolumes);
+      },
+      _ => return Action::Stay
+    }
+  }
+
+  fn draw(
+    &self, painter: &mut dyn Painter, setup: &MatchSetup, size: (u32, u32)
+  ) -> Result<(), String> {
+    menu::fill_screen(painter, size, menu::BACKGROUND)?;
+    let mut items: Vec<String> = CHANNELS.iter()
+      .map(|channel| {
+        return format!("{}: {:.0}%", channel.name(), setup.get_volumes().get(channel) * 100.0);
+      })
+      .collect();
+    items.push(String::from("Done"));
+    return menu::draw_menu(painter, "Audio", &items, self.selected, (size.1 / 2) as i32, size);
+  }
+
+  fn is_overlay(&self) -> bool {
+    return false;
+  }
+}
+
+// Returns how a setting that can only be on or off reads in a menu.
+fn on_off(on: bool) -> &'static str {
+  match on {
+    true => return "On",
+    false => return "Off"
+  }
+}
+
+/* The screen for turning effects on and off, for anyone who finds them distracting or
+ * uncomfortable. Left, right or confirm flip the selected setting, which is saved and seen as soon
+ * as it's changed.
+ */
+#[derive(Default)]
+pub struct EffectsScreen {
+  selected: usize
+}
+
+impl EffectsScreen {
+  pub fn new() -> EffectsScreen {
+    return EffectsScreen { selected: 0 };
+  }
+}
+
+impl Screen for EffectsScreen {
+  fn handle(&mut self, input: &MenuInput, setup: &mut MatchSetup) -> Action {
+    if menu::navigate(&mut self.selected, 3, input) {
+      return Action::Stay;
+    }
+    let settings: &EffectSettings = setup.get_effects();
+    let flip: bool = *input == MenuInput::Confirm || steps(input).is_some();
+    match (input, self.selected) {
+      (MenuInput::Back, _) | (MenuInput::Confirm, 2) => return Action::Pop,
+      (_, 0) if flip => {
+        *setup = setup.set_effects(settings.set_particles(!settings.get_particles()));
+      },
+      (_, 1) if flip => {
+        *setup = setup.set_effects(settings.set_screen_shake(!settings.get_screen_shake()));
+      },
+      _ => return Action::Stay
+    }
+    return Action::Run(Command::SaveEffects);
+  }
+
+  fn draw(
+    &self, painter: &mut dyn Painter, setup: &MatchSetup, size: (u32, u32)
+  ) -> Result<(), String> {
+    menu::fill_screen(painter, size, menu::BACKGROUND)?;
+    let settings: &EffectSettings = setup.get_effects();
+    let items: [String; 3] = [
+      format!("Particles: {}", on_off(*settings.get_particles())),
+      format!("Screen Shake: {}", on_off(*settings.get_screen_shake())),
+      String::from("Done")
+    ];
+    return menu::draw_menu(painter, "Effects", &items, self.selected, (size.1 / 2) as i32, size);
+  }
+
+  fn is_overlay(&self) -> bool {
+    return false;
+  }
+}
@@ -0,0 +1,225 @@
+use crate::{
+  ai::{Difficulty, DIFFICULTIES},
+  audio::Volumes,
+  controls::bindings::{Bindings, GAMEPADS, KEYBOARD_LAYOUTS},
+  model::{
+    input::{PlayerId, MAX_PLAYERS},
+    rules::Rules,
+    stage::{Stage, StageImpl, STAGES},
+    world::WorldImpl
+  },
+  view::{effects::EffectSettings, theme::THEMES}
+};
+
+/* An enum representing what controls a player slot.
+ * Keyboards and gamepads are numbered from 0, with keyboards going by which set of keys they use
+ * and gamepads by the order they were plugged in. Slots that are off don't get a player at all.
+ */
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Slot {
+  Off,
+  Keyboard(u8),
+  Gamepad(u8),
+  Bot(Difficulty)
+}
+
+impl Slot {
+  // Returns every choice a slot can be set to, in the order the player setup screen cycles them.
+  pub fn choices() -> Vec<Slot> {
+    let mut choices: Vec<Slot> = vec!(Slot::Off);
+    choices.extend((0..KEYBOARD_LAYOUTS).map(Slot::Keyboard));
+    choices.extend((0..GAMEPADS).map(Slot::Gamepad));
+    choices.extend(DIFFICULTIES.iter().map(|difficulty| Slot::Bot(*difficulty)));
+    return choices;
+  }
+
+  // Returns the choice a number of steps away from this one, wrapping around at either end.
+  pub fn cycle(&self, steps: i32) -> Slot {
+    let choices: Vec<Slot> = Slot::choices();
+    let index: i32 = choices.iter().position(|choice| choice == self).unwrap_or(0) as i32;
+    return choices[(index + steps).rem_euclid(choices.len() as i32) as usize];
+  }
+
+  pub fn name(&self) -> String {
+    match self {
+      Slot::Off => return String::from("Off"),
+      Slot::Keyboard(layout) => return format!("Keys {}", layout + 1),
+      Slot::Gamepad(pad) => return format!("Pad {}", pad + 1),
+      Slot::Bot(difficulty) => return format!("Bot {:?}", difficulty)
+    }
+  }
+}
+
+/* A struct representing everything chosen in the menus before a match starts.
+ *
+ * Slots say what controls each player. Players only get made for slots that aren't off, and are
+ * numbered in slot order, so player IDs don't have gaps in them.
+ * Stage and theme are indexes into the stages and themes that ship with the game.
+ * Rules are what the match is played under.
+ * Bindings are the controls everyone plays with, volumes are how loud the game is, and effects
+ * are which effects are drawn over the world. All of these can be changed during a match too.
+ */
+#[derive(Clone, PartialEq, Debug)]
+pub struct MatchSetup {
+  slots: [Slot; MAX_PLAYERS],
+  stage: usize,
+  theme: usize,
+  rules: Rules,
+  bindings: Bindings,
+  volumes: Volumes,
+  effects: EffectSettings
+}
+
+impl Default for MatchSetup {
+  fn default() -> MatchSetup {
+    let mut slots: [Slot; MAX_PLAYERS] = [Slot::Off; MAX_PLAYERS];
+    slots[0] = Slot::Keyboard(0);
+    slots[1] = Slot::Keyboard(1);
+    return MatchSetup {
+      slots: slots,
+      stage: 0,
+      theme: 0,
+      rules: Rules::default(),
+      bindings: Bindings::default(),
+      volumes: Volumes::default(),
+      effects: EffectSettings::default()
+    }
+  }
+}
+
+impl MatchSetup {
+  // Returns what controls each player, indexed by player ID.
+  pub fn get_players(&self) -> Vec<Slot> {
+    return self.slots.iter().filter(|slot| **slot != Slot::Off).copied().collect();
+  }
+
+  // Returns which players are bots, and how well each of them plays.
+  pub fn get_bots(&self) -> Vec<(PlayerId, Difficulty)> {
+    return self.get_players().iter().enumerate()
+      .filter_map(|(id, slot)| {
+        match slot {
+          Slot::Bot(difficulty) => return Some((id as PlayerId, *difficulty)),
+          _ => return None
+        }
+      })
+      .collect();
+  }
+
+  pub fn get_stage_name(&self) -> &'static str {
+    return STAGES[self.stage].0;
+  }
+
+  pub fn get_theme_name(&self) -> &'static str {
+    return THEMES[self.theme];
+  }
+
+  pub fn build_stage(&self) -> StageImpl {
+    return StageImpl::from_layout(STAGES[self.stage].1).unwrap();
+  }
+
+  // Returns why a match can't be started with this setup, if there's anything stopping it.
+  pub fn check(&self) -> Result<(), String> {
+    let players: usize = self.get_players().len();
+    let spawns: usize = self.build_stage().get_spawns().len();
+    if players == 0 {
+      return Err(String::from("Nobody is playing"));
+    } else if players > spawns {
+      return Err(format!("{} only fits {} players", self.get_stage_name(), spawns));
+    }
+    // Bots can be doubled up, but two players can't share the same keys or pad.
+    for (i, slot) in self.slots.iter().enumerate() {
+      let human: bool = matches!(slot, Slot::Keyboard(_) | Slot::Gamepad(_));
+      if human && self.slots[..i].contains(slot) {
+        return Err(format!("{} is taken twice", slot.name()));
+      }
+    }
+    return Ok(());
+  }
+
+  // Starts a round with the chosen stage, rules and players. See WorldImpl::start for the seed.
+  pub fn start_round(&self, seed: u64) -> WorldImpl {
+    let players: usize = self.get_players().len();
+    return WorldImpl::start(self.rules, Box::new(self.build_stage()), players, seed);
+  }
+
+  pub fn get_slots(&self) -> &[Slot; MAX_PLAYERS] {
+    return &self.slots;
+  }
+
+  pub fn get_stage(&self) -> &usize {
+    return &self.stage;
+  }
+
+  pub fn get_theme(&self) -> &usize {
+    return &self.theme;
+  }
+
+  pub fn get_rules(&self) -> &Rules {
+    return &self.rules;
+  }
+
+  pub fn get_bindings(&self) -> &Bindings {
+    return &self.bindings;
+  }
+
+  pub fn get_volumes(&self) -> &Volumes {
+    return &self.volumes;
+  }
+
+  pub fn get_effects(&self) -> &EffectSettings {
+    return &self.effects;
+  }
+
+  pub fn set_slot(&self, index: usize, slot: Slot) -> MatchSetup {
+    let mut slots: [Slot; MAX_PLAYERS] = self.slots;
+    slots[index] = slot;
+    return MatchSetup {
+      slots: slots,
+      ..self.clone()
+    }
+  }
+
+  // Picks the stage a number of steps away from the current one, wrapping around.
+  pub fn cycle_stage(&self, steps: i32) -> MatchSetup {
+    return MatchSetup {
+      stage: (self.stage as i32 + steps).rem_euclid(STAGES.len() as i32) as usize,
+      ..self.clone()
+    }
+  }
+
+  // Picks the theme a number of steps away from the current one, wrapping around.
+  pub fn cycle_theme(&self, steps: i32) -> MatchSetup {
+    return MatchSetup {
+      theme: (self.theme as i32 + steps).rem_euclid(THEMES.len() as i32) as usize,
+      ..self.clone()
+    }
+  }
+
+  pub fn set_rules(&self, rules: Rules) -> MatchSetup {
+    return MatchSetup {
+      rules: rules,
+      ..self.clone()
+    }
+  }
+
+  pub fn set_bindings(&self, bindings: Bindings) -> MatchSetup {
+    return MatchSetup {
+      bindings: bindings,
+      ..self.clone()
+    }
+  }
+
+  pub fn set_volumes(&self, volumes: Volumes) -> MatchSetup {
+    return MatchSetup {
+      volumes: volumes,
+      ..self.clone()
+    }
+  }
+
+  pub fn set_effects(&self, effects: EffectSettings) -> MatchSetup {
+    return MatchSetup {
+      effects: effects,
+      ..self.clone()
+    }
+  }
+}
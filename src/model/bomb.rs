@@ -1,18 +1,22 @@
 use crate::{
-  common::direction::Direction,
-  model::{
-    stage::Tile,
-    util
-  }
+  common::direction::{Direction, CARDINALS},
+  model::input::PlayerId
 };
-use ndarray::{Array, Ix2};
+use serde::{Deserialize, Serialize};
+
+// Returns whether a tile is on a stage of some dimensions.
+fn on_stage(tile: &(i8, i8), dimensions: &(i8, i8)) -> bool {
+  return tile.0 >= 0 && tile.1 >= 0 && tile.0 < dimensions.0 && tile.1 < dimensions.1;
+}
 
 /* A struct representing a bomb.
- * While the lifetime of a bomb is always constant, the properties of one are affected by the stats
- * of the player who placed it.
+ * While the lifetime of a bomb is decided by the rules, the properties of one are affected by the
+ * stats of the player who placed it.
  *
  * Position should be obvious.
- * Lifetime is the time (in frames) a bomb has until it detonates.
+ * Owner is the ID of the player who placed it, who gets it back once it detonates.
+ * Lifetime is the time (in frames) a bomb has until it detonates. Remote bombs don't tick down at
+ * all, and instead wait for their owner to detonate them.
  * Piercing determines whether the bomb's blasts will go through breakable blocks rather than being
  * stopped by them.
  * Range determines how many blasts there are from the explosion in each cardinal direction. For
@@ -49,6 +53,7 @@ use ndarray::{Array, Ix2};
  *
  * Position should be obvious.
  * Like bombs, they have a lifetime, but naturally it should only be a short time.
+ * Owner is the player whose bomb it was, who gets the blame for anything it kills.
  */
 
 
@@ -62,20 +67,29 @@ use ndarray::{Array, Ix2};
  * Spread range determines the remaining range that the flame can spread in. This should be 0 if the
  * flame hits a wall, or if it has reached the end of its original range.
  */
-pub trait Flame {
-  /* Functionally ticks a flame by one frame.
+pub trait Flame: Send {
+  /* Ticks a flame by one frame, in place.
    * The flame itself is dependent on the world deciding whether it has stopped or not, but if it
    * has, then it should not be able to spread anymore.
    */
-  fn tick(&self, hit_wall: bool) -> Box<dyn Flame>;
+  fn tick(&mut self, hit_wall: bool);
+
+  fn copy(&self) -> Box<dyn Flame>;
 
   fn next_position(&self) -> (i8, i8);
 
+  // Returns whether a tile is anywhere along this flame, from its start to its end.
+  fn covers(&self, tile: &(i8, i8)) -> bool;
+
   fn get_start(&self) -> &(i8, i8);
 
   fn get_end(&self) -> &(i8, i8);
 
+  fn get_direction(&self) -> &Direction;
+
   fn get_spread_range(&self) -> &i8;
+
+  fn to_state(&self) -> FlameState;
 }
 
 pub struct FlameImpl {
@@ -85,6 +99,15 @@ pub struct FlameImpl {
   spread_range: i8
 }
 
+// A struct representing everything about a flame as plain data, which can be serialized.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct FlameState {
+  start: (i8, i8),
+  end: (i8, i8),
+  direction: Direction,
+  spread_range: i8
+}
+
 impl FlameImpl {
   fn new(start: (i8, i8), end: (i8, i8), direction: Direction, spread_range: i8) -> FlameImpl {
     return FlameImpl {
@@ -94,52 +117,36 @@ impl FlameImpl {
       spread_range: spread_range
     }
   }
+
+  pub fn from_state(state: &FlameState) -> FlameImpl {
+    return FlameImpl::new(state.start, state.end, state.direction, state.spread_range);
+  }
 }
 
 impl Flame for FlameImpl {
-  fn tick(&self, hit_wall: bool) -> Box<dyn Flame> {
-    let next_position: (i8, i8) = self.next_position();
+  fn tick(&mut self, hit_wall: bool) {
     match hit_wall {
-      true => return Box::new(
-        FlameImpl {
-          start: self.start,
-          end: self.end,
-          direction: self.direction,
-          spread_range: 0,
-        }
-      ),
+      true => self.spread_range = 0,
       false => {
-        match self.spread_range {
-          0 => return Box::new(
-            FlameImpl {
-              start: self.start,
-              end: self.end,
-              direction: self.direction,
-              spread_range: 0
-            }
-          ),
-          _ => return Box::new(
-            FlameImpl {
-              start: self.start,
-              end: self.next_position(),
-              direction: self.direction,
-              spread_range: self.spread_range - 1
-            }
-          )
+        if self.spread_range > 0 {
+          self.end = self.next_position();
+          self.spread_range -= 1;
         }
       }
     }
   }
 
+  fn copy(&self) -> Box<dyn Flame> {
+    return Box::new(FlameImpl::new(self.start, self.end, self.direction, self.spread_range));
+  }
+
   fn next_position(&self) -> (i8, i8) {
-    match self.direction {
-      Direction::North => return (self.end.0, self.end.1 + 1),
-      Direction::South => return (self.end.0, self.end.1 - 1),
-      Direction::West => return (self.end.0 - 1, self.end.1),
-      Direction::East => return (self.end.0 + 1, self.end.1),
-      // We should NOT be getting intermediate directions for this.
-      _ => return self.end
-    }
+    return self.direction.step(&self.end);
+  }
+
+  fn covers(&self, tile: &(i8, i8)) -> bool {
+    return tile.0 >= self.start.0.min(self.end.0) && tile.0 <= self.start.0.max(self.end.0)
+      && tile.1 >= self.start.1.min(self.end.1) && tile.1 <= self.start.1.max(self.end.1);
   }
 
   fn get_start(&self) -> &(i8, i8) {
@@ -150,180 +157,410 @@ impl Flame for FlameImpl {
     return &self.end;
   }
 
+  fn get_direction(&self) -> &Direction {
+    return &self.direction;
+  }
+
   fn get_spread_range(&self) -> &i8 {
     return &self.spread_range;
   }
+
+  fn to_state(&self) -> FlameState {
+    return FlameState {
+      start: self.start,
+      end: self.end,
+      direction: self.direction,
+      spread_range: self.spread_range
+    }
+  }
+}
+
+impl FlameState {
+  pub fn get_start(&self) -> &(i8, i8) {
+    return &self.start;
+  }
+
+  pub fn get_end(&self) -> &(i8, i8) {
+    return &self.end;
+  }
+
+  pub fn get_direction(&self) -> &Direction {
+    return &self.direction;
+  }
+
+  pub fn get_spread_range(&self) -> &i8 {
+    return &self.spread_range;
+  }
 }
 
-pub trait Blast {
-  fn tick(&self, hit_wall: Vec<bool>) -> Box<dyn Blast>;
+/* Ticking a blast in place means the world never has to rebuild its flames, so ticking a world that
+ * is only spreading and fading blasts doesn't have to allocate at all.
+ */
+pub trait Blast: Send {
+  /* Ticks a blast by one frame, in place.
+   * Hit wall should line up with the blast's flames, and say whether each flame's next position is
+   * blocked.
+   */
+  fn tick(&mut self, hit_wall: &[bool]);
+
+  fn copy(&self) -> Box<dyn Blast>;
+
+  // Returns whether the flames are going to spread on the next tick.
+  fn is_spreading(&self) -> bool;
 
-  fn next_positions(&self) -> Vec<(i8, i8)>;
+  // Returns whether a tile is covered by this blast, either at its center or by one of its flames.
+  fn covers(&self, tile: &(i8, i8)) -> bool;
 
   fn get_center(&self) -> &(i8, i8);
 
+  fn get_owner(&self) -> &PlayerId;
+
   fn get_flames(&self) -> &Vec<Box<dyn Flame>>;
 
+  fn get_piercing(&self) -> &bool;
+
+  fn get_spread_done(&self) -> &bool;
+
+  fn get_spread_rate(&self) -> &i8;
+
+  fn get_spread_timer(&self) -> &i8;
+
   fn get_lifetime(&self) -> &i8;
+
+  fn to_state(&self) -> BlastState;
 }
 
+/* Spread rate is how many frames the flames take to spread by one tile, while spread timer counts
+ * down the frames left until they next spread.
+ */
 pub struct BlastImpl {
   center: (i8, i8),
+  owner: PlayerId,
   flames: Vec<Box<dyn Flame>>,
+  piercing: bool,
+  spread_done: bool,
+  spread_rate: i8,
+  spread_timer: i8,
+  lifetime: i8
+}
+
+/* A struct representing everything about a blast as plain data, which can be serialized. Spread
+ * done is whether the flames have stopped spreading, which is when the lifetime starts ticking.
+ */
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct BlastState {
+  center: (i8, i8),
+  owner: PlayerId,
+  flames: Vec<FlameState>,
+  piercing: bool,
   spread_done: bool,
+  spread_rate: i8,
+  spread_timer: i8,
   lifetime: i8
 }
 
 impl BlastImpl {
+  /* Free says whether there's space for a flame in each cardinal direction, in the same order as
+   * CARDINALS.
+   */
   pub fn new(
-    center: (i8, i8), range: i8,
-    up_free: bool, down_free: bool, left_free: bool, right_free: bool
+    center: (i8, i8), owner: PlayerId, range: i8, piercing: bool, lifetime: i8, spread_rate: i8,
+    free: [bool; 4]
   ) -> BlastImpl {
-    let up_point: (i8, i8) = (center.0, center.1 + 1);
-    let down_point: (i8, i8) = (center.0, center.1 - 1);
-    let left_point: (i8, i8) = (center.0 - 1, center.1);
-    let right_point: (i8, i8) = (center.0 + 1, center.1);
-
-    let flames: Vec<Box<dyn Flame>> = vec!();
+    let mut flames: Vec<Box<dyn Flame>> = Vec::with_capacity(4);
 
     // We should only make blast flames in each cardinal direction if there's space for them.
-    if up_free {
-      flames.push(Box::new(FlameImpl::new(up_point, up_point, Direction::North, range)))
-    }
-    if down_free {
-      flames.push(Box::new(FlameImpl::new(down_point, down_point, Direction::South, range)))
-    }
-    if left_free {
-      flames.push(Box::new(FlameImpl::new(left_point, left_point, Direction::West, range)))
-    }
-    if right_free {
-      flames.push(Box::new(FlameImpl::new(right_point, right_point, Direction::East, range)))
+    for (free, direction) in free.iter().zip(CARDINALS.iter()) {
+      if *free {
+        let point: (i8, i8) = direction.step(&center);
+        // The first tile of each flame already counts towards the range.
+        flames.push(Box::new(FlameImpl::new(point, point, *direction, range - 1)));
+      }
     }
- 
+
     return BlastImpl {
       center: center,
+      owner: owner,
+      spread_done: flames.is_empty(),
       flames: flames,
-      spread_done: false,
-      lifetime: 60
-    }
-  }
-
-  // Calculates this blast's flames on the next tick.
-  fn calc_flames(&self, hit_wall: Vec<bool>) -> Vec<Box<dyn Flame>> {
-    let flames: Vec<Box<dyn Flame>> = vec!();
-    for i in 0..self.flames.len() {
-      flames.push(self.flames[i].tick(hit_wall[i]));
+      piercing: piercing,
+      spread_rate: spread_rate,
+      spread_timer: spread_rate - 1,
+      lifetime: lifetime
     }
-    return flames;
   }
 
-  /* Calculates this blast's lifetime on the next tick. Additionally, this also determines whether
-   * the spread of the blast has been completed, after which its lifetime can start ticking down.
+  /* Rebuilds a blast from its state, checking that it makes sense on a stage of some dimensions
+   * first. Its center has to be on the stage, and it has to have time left to live and to spread.
+   * Flames have to spread in a cardinal direction, and stay on the stage from their start to their
+   * end.
    */
-  fn calc_lifetime(&self) -> i8 {
-    match self.spread_done {
-      true => return self.lifetime - 1,
-      false => return self.lifetime
+  pub fn from_state(state: &BlastState, dimensions: &(i8, i8)) -> Result<BlastImpl, String> {
+    if state.flames.len() > CARDINALS.len() {
+      return Err(format!("Blasts can't have {} flames.", state.flames.len()));
+    } else if state.spread_rate <= 0 {
+      return Err(String::from("Blast spread rate must be at least 1 frame per tile."));
+    } else if !(0..state.spread_rate).contains(&state.spread_timer) {
+      return Err(
+        format!("Blast spread timer must be between 0 and {}.", state.spread_rate - 1)
+      );
+    } else if state.lifetime <= 0 {
+      return Err(String::from("Blast lifetime must be at least 1 frame."));
+    } else if !on_stage(&state.center, dimensions) {
+      return Err(format!("Blast at {:?} isn't on the stage.", state.center));
     }
+    for flame in state.flames.iter() {
+      let (start, end): ((i8, i8), (i8, i8)) = (flame.start, flame.end);
+      if !CARDINALS.contains(&flame.direction) {
+        return Err(format!("Flames can't spread {:?}.", flame.direction));
+      } else if !on_stage(&start, dimensions) || !on_stage(&end, dimensions) {
+        return Err(format!("Flame from {:?} to {:?} isn't on the stage.", start, end));
+      } else if start != end
+        && Direction::from_axes(end.0 - start.0, end.1 - start.1) != Some(flame.direction)
+      {
+        return Err(
+          format!("Flame from {:?} to {:?} doesn't spread {:?}.", start, end, flame.direction)
+        );
+      }
+    }
+    return Ok(
+      BlastImpl {
+        center: state.center,
+        owner: state.owner,
+        flames: state.flames.iter()
+          .map(|flame| Box::new(FlameImpl::from_state(flame)) as Box<dyn Flame>)
+          .collect(),
+        piercing: state.piercing,
+        spread_done: state.spread_done,
+        spread_rate: state.spread_rate,
+        spread_timer: state.spread_timer,
+        lifetime: state.lifetime
+      }
+    );
   }
 
   fn calc_spread_done(&self) -> bool {
+    return self.flames.iter().all(|flame| flame.get_spread_range() == &0);
+  }
+}
+
+impl Blast for BlastImpl {
+  fn tick(&mut self, hit_wall: &[bool]) {
+    // Lifetime only starts ticking down once the blast has finished spreading.
     match self.spread_done {
-      true => return true,
+      true => self.lifetime -= 1,
       false => {
-        let all_done: bool = true;
-        for flame in self.flames {
-          all_done = all_done && flame.get_spread_range() == &0;
+        // Flames only spread on the frames where the spread timer has run out.
+        match self.spread_timer {
+          0 => {
+            for (flame, hit_wall) in self.flames.iter_mut().zip(hit_wall.iter()) {
+              flame.tick(*hit_wall);
+            }
+            self.spread_timer = self.spread_rate - 1;
+          },
+          _ => self.spread_timer -= 1
         }
-        return all_done
+        self.spread_done = self.calc_spread_done();
       }
     }
   }
-}
-
-impl Blast for BlastImpl {
-  fn tick(&self, hit_wall: Vec<bool>) -> Box<dyn Blast> {
-    // We want to calculate lifetime first.
-    let new_lifetime: i8 = self.calc_lifetime();
-
-    // I don't think the order of the two steps below matter, but I could be wrong.
-    let new_spread_done: bool = self.calc_spread_done();
-    let new_flames: Vec<Box<dyn Flame>> = self.calc_flames(hit_wall);
 
+  fn copy(&self) -> Box<dyn Blast> {
     return Box::new(
       BlastImpl {
         center: self.center,
-        flames: new_flames,
+        owner: self.owner,
+        flames: self.flames.iter().map(|flame| flame.copy()).collect(),
+        piercing: self.piercing,
         spread_done: self.spread_done,
-        lifetime: new_lifetime
+        spread_rate: self.spread_rate,
+        spread_timer: self.spread_timer,
+        lifetime: self.lifetime
       }
     )
   }
 
-  fn next_positions(&self) -> Vec<(i8, i8)> {
-    let next_positions: Vec<(i8, i8)> = vec!();
-    for flame in self.flames {
-      next_positions.push(flame.next_position());
-    }
-    return next_positions;
+  fn is_spreading(&self) -> bool {
+    return !self.spread_done && self.spread_timer == 0;
+  }
+
+  fn covers(&self, tile: &(i8, i8)) -> bool {
+    return &self.center == tile || self.flames.iter().any(|flame| flame.covers(tile));
   }
 
   fn get_center(&self) -> &(i8, i8) {
     return &self.center;
   }
 
+  fn get_owner(&self) -> &PlayerId {
+    return &self.owner;
+  }
+
   fn get_flames(&self) -> &Vec<Box<dyn Flame>> {
     return &self.flames;
   }
 
+  fn get_piercing(&self) -> &bool {
+    return &self.piercing;
+  }
+
+  fn get_spread_done(&self) -> &bool {
+    return &self.spread_done;
+  }
+
+  fn get_spread_rate(&self) -> &i8 {
+    return &self.spread_rate;
+  }
+
+  fn get_spread_timer(&self) -> &i8 {
+    return &self.spread_timer;
+  }
+
   fn get_lifetime(&self) -> &i8 {
     return &self.lifetime;
   }
+
+  fn to_state(&self) -> BlastState {
+    return BlastState {
+      center: self.center,
+      owner: self.owner,
+      flames: self.flames.iter().map(|flame| flame.to_state()).collect(),
+      piercing: self.piercing,
+      spread_done: self.spread_done,
+      spread_rate: self.spread_rate,
+      spread_timer: self.spread_timer,
+      lifetime: self.lifetime
+    }
+  }
+}
+
+impl BlastState {
+  pub fn get_center(&self) -> &(i8, i8) {
+    return &self.center;
+  }
+
+  pub fn get_owner(&self) -> &PlayerId {
+    return &self.owner;
+  }
+
+  pub fn get_flames(&self) -> &Vec<FlameState> {
+    return &self.flames;
+  }
+
+  pub fn get_piercing(&self) -> &bool {
+    return &self.piercing;
+  }
+
+  pub fn get_spread_done(&self) -> &bool {
+    return &self.spread_done;
+  }
+
+  pub fn get_spread_rate(&self) -> &i8 {
+    return &self.spread_rate;
+  }
+
+  pub fn get_spread_timer(&self) -> &i8 {
+    return &self.spread_timer;
+  }
+
+  pub fn get_lifetime(&self) -> &i8 {
+    return &self.lifetime;
+  }
 }
 
-pub trait Bomb {
-  fn tick(&self) -> Box<dyn Bomb>;
+pub trait Bomb: Send {
+  // Ticks a bomb by one frame, in place.
+  fn tick(&mut self);
+
+  // Makes a bomb detonate on the next check, regardless of how much of its fuse is left.
+  fn detonate(&mut self);
+
+  fn copy(&self) -> Box<dyn Bomb>;
 
   fn can_detonate(&self) -> bool;
 
   fn get_position(&self) -> &(i8, i8);
 
+  fn get_owner(&self) -> &PlayerId;
+
   fn get_lifetime(&self) -> &i16;
 
+  fn get_remote(&self) -> &bool;
+
   fn get_piercing(&self) -> &bool;
 
   fn get_range(&self) -> &i8;
-}
 
+  fn to_state(&self) -> BombState;
+}
 
+#[derive(Copy, Clone)]
 pub struct BombImpl {
   position: (i8, i8),
+  owner: PlayerId,
   lifetime: i16,
+  remote: bool,
+  piercing: bool,
+  range: i8
+}
+
+// A struct representing everything about a bomb as plain data, which can be serialized.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct BombState {
+  position: (i8, i8),
+  owner: PlayerId,
+  lifetime: i16,
+  remote: bool,
   piercing: bool,
   range: i8
 }
 
 impl BombImpl {
-  fn new(position: (i8, i8), piercing: bool, range: i8) -> BombImpl {
+  pub fn new(
+    position: (i8, i8), owner: PlayerId, lifetime: i16, remote: bool, piercing: bool, range: i8
+  ) -> BombImpl {
     return BombImpl {
       position: position,
-      lifetime: 300,
+      owner: owner,
+      lifetime: lifetime,
+      remote: remote,
       piercing: piercing,
       range: range
     }
-  } 
+  }
+
+  /* Rebuilds a bomb from its state, checking that it makes sense on a stage of some dimensions
+   * first. It has to be on the stage, with time left on its fuse and at least 1 tile of range.
+   */
+  pub fn from_state(state: &BombState, dimensions: &(i8, i8)) -> Result<BombImpl, String> {
+    if !on_stage(&state.position, dimensions) {
+      return Err(format!("Bomb at {:?} isn't on the stage.", state.position));
+    } else if state.lifetime <= 0 {
+      return Err(String::from("Bomb lifetime must be at least 1 frame."));
+    } else if state.range < 1 {
+      return Err(String::from("Bomb range must be at least 1 tile."));
+    }
+    return Ok(
+      BombImpl::new(
+        state.position, state.owner, state.lifetime, state.remote, state.piercing, state.range
+      )
+    );
+  }
 }
 
 impl Bomb for BombImpl {
-  fn tick(&self) -> Box<dyn Bomb> {
-    return Box::new(
-      BombImpl {
-        position: self.position,
-        lifetime: self.lifetime - 1,
-        piercing: self.piercing,
-        range: self.range
-      }
-    )
+  fn tick(&mut self) {
+    if !self.remote && self.lifetime > 0 {
+      self.lifetime -= 1;
+    }
+  }
+
+  fn detonate(&mut self) {
+    self.lifetime = 0;
+  }
+
+  fn copy(&self) -> Box<dyn Bomb> {
+    return Box::new(*self);
   }
 
   fn can_detonate(&self) -> bool {
@@ -334,10 +571,18 @@ impl Bomb for BombImpl {
     return &self.position;
   }
 
+  fn get_owner(&self) -> &PlayerId {
+    return &self.owner;
+  }
+
   fn get_lifetime(&self) -> &i16 {
     return &self.lifetime;
   }
 
+  fn get_remote(&self) -> &bool {
+    return &self.remote;
+  }
+
   fn get_piercing(&self) -> &bool {
     return &self.piercing;
   }
@@ -345,4 +590,41 @@ impl Bomb for BombImpl {
   fn get_range(&self) -> &i8 {
     return &self.range
   }
+
+  fn to_state(&self) -> BombState {
+    return BombState {
+      position: self.position,
+      owner: self.owner,
+      lifetime: self.lifetime,
+      remote: self.remote,
+      piercing: self.piercing,
+      range: self.range
+    }
+  }
+}
+
+impl BombState {
+  pub fn get_position(&self) -> &(i8, i8) {
+    return &self.position;
+  }
+
+  pub fn get_owner(&self) -> &PlayerId {
+    return &self.owner;
+  }
+
+  pub fn get_lifetime(&self) -> &i16 {
+    return &self.lifetime;
+  }
+
+  pub fn get_remote(&self) -> &bool {
+    return &self.remote;
+  }
+
+  pub fn get_piercing(&self) -> &bool {
+    return &self.piercing;
+  }
+
+  pub fn get_range(&self) -> &i8 {
+    return &self.range;
+  }
 }
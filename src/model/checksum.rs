@@ -0,0 +1,274 @@
+use crate::model::{
+  player::{Abilities, Player},
+  rules::{ItemDropRates, Rules, StartingStats},
+  stage::Stage,
+  world::World
+};
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/* A struct representing a checksum being worked out (64 bit FNV-1a).
+ * Numbers always go in as little endian bytes and lists always go in with their length first, so
+ * the same values give the same checksum on every machine and with every build, however they
+ * happen to be laid out in memory.
+ */
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Checksum {
+  hash: u64
+}
+
+impl Default for Checksum {
+  fn default() -> Checksum {
+    return Checksum { hash: FNV_OFFSET };
+  }
+}
+
+impl Checksum {
+  pub fn write(&mut self, bytes: &[u8]) {
+    for byte in bytes {
+      self.hash = (self.hash ^ *byte as u64).wrapping_mul(FNV_PRIME);
+    }
+  }
+
+  pub fn write_bool(&mut self, value: bool) {
+    self.write(&[value as u8]);
+  }
+
+  pub fn write_u8(&mut self, value: u8) {
+    self.write(&[value]);
+  }
+
+  pub fn write_i8(&mut self, value: i8) {
+    self.write(&value.to_le_bytes());
+  }
+
+  pub fn write_i16(&mut self, value: i16) {
+    self.write(&value.to_le_bytes());
+  }
+
+  pub fn write_i32(&mut self, value: i32) {
+    self.write(&value.to_le_bytes());
+  }
+
+  pub fn write_u32(&mut self, value: u32) {
+    self.write(&value.to_le_bytes());
+  }
+
+  pub fn write_u64(&mut self, value: u64) {
+    self.write(&value.to_le_bytes());
+  }
+
+  // Lengths are written as 32 bits, so they come out the same whatever the size of a usize is.
+  pub fn write_length(&mut self, length: usize) {
+    self.write_u32(length as u32);
+  }
+
+  pub fn write_tile(&mut self, tile: &(i8, i8)) {
+    self.write_i8(tile.0);
+    self.write_i8(tile.1);
+  }
+
+  pub fn finish(&self) -> u64 {
+    return self.hash;
+  }
+}
+
+fn write_abilities(checksum: &mut Checksum, abilities: &Abilities) {
+  checksum.write_bool(*abilities.get_remote_control());
+  checksum.write_bool(*abilities.get_line_bomb());
+}
+
+fn write_rules(checksum: &mut Checksum, rules: &Rules) {
+  checksum.write_i16(*rules.get_bomb_fuse());
+  checksum.write_i8(*rules.get_blast_lifetime());
+  checksum.write_i8(*rules.get_flame_spread_rate());
+  let stats: &StartingStats = rules.get_starting_stats();
+  checksum.write_i32(*stats.get_speed());
+  checksum.write_u8(*stats.get_bombs());
+  checksum.write_i8(*stats.get_range());
+  checksum.write_bool(*stats.get_piercing());
+  write_abilities(checksum, stats.get_abilities());
+  let rates: &ItemDropRates = rules.get_item_drop_rates();
+  for rate in [
+    rates.get_chance(), rates.get_bomb_up(), rates.get_fire_up(), rates.get_speed_up(),
+    rates.get_pierce_bomb(), rates.get_remote_control(), rates.get_line_bomb()
+  ] {
+    checksum.write_u8(*rate);
+  }
+  checksum.write_u32(*rules.get_round_time());
+  checksum.write_u32(*rules.get_sudden_death_time());
+}
+
+fn write_player(checksum: &mut Checksum, player: &Player) {
+  checksum.write_u8(*player.get_id());
+  checksum.write_i32(*player.get_speed());
+  checksum.write_i32(player.get_position().0);
+  checksum.write_i32(player.get_position().1);
+  checksum.write_i32(player.get_previous_position().0);
+  checksum.write_i32(player.get_previous_position().1);
+  checksum.write_u8(*player.get_direction() as u8);
+  checksum.write_u8(*player.get_bombs());
+  checksum.write_i8(*player.get_range());
+  checksum.write_bool(*player.get_piercing());
+  write_abilities(checksum, player.get_abilities());
+  checksum.write_bool(*player.get_alive());
+}
+
+/* Works out a checksum of everything a world is, the same things a WorldState captures, without
+ * capturing one or allocating anything, so it's cheap enough to do every tick. Two worlds with the
+ * same checksum are almost certainly the same, which is what netplay and replays check with.
+ *
+ * Everything goes in the order WorldState has it in, with the stage row by row from the bottom and
+ * the players, bombs, blasts and items in the order the world keeps them. Enums go in as where
+ * they are in their declaration, so reordering one changes checksums, just like changing how worlds
+ * play out does.
+ */
+pub fn checksum(world: &dyn World) -> u64 {
+  let mut checksum: Checksum = Checksum::default();
+  write_rules(&mut checksum, world.get_rules());
+
+  let stage: &dyn Stage = world.get_stage();
+  checksum.write_tile(stage.get_dimensions());
+  for tile in stage.get_tiles().iter() {
+    checksum.write_u8(*tile as u8);
+  }
+  checksum.write_length(stage.get_spawns().len());
+  for spawn in stage.get_spawns() {
+    checksum.write_tile(spawn);
+  }
+
+  checksum.write_length(world.get_players().len());
+  for player in world.get_players() {
+    write_player(&mut checksum, player);
+  }
+
+  checksum.write_length(world.get_bombs().len());
+  for bomb in world.get_bombs() {
+    checksum.write_tile(bomb.get_position());
+    checksum.write_u8(*bomb.get_owner());
+    checksum.write_i16(*bomb.get_lifetime());
+    checksum.write_bool(*bomb.get_remote());
+    checksum.write_bool(*bomb.get_piercing());
+    checksum.write_i8(*bomb.get_range());
+  }
+
+  checksum.write_length(world.get_blasts().len());
+  for blast in world.get_blasts() {
+    checksum.write_tile(blast.get_center());
+    checksum.write_u8(*blast.get_owner());
+    checksum.write_length(blast.get_flames().len());
+    for flame in blast.get_flames() {
+      checksum.write_tile(flame.get_start());
+      checksum.write_tile(flame.get_end());
+      checksum.write_u8(*flame.get_direction() as u8);
+      checksum.write_i8(*flame.get_spread_range());
+    }
+    checksum.write_bool(*blast.get_piercing());
+    checksum.write_bool(*blast.get_spread_done());
+    checksum.write_i8(*blast.get_spread_rate());
+    checksum.write_i8(*blast.get_spread_timer());
+    checksum.write_i8(*blast.get_lifetime());
+  }
+
+  checksum.write_length(world.get_items().len());
+  for (tile, item) in world.get_items() {
+    checksum.write_tile(tile);
+    checksum.write_u8(*item as u8);
+  }
+  checksum.write_u64(*world.get_rng().get_state());
+  checksum.write_u32(*world.get_time());
+  return checksum.finish();
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::model::{
+    bomb::{Bomb, BombImpl},
+    input,
+    item::Item,
+    stage::StageImpl,
+    state::WorldState,
+    world::WorldImpl
+  };
+  use serde_json::{json, Value};
+
+  // Starts a round with two bombs and two items lying around, so every list has something in it.
+  fn start() -> WorldImpl {
+    let stage: StageImpl = StageImpl::from_layout("0.+..\n.#.#.\n....1").unwrap();
+    let world: WorldImpl = WorldImpl::start(Rules::default(), Box::new(stage), 2, 5);
+    return change(&world, &|json| {
+      json["bombs"] = json!([
+        BombImpl::new((2, 0), 1, 40, false, false, 2).to_state(),
+        BombImpl::new((4, 1), 0, 90, true, false, 1).to_state()
+      ]);
+      json["items"] = json!([[[1, 2], Item::FireUp], [[2, 1], Item::SpeedUp]]);
+    });
+  }
+
+  // Rebuilds a world from its state after changing it.
+  fn change(world: &WorldImpl, change: &dyn Fn(&mut Value)) -> WorldImpl {
+    let mut json: Value = serde_json::to_value(WorldState::capture(world)).unwrap();
+    change(&mut json);
+    return WorldImpl::from_state(&serde_json::from_value(json).unwrap()).unwrap();
+  }
+
+  #[test]
+  fn equal_worlds_have_equal_checksums() {
+    let (mut a, mut b): (WorldImpl, WorldImpl) = (start(), start());
+    assert_eq!(checksum(&a), checksum(&b));
+    assert_eq!(checksum(&a), checksum(&a.clone()));
+    for _ in 0..120 {
+      a.tick_mut(&input::idle_frame());
+      b.tick_mut(&input::idle_frame());
+      assert_eq!(checksum(&a), checksum(&b));
+    }
+  }
+
+  #[test]
+  fn changing_anything_changes_the_checksum() {
+    let world: WorldImpl = start();
+    let changes: [&dyn Fn(&mut Value); 6] = [
+      &|json| json["players"][1]["position"][0] = json!(4 * 256 - 1),
+      &|json| json["bombs"][0]["lifetime"] = json!(39),
+      &|json| json["items"][1][1] = json!(Item::BombUp),
+      &|json| json["stage"]["rows"][0] = json!("....."),
+      &|json| json["rng"] = json!(6),
+      &|json| json["time"] = json!(1)
+    ];
+    let mut checksums: Vec<u64> = vec!(checksum(&world));
+    for change_field in changes.iter() {
+      let changed: u64 = checksum(&change(&world, *change_field));
+      assert!(!checksums.contains(&changed));
+      checksums.push(changed);
+    }
+  }
+
+  #[test]
+  fn checksums_dont_depend_on_how_a_state_was_written() {
+    let world: WorldImpl = start();
+    // JSON objects come back with their keys sorted, rather than in the order states write them.
+    let written: String = WorldState::capture(&world).to_json().unwrap();
+    let sorted: String = serde_json::from_str::<Value>(&written).unwrap().to_string();
+    assert_ne!(written.replace(char::is_whitespace, ""), sorted);
+    let loaded: WorldImpl =
+      WorldImpl::from_state(&WorldState::from_json(&sorted).unwrap()).unwrap();
+    assert_eq!(checksum(&loaded), checksum(&world));
+  }
+
+  #[test]
+  fn checksums_depend_on_the_order_worlds_keep_things_in() {
+    let world: WorldImpl = start();
+    let swap = |field: &'static str| {
+      return move |json: &mut Value| json[field].as_array_mut().unwrap().swap(0, 1);
+    };
+    // Bombs go off and items get picked up in the order they're kept in, so that matters.
+    assert_ne!(checksum(&change(&world, &swap("bombs"))), checksum(&world));
+    assert_ne!(checksum(&change(&world, &swap("items"))), checksum(&world));
+    // So does which coordinate is which.
+    let transpose = |json: &mut Value| json["players"][0]["position"] = json!([2 * 256, 0]);
+    assert_eq!(world.get_players()[0].get_position(), &(0, 2 * 256));
+    assert_ne!(checksum(&change(&world, &transpose)), checksum(&world));
+  }
+}
@@ -0,0 +1,35 @@
+use crate::model::{input::PlayerId, item::Item, world::Outcome};
+
+/* An enum representing why a bomb went off.
+ * Chained bombs were caught in another bomb's blast, which is given by who owned that bomb and
+ * where it was.
+ */
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DetonationCause {
+  Fuse,
+  Remote,
+  Chain { owner: PlayerId, position: (i8, i8) }
+}
+
+/* An enum representing something that happened during a tick.
+ * Ticks report these in the order they happened, so that anything reacting to a world (like
+ * audio) can do so the moment something happens, rather than working it out by comparing worlds.
+ */
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum WorldEvent {
+  // The first tick of a round.
+  RoundStarted,
+  BombPlaced { owner: PlayerId, position: (i8, i8) },
+  BombDetonated { owner: PlayerId, position: (i8, i8), cause: DetonationCause },
+  // A flame of a blast reached a new tile.
+  FlameSpread { owner: PlayerId, position: (i8, i8) },
+  // A soft wall was burned away.
+  TileDestroyed { position: (i8, i8) },
+  ItemSpawned { item: Item, position: (i8, i8) },
+  ItemCollected { id: PlayerId, item: Item, position: (i8, i8) },
+  // Killer is whoever owned the blast, which can be the player themselves.
+  PlayerKilled { id: PlayerId, killer: PlayerId },
+  // The clock reached sudden death, which is only a warning; see Rules.
+  SuddenDeath,
+  RoundEnded { outcome: Outcome }
+}
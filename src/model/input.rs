@@ -0,0 +1,133 @@
+use crate::common::direction::Direction;
+
+// The most players a world can have. Player IDs run from 0 up to (but not including) this.
+pub const MAX_PLAYERS: usize = 8;
+
+pub type PlayerId = u8;
+
+// Every direction an input can hold, in the order they're numbered in packed inputs.
+const DIRECTIONS: [Direction; 8] = [
+  Direction::North, Direction::South, Direction::West, Direction::East,
+  Direction::Northwest, Direction::Northeast, Direction::Southwest, Direction::Southeast
+];
+
+/* A struct representing what a player is doing for a single tick.
+ * Every frontend, bot and network layer drives the world through these, so a world given the same
+ * inputs will always play out the same way no matter where they came from.
+ *
+ * Direction is the way the player is moving, if they're moving at all.
+ * The rest are buttons, which should only be true on the tick they were pressed. Frontends are
+ * responsible for turning held keys into presses; holding a direction is fine though.
+ */
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct PlayerInput {
+  direction: Option<Direction>,
+  place_bomb: bool,
+  detonate: bool,
+  special: bool
+}
+
+impl PlayerInput {
+  pub fn new(
+    direction: Option<Direction>, place_bomb: bool, detonate: bool, special: bool
+  ) -> PlayerInput {
+    return PlayerInput {
+      direction: direction,
+      place_bomb: place_bomb,
+      detonate: detonate,
+      special: special
+    }
+  }
+
+  // Returns this input with every button released, keeping only the direction being held.
+  pub fn held(&self) -> PlayerInput {
+    return PlayerInput::new(self.direction, false, false, false);
+  }
+
+  /* Packs an input into a byte, for replays and netplay: the direction in the low 4 bits, then a
+   * bit for each button.
+   */
+  pub fn to_byte(&self) -> u8 {
+    let direction: u8 = match self.direction {
+      Some(direction) => DIRECTIONS.iter().position(|other| *other == direction).unwrap() as u8 + 1,
+      None => 0
+    };
+    return direction
+      | (self.place_bomb as u8) << 4
+      | (self.detonate as u8) << 5
+      | (self.special as u8) << 6;
+  }
+
+  pub fn from_byte(byte: u8) -> Result<PlayerInput, String> {
+    let direction: Option<Direction> = match byte & 0x0f {
+      0 => None,
+      index if index as usize <= DIRECTIONS.len() => Some(DIRECTIONS[index as usize - 1]),
+      _ => return Err(format!("{:#04x} isn't a valid input.", byte))
+    };
+    return Ok(PlayerInput::new(direction, byte & 0x10 != 0, byte & 0x20 != 0, byte & 0x40 != 0));
+  }
+
+  pub fn get_direction(&self) -> &Option<Direction> {
+    return &self.direction;
+  }
+
+  pub fn get_place_bomb(&self) -> &bool {
+    return &self.place_bomb;
+  }
+
+  pub fn get_detonate(&self) -> &bool {
+    return &self.detonate;
+  }
+
+  pub fn get_special(&self) -> &bool {
+    return &self.special;
+  }
+}
+
+/* The inputs of every player for a single tick, indexed by player ID.
+ * Slots without a player in them are simply ignored.
+ */
+pub type InputFrame = [PlayerInput; MAX_PLAYERS];
+
+// Returns an input frame where nobody is doing anything.
+pub fn idle_frame() -> InputFrame {
+  return [PlayerInput::default(); MAX_PLAYERS];
+}
+
+// Returns an input frame with every button released, keeping only the directions being held.
+pub fn held_frame(frame: &InputFrame) -> InputFrame {
+  let mut held: InputFrame = idle_frame();
+  for (held_input, input) in held.iter_mut().zip(frame.iter()) {
+    *held_input = input.held();
+  }
+  return held;
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn every_input_survives_a_byte() {
+    for direction in std::iter::once(None).chain(DIRECTIONS.iter().copied().map(Some)) {
+      for buttons in 0..8 {
+        let input: PlayerInput =
+          PlayerInput::new(direction, buttons & 1 != 0, buttons & 2 != 0, buttons & 4 != 0);
+        assert_eq!(PlayerInput::from_byte(input.to_byte()), Ok(input));
+      }
+    }
+  }
+
+  #[test]
+  fn bytes_with_no_direction_for_them_are_rejected() {
+    for byte in [0x09, 0x0f, 0x1a] {
+      assert!(PlayerInput::from_byte(byte).is_err());
+    }
+  }
+
+  #[test]
+  fn held_inputs_keep_only_the_direction() {
+    let input: PlayerInput = PlayerInput::new(Some(Direction::West), true, true, true);
+    assert_eq!(input.held(), PlayerInput::new(Some(Direction::West), false, false, false));
+  }
+}
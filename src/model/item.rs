@@ -0,0 +1,90 @@
+use crate::model::{
+  player::{Abilities, Player},
+  rng::Rng,
+  rules::ItemDropRates
+};
+use serde::{Deserialize, Serialize};
+
+// The most bombs a player can have out at once, however many bomb ups they pick up.
+pub const MAX_BOMBS: u8 = 8;
+
+// The furthest a player's bombs can reach, however many fire ups they pick up.
+pub const MAX_RANGE: i8 = 8;
+
+// How much faster each speed up makes a player, in sub-tile units per tick.
+pub const SPEED_UP: i32 = 4;
+
+// The fastest a player can get, in sub-tile units per tick.
+pub const MAX_SPEED: i32 = 46;
+
+/* An enum representing a power-up, which soft walls can drop when they're destroyed.
+ * Players pick items up by walking onto them, and keep what they picked up until the round ends.
+ */
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub enum Item {
+  BombUp,
+  FireUp,
+  SpeedUp,
+  PierceBomb,
+  RemoteControl,
+  LineBomb
+}
+
+pub const ITEMS: [Item; 6] = [
+  Item::BombUp, Item::FireUp, Item::SpeedUp, Item::PierceBomb, Item::RemoteControl, Item::LineBomb
+];
+
+impl Item {
+  pub fn name(&self) -> &'static str {
+    match self {
+      Item::BombUp => return "Bomb Up",
+      Item::FireUp => return "Fire Up",
+      Item::SpeedUp => return "Speed Up",
+      Item::PierceBomb => return "Pierce Bomb",
+      Item::RemoteControl => return "Remote Control",
+      Item::LineBomb => return "Line Bomb"
+    }
+  }
+
+  // Returns a player once they've picked this item up.
+  pub fn apply(&self, player: &Player) -> Player {
+    let abilities: &Abilities = player.get_abilities();
+    match self {
+      Item::BombUp => return player.set_bombs((player.get_bombs() + 1).min(MAX_BOMBS)),
+      Item::FireUp => return player.set_range((player.get_range() + 1).min(MAX_RANGE)),
+      Item::SpeedUp => return player.set_speed((player.get_speed() + SPEED_UP).min(MAX_SPEED)),
+      Item::PierceBomb => return player.set_piercing(true),
+      Item::RemoteControl => {
+        return player.set_abilities(Abilities::new(true, *abilities.get_line_bomb()));
+      },
+      Item::LineBomb => {
+        return player.set_abilities(Abilities::new(*abilities.get_remote_control(), true));
+      }
+    }
+  }
+
+  /* Rolls for what a destroyed soft wall drops, if anything. Whether it drops anything at all is
+   * down to the drop chance, and which item it is is down to each item's weight.
+   */
+  pub fn roll(rates: &ItemDropRates, rng: &mut Rng) -> Option<Item> {
+    if rng.below(100) >= *rates.get_chance() as u32 {
+      return None;
+    }
+    let weights: [u8; 6] = [
+      *rates.get_bomb_up(), *rates.get_fire_up(), *rates.get_speed_up(),
+      *rates.get_pierce_bomb(), *rates.get_remote_control(), *rates.get_line_bomb()
+    ];
+    let total: u32 = weights.iter().map(|weight| *weight as u32).sum();
+    if total == 0 {
+      return None;
+    }
+    let mut roll: u32 = rng.below(total);
+    for (item, weight) in ITEMS.iter().zip(weights.iter()) {
+      if roll < *weight as u32 {
+        return Some(*item);
+      }
+      roll -= *weight as u32;
+    }
+    return None;
+  }
+}
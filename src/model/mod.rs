@@ -1,5 +1,15 @@
 pub mod bomb;
+pub mod checksum;
+pub mod event;
+pub mod input;
+pub mod item;
 pub mod player;
+pub mod position;
+pub mod prediction;
+pub mod rng;
+pub mod rules;
+pub mod score;
 pub mod stage;
-pub mod util;
+pub mod state;
+pub mod timestep;
 pub mod world;
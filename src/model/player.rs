@@ -1,86 +1,216 @@
-use crate::common::direction::Direction;
+use crate::{
+  common::direction::Direction,
+  model::{
+    input::PlayerId,
+    position,
+    rules::StartingStats
+  }
+};
+use serde::{Deserialize, Serialize};
+
+/* A struct representing the abilities a player can have on top of their stats.
+ *
+ * Remote control stops a player's bombs from ticking down, leaving them to detonate them instead.
+ * Line bomb lets a player place as many bombs as they have left in a line in front of them.
+ */
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Abilities {
+  remote_control: bool,
+  line_bomb: bool
+}
+
+impl Abilities {
+  pub fn new(remote_control: bool, line_bomb: bool) -> Abilities {
+    return Abilities {
+      remote_control: remote_control,
+      line_bomb: line_bomb
+    }
+  }
+
+  pub fn get_remote_control(&self) -> &bool {
+    return &self.remote_control;
+  }
+
+  pub fn get_line_bomb(&self) -> &bool {
+    return &self.line_bomb;
+  }
+}
 
 /* A struct representing a player.
  * Although their movement will probably be tied to a grid system at the moment, this is subject to
  * change. Regardless, these guys are the core of the game, as they are the only entities capable of
  * placing bombs. Since they all die in one hit, there is no need to keep track of HP.
  *
- * All a player needs is a position, but it's possible they'll require a function callback as a
- * field when controllers are introduced.
+ * ID is what a player is known by for their whole life, and which slot of an input frame controls
+ * them. Two players can end up in exactly the same place, so nothing else can tell them apart.
+ * Speed and position are both in sub-tile units; see the position module. Previous position is
+ * where the player was at the start of the last tick, which renderers interpolate from.
+ * Bombs, range, piercing and abilities start off as the rules' starting stats.
+ * Alive is whether the player is still in the round. Dead players stay in the world so that IDs
+ * keep lining up with their slots, but they can't do anything.
  */
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
 pub struct Player {
-  speed: f32,
-  position: (f32, f32),
-  direction: Direction
+  id: PlayerId,
+  speed: i32,
+  position: (i32, i32),
+  previous_position: (i32, i32),
+  direction: Direction,
+  bombs: u8,
+  range: i8,
+  piercing: bool,
+  abilities: Abilities,
+  alive: bool
 }
 
-impl PartialEq for Player {
-  fn eq(&self, other: &Self) -> bool {
-    return self.speed == other.speed
-        && self.position == other.position
-        && self.direction == other.direction;
+impl Player {
+  pub fn new (
+    id: PlayerId, position: (i32, i32), direction: Direction, stats: &StartingStats
+  ) -> Player {
+    return Player {
+      id: id,
+      speed: *stats.get_speed(),
+      position: position,
+      previous_position: position,
+      direction: direction,
+      bombs: *stats.get_bombs(),
+      range: *stats.get_range(),
+      piercing: *stats.get_piercing(),
+      abilities: *stats.get_abilities(),
+      alive: true
+    }
   }
-}
 
-impl Eq for Player {}
+  /* Returns how far this player moves along each axis in a frame.
+   * Diagonal movement is split between both axes, so it isn't any faster than moving straight.
+   */
+  pub fn get_velocity(&self) -> (i32, i32) {
+    let step: (i8, i8) = self.direction.step(&(0, 0));
+    let axis_speed: i32 = match step.0 != 0 && step.1 != 0 {
+      true => position::diagonal(self.speed),
+      false => self.speed
+    };
+    return (step.0 as i32 * axis_speed, step.1 as i32 * axis_speed);
+  }
 
-impl Player {
-  pub fn new (position: (f32, f32), direction: Direction) -> Player {
+  // Returns the tile this player is standing in. This is also where their bombs get placed.
+  pub fn get_tile(&self) -> (i8, i8) {
+    return position::to_tile(&self.position);
+  }
+
+  pub fn get_id(&self) -> &PlayerId {
+    return &self.id;
+  }
+
+  pub fn get_speed(&self) -> &i32 {
+    return &self.speed;
+  }
+
+  pub fn get_position(&self) -> &(i32, i32) {
+    return &self.position;
+  }
+
+  pub fn get_previous_position(&self) -> &(i32, i32) {
+    return &self.previous_position;
+  }
+
+  pub fn get_direction(&self) -> &Direction {
+    return &self.direction;
+  }
+
+  pub fn get_bombs(&self) -> &u8 {
+    return &self.bombs;
+  }
+
+  pub fn get_range(&self) -> &i8 {
+    return &self.range;
+  }
+
+  pub fn get_piercing(&self) -> &bool {
+    return &self.piercing;
+  }
+
+  pub fn get_abilities(&self) -> &Abilities {
+    return &self.abilities;
+  }
+
+  pub fn get_alive(&self) -> &bool {
+    return &self.alive;
+  }
+
+  pub fn set_position(&self, position: (i32, i32)) -> Player {
     return Player {
-      speed: 0.1,
       position: position,
-      direction: direction
+      ..*self
     }
   }
 
-  pub fn next_position(&self) -> (f32, f32){ 
-    match self.direction {
-      Direction::North => return (self.position.0, self.position.1 + self.speed),
-      Direction::South => return (self.position.0, self.position.1 - self.speed),
-      Direction::West => return (self.position.0 - self.speed, self.position.1),
-      Direction::East => return (self.position.0 + self.speed, self.position.1),
-      intermediate => {
-        let linear_speed: f32 = self.speed / (2 as f32).sqrt();
-        match intermediate {
-          Direction::Northwest => {
-            return (self.position.0 - linear_speed, self.position.1 + linear_speed);
-          },
-          Direction::Northeast => {
-            return (self.position.0 + linear_speed, self.position.1 + linear_speed);
-          },
-          Direction::Southwest => {
-            return (self.position.0 - linear_speed, self.position.1 - linear_speed);
-          },
-          Direction::Southeast => {
-            return (self.position.0 - linear_speed, self.position.1 + linear_speed);
-          }
-        }
-      }
+  /* Moves this player by an offset without them walking there, keeping how far they moved on the
+   * last tick, for drawing them somewhere other than where they are.
+   */
+  pub fn shift(&self, offset: (i32, i32)) -> Player {
+    return Player {
+      position: (self.position.0 + offset.0, self.position.1 + offset.1),
+      previous_position: (self.previous_position.0 + offset.0, self.previous_position.1 + offset.1),
+      ..*self
     }
   }
 
-  pub fn set_position(&self, position: (f32, f32)) -> Player {
+  // Remembers where this player is now, before a tick moves them anywhere.
+  pub fn set_previous_position(&self) -> Player {
     return Player {
-      speed: self.speed,
-      position: position,
-      direction: self.direction
+      previous_position: self.position,
+      ..*self
     }
   }
 
-  pub fn set_next_position(&self) -> Player {
+  pub fn set_direction(&self, direction: Direction) -> Player {
     return Player {
-      speed: self.speed,
-      position: self.next_position(),
-      direction: self.direction
+      direction: direction,
+      ..*self
     }
   }
 
-  pub fn set_direction(&self, direction: Direction) -> Player {
+  pub fn set_speed(&self, speed: i32) -> Player {
+    return Player {
+      speed: speed,
+      ..*self
+    }
+  }
+
+  pub fn set_bombs(&self, bombs: u8) -> Player {
+    return Player {
+      bombs: bombs,
+      ..*self
+    }
+  }
+
+  pub fn set_range(&self, range: i8) -> Player {
+    return Player {
+      range: range,
+      ..*self
+    }
+  }
+
+  pub fn set_piercing(&self, piercing: bool) -> Player {
+    return Player {
+      piercing: piercing,
+      ..*self
+    }
+  }
+
+  pub fn set_abilities(&self, abilities: Abilities) -> Player {
+    return Player {
+      abilities: abilities,
+      ..*self
+    }
+  }
+
+  pub fn set_alive(&self, alive: bool) -> Player {
     return Player {
-      speed: self.speed,
-      position: self.position,
-      direction: direction
+      alive: alive,
+      ..*self
     }
   }
 }
@@ -0,0 +1,32 @@
+/* Player positions and speeds are kept in fixed point rather than floats, so that moving around
+ * gives exactly the same results on every compiler and platform.
+ *
+ * A tile is split into SUBTILE units along each axis, and a position of (x, y) in tiles is stored
+ * as (x * SUBTILE, y * SUBTILE). In other words, a player is standing exactly on the center of a
+ * tile when both of their coordinates are multiples of SUBTILE.
+ */
+pub const SUBTILE: i32 = 256;
+
+/* Roughly 1 / sqrt(2) in units of 1 / SUBTILE.
+ * Moving diagonally should cover about the same distance as moving straight, so each axis only
+ * gets this fraction of the speed.
+ */
+const DIAGONAL_FACTOR: i32 = 181;
+
+// Returns how far along each axis a speed gets a player when moving diagonally.
+pub fn diagonal(speed: i32) -> i32 {
+  return speed * DIAGONAL_FACTOR / SUBTILE;
+}
+
+// Returns the tile a position is in, which is whichever tile center is closest.
+pub fn to_tile(position: &(i32, i32)) -> (i8, i8) {
+  return (
+    (position.0 + SUBTILE / 2).div_euclid(SUBTILE) as i8,
+    (position.1 + SUBTILE / 2).div_euclid(SUBTILE) as i8
+  );
+}
+
+// Returns the position at the exact center of a tile.
+pub fn from_tile(tile: &(i8, i8)) -> (i32, i32) {
+  return (tile.0 as i32 * SUBTILE, tile.1 as i32 * SUBTILE);
+}
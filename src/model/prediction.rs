@@ -0,0 +1,243 @@
+use crate::model::{
+  bomb::Flame,
+  event::WorldEvent,
+  input::{self, InputFrame},
+  world::World
+};
+use ndarray::{Array, Ix2, Ix3};
+
+/* A struct representing which tiles of a world are going to be lethal over the next few ticks, if
+ * nobody does anything.
+ *
+ * This is worked out by playing a snapshot of the world forward with nobody pressing anything, so
+ * it follows exactly the same rules the world does: flames spread, soft walls burn and bombs set
+ * each other off just like they would for real. What it can't know is what players are going to
+ * do, so it leaves out any bombs placed from here on, and remote bombs only go off if a blast
+ * reaches them.
+ *
+ * Tick 0 is the world as it is right now, and tick n is the world after n more ticks. Anyone on a
+ * tile that's lethal on tick n dies during that tick. Predictions stop early once there's nothing
+ * left that could go off by itself, and everything is safe after their last tick.
+ *
+ * Spans are the first and last tick each tile is lethal on, if it ever is. A tile isn't
+ * necessarily lethal on every tick in between, since it could be caught by two blasts that go off
+ * a while apart.
+ * Detonations are where each bomb goes off, along with the tick it goes off on.
+ *
+ * Tiles are stored row by row like a stage's, so a position of (x, y) on tick n lives at [n, y, x],
+ * and its span lives at [y, x].
+ */
+#[derive(Clone, PartialEq, Debug)]
+pub struct BlastPrediction {
+  lethal: Array<bool, Ix3>,
+  spans: Array<Option<(u32, u32)>, Ix2>,
+  detonations: Vec<((i8, i8), u32)>
+}
+
+// Returns whether a tile is on a stage of some dimensions.
+fn is_on_stage(tile: &(i8, i8), dimensions: &(i8, i8)) -> bool {
+  return tile.0 >= 0 && tile.1 >= 0 && tile.0 < dimensions.0 && tile.1 < dimensions.1;
+}
+
+/* Goes over every tile of a stage a flame covers, from its start to its end. A flame whose end
+ * can't be reached from its start (which only a corrupted state could have) stops at the edge of
+ * the stage rather than going on forever.
+ */
+fn cover_flame(flame: &dyn Flame, dimensions: &(i8, i8), cover: &mut dyn FnMut(&(i8, i8))) {
+  let mut tile: (i8, i8) = *flame.get_start();
+  while is_on_stage(&tile, dimensions) {
+    cover(&tile);
+    if &tile == flame.get_end() {
+      return;
+    }
+    tile = flame.get_direction().step(&tile);
+  }
+}
+
+/* Adds which tiles of a world are covered by a blast, row by row, to the end of a grid. Blasts
+ * cover their center and every tile from the start to the end of each of their flames.
+ */
+fn push_covered(world: &dyn World, lethal: &mut Vec<bool>) {
+  let dimensions: &(i8, i8) = world.get_stage().get_dimensions();
+  let offset: usize = lethal.len();
+  lethal.resize(offset + dimensions.0 as usize * dimensions.1 as usize, false);
+  let mut cover = |tile: &(i8, i8)| {
+    if is_on_stage(tile, dimensions) {
+      lethal[offset + tile.1 as usize * dimensions.0 as usize + tile.0 as usize] = true;
+    }
+  };
+  for blast in world.get_blasts() {
+    cover(blast.get_center());
+    for flame in blast.get_flames() {
+      cover_flame(flame.as_ref(), dimensions, &mut cover);
+    }
+  }
+}
+
+// Returns whether anything in a world could still go off without anyone pressing anything.
+fn is_settled(world: &dyn World) -> bool {
+  return world.get_blasts().is_empty() && world.get_bombs().iter().all(|bomb| *bomb.get_remote());
+}
+
+impl BlastPrediction {
+  // Predicts a world's blasts for up to a number of ticks from now.
+  pub fn new(world: &dyn World, horizon: u32) -> BlastPrediction {
+    let dimensions: &(i8, i8) = world.get_stage().get_dimensions();
+    let mut future: Box<dyn World> = world.snapshot();
+    let idle: InputFrame = input::idle_frame();
+    let mut lethal: Vec<bool> = vec!();
+    push_covered(future.as_ref(), &mut lethal);
+    let mut detonations: Vec<((i8, i8), u32)> = vec!();
+    let mut ticks: u32 = 0;
+    while ticks < horizon && !is_settled(future.as_ref()) {
+      ticks += 1;
+      for event in future.tick_mut(&idle) {
+        if let WorldEvent::BombDetonated { position, .. } = event {
+          detonations.push((position, ticks));
+        }
+      }
+      push_covered(future.as_ref(), &mut lethal);
+    }
+    let area: usize = dimensions.0 as usize * dimensions.1 as usize;
+    let mut spans: Vec<Option<(u32, u32)>> = vec!(None; area);
+    for (i, _) in lethal.iter().enumerate().filter(|(_, lethal)| **lethal) {
+      let tick: u32 = (i / area) as u32;
+      let span: &mut Option<(u32, u32)> = &mut spans[i % area];
+      *span = Some((span.map_or(tick, |(first, _)| first), tick));
+    }
+    let shape: (usize, usize) = (dimensions.1 as usize, dimensions.0 as usize);
+    return BlastPrediction {
+      lethal: Array::from_shape_vec((ticks as usize + 1, shape.0, shape.1), lethal)
+        .expect("Prediction has the wrong shape"),
+      spans: Array::from_shape_vec(shape, spans).expect("Prediction has the wrong shape"),
+      detonations: detonations
+    }
+  }
+
+  pub fn get_lethal(&self) -> &Array<bool, Ix3> {
+    return &self.lethal;
+  }
+
+  pub fn get_spans(&self) -> &Array<Option<(u32, u32)>, Ix2> {
+    return &self.spans;
+  }
+
+  pub fn get_detonations(&self) -> &Vec<((i8, i8), u32)> {
+    return &self.detonations;
+  }
+
+  // Returns the last tick this prediction covers.
+  pub fn get_last_tick(&self) -> u32 {
+    return self.lethal.shape()[0] as u32 - 1;
+  }
+
+  // Returns whether a tile is lethal on a tick. Tiles off the stage never are.
+  pub fn is_lethal(&self, tile: &(i8, i8), tick: u32) -> bool {
+    if tile.0 < 0 || tile.1 < 0 {
+      return false;
+    }
+    return self.lethal.get([tick as usize, tile.1 as usize, tile.0 as usize]).copied()
+      .unwrap_or(false);
+  }
+
+  // Returns every tick a tile is lethal on, in order.
+  pub fn lethal_ticks(&self, tile: &(i8, i8)) -> Vec<u32> {
+    return (0..=self.get_last_tick()).filter(|tick| self.is_lethal(tile, *tick)).collect();
+  }
+
+  // Returns the first and last tick a tile is lethal on, if it ever is.
+  pub fn get_span(&self, tile: &(i8, i8)) -> Option<(u32, u32)> {
+    if tile.0 < 0 || tile.1 < 0 {
+      return None;
+    }
+    return self.spans.get([tile.1 as usize, tile.0 as usize]).copied().flatten();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    common::direction::Direction,
+    model::{
+      bomb::{Bomb, BombImpl, FlameImpl, FlameState},
+      player::Player,
+      position,
+      rules::Rules,
+      stage::{StageImpl, Tile},
+      world::WorldImpl
+    }
+  };
+
+  fn covered(flame: &str) -> Vec<(i8, i8)> {
+    let state: FlameState = serde_json::from_str(flame).unwrap();
+    let mut tiles: Vec<(i8, i8)> = vec!();
+    cover_flame(&FlameImpl::from_state(&state), &(5, 5), &mut |tile| tiles.push(*tile));
+    return tiles;
+  }
+
+  #[test]
+  fn flames_cover_their_start_to_their_end() {
+    let flame: &str = r#"{"start":[1,2],"end":[3,2],"direction":"East","spread_range":0}"#;
+    assert_eq!(covered(flame), vec!((1, 2), (2, 2), (3, 2)));
+  }
+
+  #[test]
+  fn flames_that_never_reach_their_end_stop_at_the_edge() {
+    let behind: &str = r#"{"start":[2,2],"end":[0,2],"direction":"East","spread_range":0}"#;
+    assert_eq!(covered(behind), vec!((2, 2), (3, 2), (4, 2)));
+    let off_line: &str = r#"{"start":[2,2],"end":[3,3],"direction":"South","spread_range":0}"#;
+    assert_eq!(covered(off_line), vec!((2, 2), (2, 1), (2, 0)));
+    let off_stage: &str = r#"{"start":[9,2],"end":[9,4],"direction":"North","spread_range":2}"#;
+    assert_eq!(covered(off_stage), vec!());
+  }
+
+  #[test]
+  fn predictions_match_what_the_world_does() {
+    // The first bomb sets off the second, and one of the second's flames stops at a soft wall.
+    let layout: &str = "\
+........
+........
+......+.";
+    let rules: Rules = Rules::default();
+    let players: Vec<Player> = [(0, 2), (7, 2)].iter().enumerate()
+      .map(|(id, tile)| {
+        return Player::new(
+          id as u8, position::from_tile(tile), Direction::South, rules.get_starting_stats()
+        );
+      })
+      .collect();
+    let bombs: Vec<Box<dyn Bomb>> = vec!(
+      Box::new(BombImpl::new((1, 0), 0, 5, false, false, 3)),
+      Box::new(BombImpl::new((4, 0), 1, 200, false, false, 3))
+    );
+    let stage: StageImpl = StageImpl::from_layout(layout).unwrap();
+    let world: WorldImpl = WorldImpl::new(rules, Box::new(stage), players, bombs, vec!(), 0);
+    let prediction: BlastPrediction = BlastPrediction::new(&world, 200);
+
+    let mut future: WorldImpl = world.clone();
+    let mut detonations: Vec<((i8, i8), u32)> = vec!();
+    let dimensions: (i8, i8) = *world.get_stage().get_dimensions();
+    for tick in 0..=prediction.get_last_tick() {
+      if tick > 0 {
+        for event in future.tick_mut(&input::idle_frame()) {
+          if let WorldEvent::BombDetonated { position, .. } = event {
+            detonations.push((position, tick));
+          }
+        }
+      }
+      for tile in (0..dimensions.0).flat_map(|x| (0..dimensions.1).map(move |y| (x, y))) {
+        let covered: bool = future.get_blasts().iter().any(|blast| blast.covers(&tile));
+        assert_eq!(prediction.is_lethal(&tile, tick), covered, "{:?} on tick {}", tile, tick);
+      }
+    }
+    assert!(future.get_blasts().is_empty() && future.get_bombs().is_empty());
+    assert_eq!(prediction.get_detonations(), &detonations);
+    assert_eq!(detonations, vec!(((1, 0), 5), ((4, 0), 7)));
+    // The soft wall burns but stops the second bomb's flame, so nothing from there on is lethal.
+    assert!(prediction.get_span(&(5, 0)).is_some());
+    assert_eq!(prediction.get_span(&(6, 0)), None);
+    assert_eq!(prediction.get_span(&(7, 0)), None);
+    assert_eq!(future.get_stage().get_tile(&(6, 0)), Ok(Tile::Ground));
+  }
+}
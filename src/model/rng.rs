@@ -0,0 +1,43 @@
+/* A struct representing a random number generator (SplitMix64).
+ * Worlds carry their own rather than using anything from the platform, so that a round plays out
+ * exactly the same from the same seed and inputs, on every machine. It's tiny and fast, which
+ * matters more here than statistical quality.
+ *
+ * State is everything the generator needs to carry on from where it is.
+ */
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Rng {
+  state: u64
+}
+
+impl Rng {
+  pub fn new(seed: u64) -> Rng {
+    return Rng { state: seed };
+  }
+
+  pub fn next_u64(&mut self) -> u64 {
+    self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z: u64 = self.state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    return z ^ (z >> 31);
+  }
+
+  // Returns a number from 0 up to (but not including) a bound, which has to be above 0.
+  pub fn below(&mut self, bound: u32) -> u32 {
+    return (((self.next_u64() >> 32) * bound as u64) >> 32) as u32;
+  }
+
+  pub fn get_state(&self) -> &u64 {
+    return &self.state;
+  }
+}
+
+/* Returns a seed taken from the system clock, for frontends that want every round to play out
+ * differently. Nothing in the model calls this, since that would make it nondeterministic.
+ */
+pub fn clock_seed() -> u64 {
+  return std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)
+    .map(|elapsed| elapsed.as_nanos() as u64)
+    .unwrap_or(0);
+}
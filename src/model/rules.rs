@@ -0,0 +1,375 @@
+use crate::{
+  common::config,
+  model::{item::MAX_SPEED, player::Abilities, timestep::TICK_RATE}
+};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/* A struct representing the stats every player starts a round with.
+ *
+ * Speed is how far a player moves in a single frame, in sub-tile units (see the position module).
+ * Bombs is how many bombs a player can have on the field at once.
+ * Range and piercing are passed on to every bomb the player places; see BombImpl.
+ * Abilities are what the player can do beyond placing bombs; see Abilities.
+ */
+#[derive(Copy, Clone, PartialEq, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct StartingStats {
+  speed: i32,
+  bombs: u8,
+  range: i8,
+  piercing: bool,
+  abilities: Abilities
+}
+
+impl Default for StartingStats {
+  fn default() -> StartingStats {
+    return StartingStats {
+      speed: 26,
+      bombs: 1,
+      range: 2,
+      piercing: false,
+      abilities: Abilities::default()
+    }
+  }
+}
+
+impl StartingStats {
+  pub fn get_speed(&self) -> &i32 {
+    return &self.speed;
+  }
+
+  pub fn get_bombs(&self) -> &u8 {
+    return &self.bombs;
+  }
+
+  pub fn get_range(&self) -> &i8 {
+    return &self.range;
+  }
+
+  pub fn get_piercing(&self) -> &bool {
+    return &self.piercing;
+  }
+
+  pub fn get_abilities(&self) -> &Abilities {
+    return &self.abilities;
+  }
+
+  pub fn set_speed(&self, speed: i32) -> StartingStats {
+    return StartingStats {
+      speed: speed,
+      ..*self
+    }
+  }
+
+  pub fn set_bombs(&self, bombs: u8) -> StartingStats {
+    return StartingStats {
+      bombs: bombs,
+      ..*self
+    }
+  }
+
+  pub fn set_range(&self, range: i8) -> StartingStats {
+    return StartingStats {
+      range: range,
+      ..*self
+    }
+  }
+
+  pub fn set_piercing(&self, piercing: bool) -> StartingStats {
+    return StartingStats {
+      piercing: piercing,
+      ..*self
+    }
+  }
+
+  pub fn set_abilities(&self, abilities: Abilities) -> StartingStats {
+    return StartingStats {
+      abilities: abilities,
+      ..*self
+    }
+  }
+}
+
+/* A struct representing how likely soft walls are to leave items behind.
+ *
+ * Chance is the percentage (0 to 100) of destroyed soft walls that drop an item at all. The rest of
+ * the fields are relative weights deciding which item gets dropped, so they don't need to add up to
+ * anything in particular. A weight of 0 means that item never drops.
+ */
+#[derive(Copy, Clone, PartialEq, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ItemDropRates {
+  chance: u8,
+  bomb_up: u8,
+  fire_up: u8,
+  speed_up: u8,
+  pierce_bomb: u8,
+  remote_control: u8,
+  line_bomb: u8
+}
+
+impl Default for ItemDropRates {
+  fn default() -> ItemDropRates {
+    return ItemDropRates {
+      chance: 30,
+      bomb_up: 4,
+      fire_up: 4,
+      speed_up: 3,
+      pierce_bomb: 1,
+      remote_control: 1,
+      line_bomb: 1
+    }
+  }
+}
+
+impl ItemDropRates {
+  pub fn get_chance(&self) -> &u8 {
+    return &self.chance;
+  }
+
+  pub fn get_bomb_up(&self) -> &u8 {
+    return &self.bomb_up;
+  }
+
+  pub fn get_fire_up(&self) -> &u8 {
+    return &self.fire_up;
+  }
+
+  pub fn get_speed_up(&self) -> &u8 {
+    return &self.speed_up;
+  }
+
+  pub fn get_pierce_bomb(&self) -> &u8 {
+    return &self.pierce_bomb;
+  }
+
+  pub fn get_remote_control(&self) -> &u8 {
+    return &self.remote_control;
+  }
+
+  pub fn get_line_bomb(&self) -> &u8 {
+    return &self.line_bomb;
+  }
+}
+
+/* The longest a round can last, in frames. Nobody plays for this long, but anything recorded from a
+ * round (like a replay) can be held to it.
+ */
+pub const MAX_ROUND_TIME: u32 = 60 * 60 * TICK_RATE;
+
+/* A struct representing the rule set a world is played under.
+ * Different leagues play with different numbers, so none of these should be hard-coded anywhere
+ * else in the model. Every duration is measured in frames, just like bomb and blast lifetimes.
+ *
+ * Bomb fuse is the lifetime a bomb is placed with.
+ * Blast lifetime is how long a blast lingers once it has finished spreading.
+ * Flame spread rate is how many frames it takes a flame to spread by one tile.
+ * Round time is how long a round lasts before it ends in a draw.
+ * Sudden death time is how many frames should be left on the clock when sudden death starts.
+ * Sudden death doesn't change how the round plays. It only warns everyone that the round is about
+ * to end, through WorldEvent::SuddenDeath and the clock.
+ *
+ * Any field missing from a rules file falls back to its default, so a file only has to mention what
+ * it changes.
+ */
+#[derive(Copy, Clone, PartialEq, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Rules {
+  bomb_fuse: i16,
+  blast_lifetime: i8,
+  flame_spread_rate: i8,
+  starting_stats: StartingStats,
+  item_drop_rates: ItemDropRates,
+  round_time: u32,
+  sudden_death_time: u32
+}
+
+impl Default for Rules {
+  fn default() -> Rules {
+    return Rules {
+      bomb_fuse: 300,
+      blast_lifetime: 60,
+      flame_spread_rate: 1,
+      starting_stats: StartingStats::default(),
+      item_drop_rates: ItemDropRates::default(),
+      round_time: 10800,
+      sudden_death_time: 3600
+    }
+  }
+}
+
+impl Rules {
+  // Loads a rule set from a TOML or RON file; see config.
+  pub fn from_file(path: &Path) -> Result<Rules, String> {
+    return config::load::<Rules>(path, "rules").and_then(Rules::validate);
+  }
+
+  pub fn from_toml(contents: &str) -> Result<Rules, String> {
+    return config::from_toml::<Rules>(contents, "rules").and_then(Rules::validate);
+  }
+
+  pub fn from_ron(contents: &str) -> Result<Rules, String> {
+    return config::from_ron::<Rules>(contents, "rules").and_then(Rules::validate);
+  }
+
+  // Rejects rule sets that would leave the world in a state it can't progress from.
+  pub fn validate(self) -> Result<Rules, String> {
+    if self.bomb_fuse <= 0 {
+      return Err(String::from("Bomb fuse must be at least 1 frame."));
+    } else if self.blast_lifetime <= 0 {
+      return Err(String::from("Blast lifetime must be at least 1 frame."));
+    } else if self.flame_spread_rate <= 0 {
+      return Err(String::from("Flame spread rate must be at least 1 frame per tile."));
+    } else if !(1..=MAX_SPEED).contains(&self.starting_stats.speed) {
+      // Players any faster could move more than a tile at once, and skip right over walls.
+      return Err(format!("Starting speed must be between 1 and {}.", MAX_SPEED));
+    } else if self.starting_stats.bombs < 1 {
+      return Err(String::from("Players must start with at least 1 bomb."));
+    } else if self.starting_stats.range < 1 {
+      return Err(String::from("Starting range must be at least 1 tile."));
+    } else if self.item_drop_rates.chance > 100 {
+      return Err(String::from("Item drop chance can't be above 100 percent."));
+    } else if self.round_time > MAX_ROUND_TIME {
+      return Err(format!("Rounds can't last longer than {} frames.", MAX_ROUND_TIME));
+    } else if self.sudden_death_time > self.round_time {
+      return Err(String::from("Sudden death can't start before the round does."));
+    } else {
+      return Ok(self);
+    }
+  }
+
+  pub fn get_bomb_fuse(&self) -> &i16 {
+    return &self.bomb_fuse;
+  }
+
+  pub fn get_blast_lifetime(&self) -> &i8 {
+    return &self.blast_lifetime;
+  }
+
+  pub fn get_flame_spread_rate(&self) -> &i8 {
+    return &self.flame_spread_rate;
+  }
+
+  pub fn get_starting_stats(&self) -> &StartingStats {
+    return &self.starting_stats;
+  }
+
+  pub fn get_item_drop_rates(&self) -> &ItemDropRates {
+    return &self.item_drop_rates;
+  }
+
+  pub fn get_round_time(&self) -> &u32 {
+    return &self.round_time;
+  }
+
+  pub fn get_sudden_death_time(&self) -> &u32 {
+    return &self.sudden_death_time;
+  }
+
+  /* The setters don't validate anything, so whatever calls them (like the rules editor) has to keep
+   * the values in range itself.
+   */
+  pub fn set_bomb_fuse(&self, bomb_fuse: i16) -> Rules {
+    return Rules {
+      bomb_fuse: bomb_fuse,
+      ..*self
+    }
+  }
+
+  pub fn set_blast_lifetime(&self, blast_lifetime: i8) -> Rules {
+    return Rules {
+      blast_lifetime: blast_lifetime,
+      ..*self
+    }
+  }
+
+  pub fn set_flame_spread_rate(&self, flame_spread_rate: i8) -> Rules {
+    return Rules {
+      flame_spread_rate: flame_spread_rate,
+      ..*self
+    }
+  }
+
+  pub fn set_starting_stats(&self, starting_stats: StartingStats) -> Rules {
+    return Rules {
+      starting_stats: starting_stats,
+      ..*self
+    }
+  }
+
+  pub fn set_round_time(&self, round_time: u32) -> Rules {
+    return Rules {
+      round_time: round_time,
+      ..*self
+    }
+  }
+
+  pub fn set_sudden_death_time(&self, sudden_death_time: u32) -> Rules {
+    return Rules {
+      sudden_death_time: sudden_death_time,
+      ..*self
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn default_rules_are_valid() {
+    assert_eq!(Rules::default().validate(), Ok(Rules::default()));
+  }
+
+  #[test]
+  fn starting_stats_players_cant_play_with_are_rejected() {
+    let rules: Rules = Rules::default();
+    let stats: StartingStats = *rules.get_starting_stats();
+    for speed in [-4, 0, MAX_SPEED + 1] {
+      assert!(rules.set_starting_stats(stats.set_speed(speed)).validate().is_err());
+    }
+    assert!(rules.set_starting_stats(stats.set_speed(MAX_SPEED)).validate().is_ok());
+    assert!(rules.set_starting_stats(stats.set_bombs(0)).validate().is_err());
+    assert!(rules.set_starting_stats(stats.set_range(0)).validate().is_err());
+    assert!(rules.set_starting_stats(stats.set_range(-1)).validate().is_err());
+  }
+
+  #[test]
+  fn rule_sets_that_cant_progress_are_rejected() {
+    let rules: Rules = Rules::default();
+    assert!(rules.set_bomb_fuse(0).validate().is_err());
+    assert!(rules.set_blast_lifetime(0).validate().is_err());
+    assert!(rules.set_flame_spread_rate(0).validate().is_err());
+    assert!(rules.set_round_time(60).set_sudden_death_time(61).validate().is_err());
+    assert!(rules.set_round_time(MAX_ROUND_TIME + 1).validate().is_err());
+  }
+
+  #[test]
+  fn rules_load_from_toml() {
+    let contents: &str = "bomb_fuse = 120\n\n[starting_stats]\nspeed = 30\nrange = 3\n";
+    let rules: Rules = Rules::from_toml(contents).unwrap();
+    assert_eq!(*rules.get_bomb_fuse(), 120);
+    assert_eq!(*rules.get_starting_stats().get_speed(), 30);
+    assert_eq!(*rules.get_starting_stats().get_range(), 3);
+    assert_eq!(rules.get_blast_lifetime(), Rules::default().get_blast_lifetime());
+  }
+
+  #[test]
+  fn rules_load_from_ron() {
+    let contents: &str = "(blast_lifetime: 30, starting_stats: (bombs: 2, piercing: true))";
+    let rules: Rules = Rules::from_ron(contents).unwrap();
+    assert_eq!(*rules.get_blast_lifetime(), 30);
+    assert_eq!(*rules.get_starting_stats().get_bombs(), 2);
+    assert!(*rules.get_starting_stats().get_piercing());
+    assert_eq!(Rules::from_ron(&ron::ser::to_string(&rules).unwrap()), Ok(rules));
+  }
+
+  #[test]
+  fn invalid_files_are_rejected() {
+    assert!(Rules::from_toml("bomb_fuse = \"soon\"").is_err());
+    assert!(Rules::from_toml("[starting_stats]\nspeed = 300\n").is_err());
+    assert!(Rules::from_ron("(flame_spread_rate: 0)").is_err());
+    assert!(Rules::from_ron("(starting_stats: (range: 0))").is_err());
+  }
+}
@@ -0,0 +1,44 @@
+use crate::model::{
+  input::{PlayerId, MAX_PLAYERS},
+  world::Outcome
+};
+
+/* A struct representing the tally of a match, which is however many rounds get played in a row.
+ * Worlds only ever know about the round they're playing, so this is kept by whatever starts them.
+ *
+ * Wins are indexed by player ID.
+ * Draws and rounds should be obvious.
+ */
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Scoreboard {
+  wins: [u32; MAX_PLAYERS],
+  draws: u32,
+  rounds: u32
+}
+
+impl Scoreboard {
+  pub fn new() -> Scoreboard {
+    return Scoreboard::default();
+  }
+
+  // Adds a round's outcome to the tally.
+  pub fn record(&mut self, outcome: &Outcome) {
+    match outcome {
+      Outcome::Win(id) => self.wins[*id as usize % MAX_PLAYERS] += 1,
+      Outcome::Draw => self.draws += 1
+    }
+    self.rounds += 1;
+  }
+
+  pub fn get_wins(&self, id: &PlayerId) -> &u32 {
+    return &self.wins[*id as usize % MAX_PLAYERS];
+  }
+
+  pub fn get_draws(&self) -> &u32 {
+    return &self.draws;
+  }
+
+  pub fn get_rounds(&self) -> &u32 {
+    return &self.rounds;
+  }
+}
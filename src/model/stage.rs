@@ -1,7 +1,8 @@
 use ndarray::{Array, Ix2};
+use serde::{Deserialize, Serialize};
 use std::convert::TryFrom;
 
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub enum Tile {
   Ground, SoftWall, HardWall
 }
@@ -14,48 +15,242 @@ impl Tile {
       Tile::HardWall => return true
     }
   }
-}
 
-pub fn get_tile(tiles: &Array<Tile, Ix2>, position: &(i8, i8)) -> Tile {
-  let usize_position: (usize, usize) =
-    (usize::try_from(position.0).unwrap(), usize::try_from(position.1).unwrap());
-  return tiles[[usize_position.1, usize_position.0]].clone();
+  // Returns the character a tile is written as in stage layouts.
+  pub fn to_char(&self) -> char {
+    match self {
+      Tile::Ground => return '.',
+      Tile::SoftWall => return '+',
+      Tile::HardWall => return '#'
+    }
+  }
+
+  pub fn from_char(character: char) -> Option<Tile> {
+    match character {
+      '.' => return Some(Tile::Ground),
+      '+' => return Some(Tile::SoftWall),
+      '#' => return Some(Tile::HardWall),
+      _ => return None
+    }
+  }
 }
 
 /* A struct representing a stage.
  * Stages should only be 15 by 9 at max.
+ *
+ * Tiles are stored row by row, so a position of (x, y) lives at [y, x] in the array.
+ * Spawns are where each player starts, indexed by player ID.
  */
-pub trait Stage {
+pub trait Stage: Send {
 	fn copy(&self) -> Box<dyn Stage>;
 
+  fn get_dimensions(&self) -> &(i8, i8);
+
+  fn get_spawns(&self) -> &Vec<(i8, i8)>;
+
   fn get_tiles(&self) -> &Array<Tile, Ix2>;
 
   fn get_tile(&self, position: &(i8, i8)) -> Result<Tile, &str>;
 
-  fn set_tile(&self, position: &(i8, i8), tile: Tile) -> Box<dyn Stage>;
+  // Sets a tile in place, doing nothing if the position is out of bounds.
+  fn set_tile(&mut self, position: &(i8, i8), tile: Tile);
+
+  fn to_state(&self) -> StageState;
 }
 
 pub struct StageImpl {
   dimensions: (i8, i8),
-  tiles: Array<Tile, Ix2>
+  tiles: Array<Tile, Ix2>,
+  spawns: Vec<(i8, i8)>
+}
+
+/* A struct representing everything about a stage as plain data, which can be serialized; see
+ * WorldState.
+ * Rows are the stage's tiles from the top down, written the way layouts write them. Spawns are kept
+ * apart from them, since stages built in code can put them anywhere.
+ */
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct StageState {
+  rows: Vec<String>,
+  spawns: Vec<(i8, i8)>
+}
+
+// The largest a stage can be, in tiles.
+pub const MAX_DIMENSIONS: (i8, i8) = (15, 9);
+
+/* The classic stage: pillars in every other tile, soft walls everywhere else, and just enough room
+ * around each spawn for players to get out of the way of their first bomb.
+ */
+pub const CLASSIC_LAYOUT: &str = "\
+0.++++.4.++++.1
+.#+#+#+#+#+#+#.
++++++++++++++++
++#+#+#+#+#+#+#+
+5.+++++++++++.6
++#+#+#+#+#+#+#+
++++++++++++++++
+.#+#+#+#+#+#+#.
+2.++++.7.++++.3";
+
+// An open stage with only a few soft walls, for rounds that get straight to the fighting.
+pub const ARENA_LAYOUT: &str = "\
+0....+.4.+....1
+.#.#.#.#.#.#.#.
+..+.+.....+.+..
+.#+#.#.#.#.#+#.
+5.+.........+.6
+.#+#.#.#.#.#+#.
+..+.+.....+.+..
+.#.#.#.#.#.#.#.
+2....+.7.+....3";
+
+// The classic stage without its pillars, so there's nothing but soft walls to blast through.
+pub const RUSH_LAYOUT: &str = "\
+0.++++.4.++++.1
+.+++++++++++++.
++++++++++++++++
++++++++++++++++
+5.+++++++++++.6
++++++++++++++++
++++++++++++++++
+.+++++++++++++.
+2.++++.7.++++.3";
+
+// The stages that ship with the game, by name.
+pub const STAGES: [(&str, &str); 3] =
+  [("Classic", CLASSIC_LAYOUT), ("Arena", ARENA_LAYOUT), ("Rush", RUSH_LAYOUT)];
+
+/* Writes a stage out as a text layout, the way StageImpl::from_layout reads them, so building a
+ * stage from it gives the same stage back. Layouts only have digits for the first 8 spawns, so any
+ * after those are left out.
+ */
+pub fn to_layout(stage: &dyn Stage) -> String {
+  let dimensions: (i8, i8) = *stage.get_dimensions();
+  let mut rows: Vec<String> = vec!();
+  for y in (0..dimensions.1).rev() {
+    let row: String = (0..dimensions.0)
+      .map(|x| {
+        let spawn: Option<usize> =
+          stage.get_spawns().iter().take(8).position(|spawn| spawn == &(x, y));
+        match spawn {
+          Some(id) => return std::char::from_digit(id as u32, 10).unwrap(),
+          None => return stage.get_tile(&(x, y)).unwrap_or(Tile::Ground).to_char()
+        }
+      })
+      .collect();
+    rows.push(row);
+  }
+  return rows.join("\n");
+}
+
+impl StageState {
+  pub fn get_rows(&self) -> &Vec<String> {
+    return &self.rows;
+  }
+
+  pub fn get_spawns(&self) -> &Vec<(i8, i8)> {
+    return &self.spawns;
+  }
 }
 
 fn get_dimensions(tiles: &Array<Tile, Ix2>) -> (i8, i8) {
   let shape: &[usize] = tiles.shape();
-  return (i8::try_from(shape[0]).unwrap(), i8::try_from(shape[1]).unwrap());
+  return (i8::try_from(shape[1]).unwrap(), i8::try_from(shape[0]).unwrap());
 }
 
 impl StageImpl {
-  fn new(tiles: Array<Tile, Ix2>) -> StageImpl {
+  pub fn new(tiles: Array<Tile, Ix2>, spawns: Vec<(i8, i8)>) -> StageImpl {
     return StageImpl {
       dimensions: get_dimensions(&tiles),
-      tiles: tiles
+      tiles: tiles,
+      spawns: spawns
     }
   }
 
+  /* Builds a stage from a text layout, where each line is a row of tiles from the top of the stage
+   * down. A '.' is ground, a '+' is a soft wall and a '#' is a hard wall, while the digits 0 to 7
+   * are ground that the player with that ID spawns on.
+   */
+  pub fn from_layout(layout: &str) -> Result<StageImpl, String> {
+    let rows: Vec<&str> = layout.lines().map(|row| row.trim()).filter(|row| !row.is_empty())
+      .collect();
+    let height: usize = rows.len();
+    let width: usize = rows.first().map(|row| row.chars().count()).unwrap_or(0);
+    if width == 0 || height == 0 {
+      return Err(String::from("Stage layout is empty."));
+    } else if width > MAX_DIMENSIONS.0 as usize || height > MAX_DIMENSIONS.1 as usize {
+      return Err(format!("Stage layout is {} by {}, which is bigger than 15 by 9.", width, height));
+    }
+
+    let mut tiles: Array<Tile, Ix2> = Array::from_elem((height, width), Tile::Ground);
+    let mut spawns: Vec<Option<(i8, i8)>> = vec!();
+    for (row_index, row) in rows.iter().enumerate() {
+      if row.chars().count() != width {
+        return Err(format!("Row {} of the stage layout isn't {} tiles wide.", row_index, width));
+      }
+      // The first row is the top of the stage, which is the highest y.
+      let y: usize = height - 1 - row_index;
+      for (x, character) in row.chars().enumerate() {
+        tiles[[y, x]] = match character {
+          '0'..='7' => {
+            let id: usize = character.to_digit(10).unwrap() as usize;
+            if spawns.len() <= id {
+              spawns.resize(id + 1, None);
+            }
+            spawns[id] = Some((x as i8, y as i8));
+            Tile::Ground
+          },
+          _ => {
+            Tile::from_char(character)
+              .ok_or_else(|| format!("'{}' isn't a tile in stage layouts.", character))?
+          }
+        };
+      }
+    }
+
+    let mut ordered_spawns: Vec<(i8, i8)> = vec!();
+    for (id, spawn) in spawns.iter().enumerate() {
+      match spawn {
+        Some(spawn) => ordered_spawns.push(*spawn),
+        None => return Err(format!("Stage layout is missing a spawn for player {}.", id))
+      }
+    }
+    return Ok(StageImpl::new(tiles, ordered_spawns));
+  }
+
+  // Rebuilds a stage from its state, checking that it makes sense first.
+  pub fn from_state(state: &StageState) -> Result<StageImpl, String> {
+    let height: usize = state.rows.len();
+    let width: usize = state.rows.first().map(|row| row.chars().count()).unwrap_or(0);
+    if width == 0 || height == 0 {
+      return Err(String::from("Stage is empty."));
+    } else if width > MAX_DIMENSIONS.0 as usize || height > MAX_DIMENSIONS.1 as usize {
+      return Err(format!("Stage is {} by {}, which is bigger than 15 by 9.", width, height));
+    }
+    let mut tiles: Array<Tile, Ix2> = Array::from_elem((height, width), Tile::Ground);
+    for (row_index, row) in state.rows.iter().enumerate() {
+      if row.chars().count() != width {
+        return Err(format!("Row {} of the stage isn't {} tiles wide.", row_index, width));
+      }
+      for (x, character) in row.chars().enumerate() {
+        tiles[[height - 1 - row_index, x]] = Tile::from_char(character)
+          .ok_or_else(|| format!("'{}' isn't a tile.", character))?;
+      }
+    }
+    let stage: StageImpl = StageImpl::new(tiles, state.spawns.clone());
+    if let Some(spawn) = stage.spawns.iter().find(|spawn| stage.out_of_bounds(spawn)) {
+      return Err(format!("Spawn {:?} is off the stage.", spawn));
+    }
+    return Ok(stage);
+  }
+
+  pub fn classic() -> StageImpl {
+    return StageImpl::from_layout(CLASSIC_LAYOUT).unwrap();
+  }
+
   fn out_of_bounds(&self, position: &(i8, i8)) -> bool {
-    return position.0 >= 0 && position.0 <= self.dimensions.0 - 1
-      && position.1 >= 0 && position.1 <= self.dimensions.1 - 1;
+    return position.0 < 0 || position.0 > self.dimensions.0 - 1
+      || position.1 < 0 || position.1 > self.dimensions.1 - 1;
   }
 
   fn get_usize_position(&self, position: &(i8, i8)) -> (usize, usize) {
@@ -68,11 +263,20 @@ impl Stage for StageImpl {
 		return Box::new(
 			StageImpl {
 				dimensions: self.dimensions,
-				tiles: self.tiles.clone()
+				tiles: self.tiles.clone(),
+				spawns: self.spawns.clone()
 			}
 		)
 	}
 
+  fn get_dimensions(&self) -> &(i8, i8) {
+    return &self.dimensions;
+  }
+
+  fn get_spawns(&self) -> &Vec<(i8, i8)> {
+    return &self.spawns;
+  }
+
   fn get_tiles(&self) -> &Array<Tile, Ix2> {
     return &self.tiles;
   }
@@ -86,16 +290,20 @@ impl Stage for StageImpl {
     }
   }
 
-  fn set_tile(&self, position: &(i8, i8), tile: Tile) -> Box<dyn Stage> {
-    let usize_position: (usize, usize) = self.get_usize_position(position);
-    let mut new_tiles: Array<Tile, Ix2> = self.tiles.clone();
-    new_tiles[[usize_position.1, usize_position.0]] = tile;
+  fn set_tile(&mut self, position: &(i8, i8), tile: Tile) {
+    if !self.out_of_bounds(position) {
+      let usize_position: (usize, usize) = self.get_usize_position(position);
+      self.tiles[[usize_position.1, usize_position.0]] = tile;
+    }
+  }
 
-    return Box::new(
-      StageImpl {
-        dimensions: self.dimensions,
-        tiles: new_tiles
-      }
-    );
+  fn to_state(&self) -> StageState {
+    let rows: Vec<String> = self.tiles.outer_iter().rev()
+      .map(|row| row.iter().map(Tile::to_char).collect())
+      .collect();
+    return StageState {
+      rows: rows,
+      spawns: self.spawns.clone()
+    }
   }
 }
@@ -0,0 +1,609 @@
+use crate::model::{
+  bomb::{BlastState, BombState, FlameState},
+  checksum,
+  item::Item,
+  player::Player,
+  rules::Rules,
+  stage::StageState,
+  world::{World, WorldImpl}
+};
+use bincode::Options;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Debug, fs, path::Path};
+
+// What the binary form starts with, so that anything else gets turned away up front.
+const MAGIC: &[u8; 4] = b"OBST";
+
+/* The version of the binary form this build reads and writes. It has to go up whenever anything in
+ * a world state changes shape, since the binary form has no field names to go by.
+ */
+pub const STATE_VERSION: u16 = 1;
+
+/* The most bytes the bincode in a binary state can take up, so that a broken or hostile file can't
+ * make loading it allocate without end. A bomb and a blast on every tile of the biggest stage there
+ * can be still comes to well under this.
+ */
+const MAX_STATE_SIZE: u64 = 16 << 20;
+
+// Returns how the binary form is written, which is bincode's usual encoding, up to a limit.
+fn encoding() -> impl Options {
+  return bincode::options()
+    .with_fixint_encoding()
+    .allow_trailing_bytes()
+    .with_limit(MAX_STATE_SIZE);
+}
+
+/* A struct representing everything a world is, as plain data that can be serialized. Worlds hold
+ * their stage, bombs and blasts as trait objects, which can't be, so saving a world means capturing
+ * it into one of these, and loading it means rebuilding it with WorldImpl::from_state.
+ *
+ * Rng is the state of the world's random number generator, so that a loaded world carries on
+ * dropping the same items. Everything else is as it is in the world.
+ *
+ * There are two forms. JSON is stable and readable, for debugging. The binary form is compact, for
+ * snapshots, and starts with the version it was written with so that states from any other version
+ * get turned away rather than misread. After that, it's the state as bincode.
+ */
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct WorldState {
+  rules: Rules,
+  stage: StageState,
+  players: Vec<Player>,
+  bombs: Vec<BombState>,
+  blasts: Vec<BlastState>,
+  items: Vec<((i8, i8), Item)>,
+  rng: u64,
+  time: u32
+}
+
+impl WorldState {
+  pub fn capture(world: &dyn World) -> WorldState {
+    return WorldState {
+      rules: *world.get_rules(),
+      stage: world.get_stage().to_state(),
+      players: world.get_players().clone(),
+      bombs: world.get_bombs().iter().map(|bomb| bomb.to_state()).collect(),
+      blasts: world.get_blasts().iter().map(|blast| blast.to_state()).collect(),
+      items: world.get_items().clone(),
+      rng: *world.get_rng().get_state(),
+      time: *world.get_time()
+    }
+  }
+
+  pub fn to_json(&self) -> Result<String, String> {
+    return serde_json::to_string_pretty(self).map_err(|e| format!("Couldn't write state: {}", e));
+  }
+
+  pub fn from_json(json: &str) -> Result<WorldState, String> {
+    return serde_json::from_str(json).map_err(|e| format!("Invalid JSON state: {}", e));
+  }
+
+  pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+    let mut bytes: Vec<u8> = MAGIC.to_vec();
+    bytes.extend_from_slice(&STATE_VERSION.to_le_bytes());
+    encoding().serialize_into(&mut bytes, self)
+      .map_err(|e| format!("Couldn't write state: {}", e))?;
+    return Ok(bytes);
+  }
+
+  pub fn from_bytes(bytes: &[u8]) -> Result<WorldState, String> {
+    let header: usize = MAGIC.len() + 2;
+    if bytes.len() < header || &bytes[..MAGIC.len()] != MAGIC {
+      return Err(String::from("That isn't a world state."));
+    }
+    let version: u16 = u16::from_le_bytes([bytes[MAGIC.len()], bytes[MAGIC.len() + 1]]);
+    if version != STATE_VERSION {
+      return Err(
+        format!("State is version {}, but only version {} can be loaded.", version, STATE_VERSION)
+      );
+    }
+    let mut body: &[u8] = &bytes[header..];
+    let state: WorldState = encoding().deserialize_from(&mut body)
+      .map_err(|e| {
+        match *e {
+          bincode::ErrorKind::Io(_) => return String::from("State ends too early."),
+          bincode::ErrorKind::SizeLimit => return String::from("State is too big."),
+          e => return format!("Invalid state: {}", e)
+        }
+      })?;
+    if !body.is_empty() {
+      return Err(String::from("State has something after its end."));
+    }
+    return Ok(state);
+  }
+
+  /* Loads a state from a file, picking the form from its extension.
+   * JSON (.json) and binary (.state) are supported.
+   */
+  pub fn load(path: &Path) -> Result<WorldState, String> {
+    let bytes: Vec<u8> = fs::read(path)
+      .map_err(|e| format!("Couldn't read state from {}: {}", path.display(), e))?;
+    match path.extension().and_then(|extension| extension.to_str()) {
+      Some("json") => {
+        let json: String = String::from_utf8(bytes)
+          .map_err(|_| String::from("Invalid JSON state: it isn't UTF-8."))?;
+        return WorldState::from_json(&json);
+      },
+      Some("state") => return WorldState::from_bytes(&bytes),
+      _ => return Err(format!("{} is neither a .json nor a .state file.", path.display()))
+    }
+  }
+
+  pub fn save(&self, path: &Path) -> Result<(), String> {
+    let bytes: Vec<u8> = match path.extension().and_then(|extension| extension.to_str()) {
+      Some("json") => self.to_json()?.into_bytes(),
+      Some("state") => self.to_bytes()?,
+      _ => return Err(format!("{} is neither a .json nor a .state file.", path.display()))
+    };
+    return fs::write(path, bytes)
+      .map_err(|e| format!("Couldn't save state to {}: {}", path.display(), e));
+  }
+
+  pub fn get_rules(&self) -> &Rules {
+    return &self.rules;
+  }
+
+  pub fn get_stage(&self) -> &StageState {
+    return &self.stage;
+  }
+
+  pub fn get_players(&self) -> &Vec<Player> {
+    return &self.players;
+  }
+
+  pub fn get_bombs(&self) -> &Vec<BombState> {
+    return &self.bombs;
+  }
+
+  pub fn get_blasts(&self) -> &Vec<BlastState> {
+    return &self.blasts;
+  }
+
+  pub fn get_items(&self) -> &Vec<((i8, i8), Item)> {
+    return &self.items;
+  }
+
+  pub fn get_rng(&self) -> &u64 {
+    return &self.rng;
+  }
+
+  pub fn get_time(&self) -> &u32 {
+    return &self.time;
+  }
+}
+
+/* Checks that a world comes back exactly as it was after going through either form, returning the
+ * world rebuilt from the binary form so that callers can check it carries on the same way too.
+ */
+pub fn check_round_trip(world: &dyn World) -> Result<WorldImpl, String> {
+  let state: WorldState = WorldState::capture(world);
+  let from_json: WorldState = WorldState::from_json(&state.to_json()?)?;
+  if from_json != state {
+    return Err(format!("State at tick {} changed going through JSON.", state.time));
+  }
+  let from_bytes: WorldState = WorldState::from_bytes(&state.to_bytes()?)?;
+  if from_bytes != state {
+    return Err(format!("State at tick {} changed going through bytes.", state.time));
+  }
+  let restored: WorldImpl = WorldImpl::from_state(&from_bytes)?;
+  if let Some(difference) = diff(&state, &WorldState::capture(&restored)) {
+    return Err(
+      format!(
+        "World at tick {} changed being rebuilt from its state, at {}", state.time, difference
+      )
+    );
+  }
+  if checksum::checksum(&restored) != checksum::checksum(world) {
+    return Err(format!("World at tick {} has a different checksum once rebuilt.", state.time));
+  }
+  return Ok(restored);
+}
+
+// Describes a field if it's different in two states, for diff.
+fn compare<T: PartialEq + Debug>(field: &str, a: &T, b: &T) -> Option<String> {
+  if a == b {
+    return None;
+  }
+  return Some(format!("{}: {:?} vs {:?}", field, a, b));
+}
+
+// Finds the first difference between two lists, going by their lengths and then item by item.
+fn compare_lists<T>(
+  field: &str, a: &[T], b: &[T], compare_items: fn(&str, &T, &T) -> Option<String>
+) -> Option<String> {
+  let lengths: Option<String> = compare(&format!("{}.len", field), &a.len(), &b.len());
+  if lengths.is_some() {
+    return lengths;
+  }
+  return a.iter().zip(b.iter()).enumerate()
+    .find_map(|(index, (a_item, b_item))| {
+      return compare_items(&format!("{}[{}]", field, index), a_item, b_item);
+    });
+}
+
+fn compare_rules(field: &str, a: &Rules, b: &Rules) -> Option<String> {
+  return compare(&format!("{}.bomb_fuse", field), a.get_bomb_fuse(), b.get_bomb_fuse())
+    .or_else(|| {
+      compare(&format!("{}.blast_lifetime", field), a.get_blast_lifetime(), b.get_blast_lifetime())
+    })
+    .or_else(|| {
+      compare(
+        &format!("{}.flame_spread_rate", field),
+        a.get_flame_spread_rate(), b.get_flame_spread_rate()
+      )
+    })
+    .or_else(|| {
+      compare(&format!("{}.starting_stats", field), a.get_starting_stats(), b.get_starting_stats())
+    })
+    .or_else(|| {
+      compare(
+        &format!("{}.item_drop_rates", field), a.get_item_drop_rates(), b.get_item_drop_rates()
+      )
+    })
+    .or_else(|| compare(&format!("{}.round_time", field), a.get_round_time(), b.get_round_time()))
+    .or_else(|| {
+      compare(
+        &format!("{}.sudden_death_time", field),
+        a.get_sudden_death_time(), b.get_sudden_death_time()
+      )
+    });
+}
+
+// Stages are gone through from the bottom row up, the way checksums go through them.
+fn compare_stages(field: &str, a: &StageState, b: &StageState) -> Option<String> {
+  let rows: Option<String> =
+    compare(&format!("{}.rows.len", field), &a.get_rows().len(), &b.get_rows().len());
+  if rows.is_some() {
+    return rows;
+  }
+  let height: usize = a.get_rows().len();
+  let rows = a.get_rows().iter().zip(b.get_rows().iter()).enumerate();
+  for (row_index, (a_row, b_row)) in rows.rev() {
+    let tiles: Option<String> = compare(
+      &format!("{}.rows[{}].len", field, row_index), &a_row.chars().count(), &b_row.chars().count()
+    ).or_else(|| {
+      return a_row.chars().zip(b_row.chars()).enumerate()
+        .find_map(|(x, (a_tile, b_tile))| {
+          let y: usize = height - 1 - row_index;
+          return compare(&format!("{}.tile({}, {})", field, x, y), &a_tile, &b_tile);
+        });
+    });
+    if tiles.is_some() {
+      return tiles;
+    }
+  }
+  return compare_lists(&format!("{}.spawns", field), a.get_spawns(), b.get_spawns(), compare);
+}
+
+fn compare_players(field: &str, a: &Player, b: &Player) -> Option<String> {
+  return compare(&format!("{}.id", field), a.get_id(), b.get_id())
+    .or_else(|| compare(&format!("{}.speed", field), a.get_speed(), b.get_speed()))
+    .or_else(|| compare(&format!("{}.position", field), a.get_position(), b.get_position()))
+    .or_else(|| {
+      compare(
+        &format!("{}.previous_position", field),
+        a.get_previous_position(), b.get_previous_position()
+      )
+    })
+    .or_else(|| compare(&format!("{}.direction", field), a.get_direction(), b.get_direction()))
+    .or_else(|| compare(&format!("{}.bombs", field), a.get_bombs(), b.get_bombs()))
+    .or_else(|| compare(&format!("{}.range", field), a.get_range(), b.get_range()))
+    .or_else(|| compare(&format!("{}.piercing", field), a.get_piercing(), b.get_piercing()))
+    .or_else(|| compare(&format!("{}.abilities", field), a.get_abilities(), b.get_abilities()))
+    .or_else(|| compare(&format!("{}.alive", field), a.get_alive(), b.get_alive()));
+}
+
+fn compare_bombs(field: &str, a: &BombState, b: &BombState) -> Option<String> {
+  return compare(&format!("{}.position", field), a.get_position(), b.get_position())
+    .or_else(|| compare(&format!("{}.owner", field), a.get_owner(), b.get_owner()))
+    .or_else(|| compare(&format!("{}.lifetime", field), a.get_lifetime(), b.get_lifetime()))
+    .or_else(|| compare(&format!("{}.remote", field), a.get_remote(), b.get_remote()))
+    .or_else(|| compare(&format!("{}.piercing", field), a.get_piercing(), b.get_piercing()))
+    .or_else(|| compare(&format!("{}.range", field), a.get_range(), b.get_range()));
+}
+
+fn compare_flames(field: &str, a: &FlameState, b: &FlameState) -> Option<String> {
+  return compare(&format!("{}.start", field), a.get_start(), b.get_start())
+    .or_else(|| compare(&format!("{}.end", field), a.get_end(), b.get_end()))
+    .or_else(|| compare(&format!("{}.direction", field), a.get_direction(), b.get_direction()))
+    .or_else(|| {
+      compare(&format!("{}.spread_range", field), a.get_spread_range(), b.get_spread_range())
+    });
+}
+
+fn compare_blasts(field: &str, a: &BlastState, b: &BlastState) -> Option<String> {
+  return compare(&format!("{}.center", field), a.get_center(), b.get_center())
+    .or_else(|| compare(&format!("{}.owner", field), a.get_owner(), b.get_owner()))
+    .or_else(|| {
+      compare_lists(&format!("{}.flames", field), a.get_flames(), b.get_flames(), compare_flames)
+    })
+    .or_else(|| compare(&format!("{}.piercing", field), a.get_piercing(), b.get_piercing()))
+    .or_else(|| {
+      compare(&format!("{}.spread_done", field), a.get_spread_done(), b.get_spread_done())
+    })
+    .or_else(|| {
+      compare(&format!("{}.spread_rate", field), a.get_spread_rate(), b.get_spread_rate())
+    })
+    .or_else(|| {
+      compare(&format!("{}.spread_timer", field), a.get_spread_timer(), b.get_spread_timer())
+    })
+    .or_else(|| compare(&format!("{}.lifetime", field), a.get_lifetime(), b.get_lifetime()));
+}
+
+/* Finds the first field that's different between two states, going through them in the same order
+ * checksums do, and describes where it is and what it is in each, like
+ * "players[2].position: (1536, 768) vs (1540, 768)". Returns none if the states are the same.
+ * Lists are compared by length before their items, so a bomb that's only in one state shows up as
+ * the lengths being different rather than as every bomb after it being different.
+ */
+pub fn diff(a: &WorldState, b: &WorldState) -> Option<String> {
+  return compare_rules("rules", &a.rules, &b.rules)
+    .or_else(|| compare_stages("stage", &a.stage, &b.stage))
+    .or_else(|| compare_lists("players", &a.players, &b.players, compare_players))
+    .or_else(|| compare_lists("bombs", &a.bombs, &b.bombs, compare_bombs))
+    .or_else(|| compare_lists("blasts", &a.blasts, &b.blasts, compare_blasts))
+    .or_else(|| compare_lists("items", &a.items, &b.items, compare))
+    .or_else(|| compare("rng", &a.rng, &b.rng))
+    .or_else(|| compare("time", &a.time, &b.time));
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    common::direction::Direction,
+    model::{
+      bomb::{Bomb, BombImpl},
+      input::{self, InputFrame, PlayerInput},
+      item::MAX_SPEED,
+      position,
+      stage::StageImpl
+    }
+  };
+  use serde_json::{json, Value};
+
+  // A stage with room for flames to spread, and a soft wall for one of them to burn.
+  const LAYOUT: &str = "\
+0.....
+.#.#..
+...+.1";
+
+  /* Starts a round with a player placing a bomb, checking its state round trips on the way, and
+   * plays on until the bomb has gone off and its flames have spread.
+   */
+  fn play_blast() -> WorldImpl {
+    let stage: StageImpl = StageImpl::from_layout(LAYOUT).unwrap();
+    let mut world: WorldImpl = WorldImpl::start(Rules::default(), Box::new(stage), 2, 1);
+    let mut frame: InputFrame = input::idle_frame();
+    frame[0] = PlayerInput::new(Some(Direction::North), true, false, false);
+    world.tick_mut(&frame);
+    let mut spread: u32 = 0;
+    while spread < 3 {
+      world.tick_mut(&input::idle_frame());
+      if !world.get_blasts().is_empty() {
+        spread += 1;
+      }
+      if world.get_time().is_multiple_of(50) || spread > 0 {
+        check_round_trip(&world).unwrap();
+      }
+    }
+    return world;
+  }
+
+  // Changes the first flame of a world's state that's spread past its start, and rebuilds it.
+  fn change_flame(
+    world: &WorldImpl, change: &dyn Fn(&FlameState, &mut Value)
+  ) -> Result<(), String> {
+    let state: WorldState = WorldState::capture(world);
+    let (blast, flame): (usize, usize) = state.blasts.iter().enumerate()
+      .find_map(|(i, blast)| {
+        return blast.get_flames().iter()
+          .position(|flame| flame.get_start() != flame.get_end())
+          .map(|flame| (i, flame));
+      })
+      .expect("Nothing has spread");
+    let mut json: Value = serde_json::from_str(&state.to_json()?).unwrap();
+    change(&state.blasts[blast].get_flames()[flame], &mut json["blasts"][blast]["flames"][flame]);
+    return WorldImpl::from_state(&WorldState::from_json(&json.to_string())?).map(|_| ());
+  }
+
+  /* Changes a world's state, with a bomb and an item added so that there's one of everything to
+   * change, and rebuilds it.
+   */
+  fn change_state(world: &WorldImpl, change: &dyn Fn(&mut Value)) -> Result<(), String> {
+    let mut json: Value = serde_json::from_str(&WorldState::capture(world).to_json()?).unwrap();
+    json["bombs"] = json!([BombImpl::new((4, 2), 1, 10, false, false, 2).to_state()]);
+    json["items"] = json!([[[4, 0], Item::BombUp]]);
+    change(&mut json);
+    return WorldImpl::from_state(&WorldState::from_json(&json.to_string())?).map(|_| ());
+  }
+
+  #[test]
+  fn worlds_survive_both_forms() {
+    let world: WorldImpl = play_blast();
+    assert!(!world.get_blasts().is_empty());
+    assert_eq!(change_flame(&world, &|_, _| {}), Ok(()));
+    assert_eq!(change_state(&world, &|_| {}), Ok(()));
+  }
+
+  #[test]
+  fn bombs_off_the_stage_are_rejected() {
+    let world: WorldImpl = play_blast();
+    for position in [json!([127, 0]), json!([-1, 0]), json!([6, 0]), json!([0, 3])] {
+      let change = |json: &mut Value| json["bombs"][0]["position"] = position.clone();
+      assert!(change_state(&world, &change).is_err(), "{} was let through", position);
+    }
+  }
+
+  #[test]
+  fn players_off_the_stage_are_rejected() {
+    let world: WorldImpl = play_blast();
+    let furthest: (i32, i32) = position::from_tile(&(5, 2));
+    let positions: [(i32, i32); 4] =
+      [(32512, 0), (-1, 0), (furthest.0 + 1, 0), (0, furthest.1 + 1)];
+    for position in positions {
+      let change = |json: &mut Value| json["players"][1]["position"] = json!(position);
+      assert!(change_state(&world, &change).is_err(), "{:?} was let through", position);
+      let change = |json: &mut Value| json["players"][1]["previous_position"] = json!(position);
+      assert!(change_state(&world, &change).is_err(), "{:?} was let through", position);
+    }
+  }
+
+  #[test]
+  fn blasts_off_the_stage_are_rejected() {
+    let world: WorldImpl = play_blast();
+    for center in [json!([-1, 0]), json!([6, 0]), json!([0, 3])] {
+      let change = |json: &mut Value| json["blasts"][0]["center"] = center.clone();
+      assert!(change_state(&world, &change).is_err(), "{} was let through", center);
+    }
+  }
+
+  #[test]
+  fn items_off_the_stage_are_rejected() {
+    let world: WorldImpl = play_blast();
+    for position in [json!([-1, 0]), json!([6, 0]), json!([0, 3])] {
+      let change = |json: &mut Value| json["items"][0][0] = position.clone();
+      assert!(change_state(&world, &change).is_err(), "{} was let through", position);
+    }
+  }
+
+  #[test]
+  fn speeds_that_could_skip_walls_are_rejected() {
+    let world: WorldImpl = play_blast();
+    for speed in [-1, 0, MAX_SPEED + 1, 100000] {
+      let change = |json: &mut Value| json["players"][0]["speed"] = json!(speed);
+      assert!(change_state(&world, &change).is_err(), "{} was let through", speed);
+    }
+  }
+
+  #[test]
+  fn players_without_bombs_or_range_are_rejected() {
+    let world: WorldImpl = play_blast();
+    assert!(change_state(&world, &|json| json["players"][0]["bombs"] = json!(0)).is_err());
+    for range in [0, -1] {
+      let change = |json: &mut Value| json["players"][0]["range"] = json!(range);
+      assert!(change_state(&world, &change).is_err(), "{} was let through", range);
+    }
+  }
+
+  #[test]
+  fn bombs_without_range_are_rejected() {
+    let world: WorldImpl = play_blast();
+    for range in [0, -1] {
+      let change = |json: &mut Value| json["bombs"][0]["range"] = json!(range);
+      assert!(change_state(&world, &change).is_err(), "{} was let through", range);
+    }
+  }
+
+  #[test]
+  fn lifetimes_that_have_run_out_are_rejected() {
+    let world: WorldImpl = play_blast();
+    for lifetime in [0, -1, -100] {
+      let change = |json: &mut Value| json["bombs"][0]["lifetime"] = json!(lifetime);
+      assert!(change_state(&world, &change).is_err(), "Bomb lifetime {} got through", lifetime);
+      let change = |json: &mut Value| json["blasts"][0]["lifetime"] = json!(lifetime);
+      assert!(change_state(&world, &change).is_err(), "Blast lifetime {} got through", lifetime);
+    }
+  }
+
+  #[test]
+  fn spread_timers_past_the_spread_rate_are_rejected() {
+    let world: WorldImpl = play_blast();
+    let rate: i8 = *world.get_blasts()[0].get_spread_rate();
+    for timer in [-1, rate, rate + 1] {
+      let change = |json: &mut Value| json["blasts"][0]["spread_timer"] = json!(timer);
+      assert!(change_state(&world, &change).is_err(), "{} was let through", timer);
+    }
+  }
+
+  // Returns a world's state, and the same state with some changes made to it.
+  fn changed_state(world: &WorldImpl, change: &dyn Fn(&mut Value)) -> (WorldState, WorldState) {
+    let state: WorldState = WorldState::capture(world);
+    let mut json: Value = serde_json::to_value(&state).unwrap();
+    change(&mut json);
+    return (state, serde_json::from_value(json).unwrap());
+  }
+
+  #[test]
+  fn identical_states_have_no_differences() {
+    let world: WorldImpl = play_blast();
+    let state: WorldState = WorldState::capture(&world);
+    assert_eq!(diff(&state, &state.clone()), None);
+    assert_eq!(diff(&state, &WorldState::capture(&check_round_trip(&world).unwrap())), None);
+  }
+
+  #[test]
+  fn diffs_name_the_first_field_that_differs() {
+    let world: WorldImpl = play_blast();
+    let position: (i32, i32) = *world.get_players()[1].get_position();
+    let (a, b): (WorldState, WorldState) = changed_state(&world, &|json| {
+      json["players"][1]["position"][0] = json!(position.0 - 1);
+      json["rng"] = json!(0);
+    });
+    assert_eq!(
+      diff(&a, &b),
+      Some(
+        format!(
+          "players[1].position: {:?} vs {:?}", position, (position.0 - 1, position.1)
+        )
+      )
+    );
+
+    let rng: u64 = *a.get_rng();
+    let (a, b): (WorldState, WorldState) = changed_state(&world, &|json| json["rng"] = json!(0));
+    assert_eq!(diff(&a, &b), Some(format!("rng: {} vs 0", rng)));
+
+    let (a, b): (WorldState, WorldState) =
+      changed_state(&world, &|json| json["stage"]["rows"][0] = json!("+....."));
+    assert_eq!(diff(&a, &b), Some(String::from("stage.tile(0, 2): '.' vs '+'")));
+
+    let (a, b): (WorldState, WorldState) = changed_state(&world, &|json| {
+      json["blasts"][0]["flames"][1]["spread_range"] = json!(7);
+      json["time"] = json!(0);
+    });
+    let spread_range: i8 = *a.get_blasts()[0].get_flames()[1].get_spread_range();
+    assert_eq!(
+      diff(&a, &b),
+      Some(format!("blasts[0].flames[1].spread_range: {} vs 7", spread_range))
+    );
+
+    let (a, b): (WorldState, WorldState) =
+      changed_state(&world, &|json| json["blasts"] = json!([]));
+    assert_eq!(diff(&a, &b), Some(String::from("blasts.len: 1 vs 0")));
+  }
+
+  #[test]
+  fn flames_that_dont_fit_the_stage_are_rejected() {
+    let world: WorldImpl = play_blast();
+    let dimensions: (i8, i8) = *world.get_stage().get_dimensions();
+    let backwards = |flame: &FlameState, json: &mut Value| {
+      let (start, end): (&(i8, i8), &(i8, i8)) = (flame.get_start(), flame.get_end());
+      let direction: Option<Direction> = Direction::from_axes(start.0 - end.0, start.1 - end.1);
+      json["direction"] = serde_json::to_value(direction.unwrap()).unwrap();
+    };
+    assert!(change_flame(&world, &backwards).is_err());
+    let diagonal = |_: &FlameState, json: &mut Value| json["direction"] = json!("Northeast");
+    assert!(change_flame(&world, &diagonal).is_err());
+    let off_stage = |flame: &FlameState, json: &mut Value| {
+      json["end"] = json!([dimensions.0, flame.get_start().1]);
+    };
+    assert!(change_flame(&world, &off_stage).is_err());
+    let off_line = |flame: &FlameState, json: &mut Value| {
+      let start: &(i8, i8) = flame.get_start();
+      json["end"] = json!([dimensions.0 - 1 - start.0, dimensions.1 - 1 - start.1]);
+    };
+    assert!(change_flame(&world, &off_line).is_err());
+  }
+
+  #[test]
+  fn broken_binary_states_are_rejected() {
+    let bytes: Vec<u8> = WorldState::capture(&play_blast()).to_bytes().unwrap();
+    assert!(WorldState::from_bytes(&bytes).is_ok());
+    assert!(WorldState::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    assert!(WorldState::from_bytes(&[&bytes[..], &[0]].concat()).is_err());
+    assert!(WorldState::from_bytes(&[b"OBRP", &bytes[MAGIC.len()..]].concat()).is_err());
+    let mut version: Vec<u8> = bytes.clone();
+    version[MAGIC.len()] ^= 1;
+    assert!(WorldState::from_bytes(&version).is_err());
+  }
+}
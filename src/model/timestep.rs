@@ -0,0 +1,134 @@
+use crate::model::{
+  event::WorldEvent,
+  input::{self, InputFrame},
+  world::World
+};
+use std::time::Duration;
+
+// The rate the simulation runs at, in ticks per second. Every lifetime in the model is in these.
+pub const TICK_RATE: u32 = 60;
+
+/* A struct representing a fixed timestep driver for a world.
+ * Frontends run at whatever frame rate they can manage, but the world should only ever be ticked at
+ * a constant rate so that it behaves the same everywhere. This keeps an accumulator of real time
+ * that hasn't been simulated yet, and works out how many ticks that time is worth.
+ *
+ * Tick length is the real time a single tick represents.
+ * Max frame time is the most real time a single update is allowed to account for. If a frontend
+ * stalls (or a tick takes longer to simulate than it represents), anything past this is dropped
+ * rather than simulated, since trying to catch up would only make the next frame take even longer.
+ * Accumulator is the real time that has been fed in but not simulated yet. It is always less than a
+ * tick long after an update.
+ */
+pub struct FixedTimestep {
+  tick_length: Duration,
+  max_frame_time: Duration,
+  accumulator: Duration
+}
+
+impl FixedTimestep {
+  pub fn new(tick_rate: u32, max_frame_time: Duration) -> FixedTimestep {
+    return FixedTimestep {
+      tick_length: Duration::from_secs(1) / tick_rate,
+      max_frame_time: max_frame_time,
+      accumulator: Duration::from_secs(0)
+    }
+  }
+
+  /* Feeds real elapsed time into the accumulator and returns how many ticks are now due, leaving
+   * whatever is left over for the next call.
+   */
+  pub fn advance(&mut self, elapsed: Duration) -> u32 {
+    self.accumulator += elapsed.min(self.max_frame_time);
+    let mut ticks: u32 = 0;
+    while self.accumulator >= self.tick_length {
+      self.accumulator -= self.tick_length;
+      ticks += 1;
+    }
+    return ticks;
+  }
+
+  /* Advances by the elapsed time, and ticks the world in place however many times that calls for,
+   * returning how many ticks were run along with everything that happened during them.
+   * Button presses only go to the first of those ticks, while directions are held for all of them.
+   * If no ticks were run, nothing saw the presses, so the frontend should hang on to them for the
+   * next update.
+   */
+  pub fn update(
+    &mut self, world: &mut dyn World, elapsed: Duration, inputs: &InputFrame
+  ) -> (u32, Vec<WorldEvent>) {
+    return self.update_with(world, elapsed, inputs, &mut |_, _| {});
+  }
+
+  /* Updates like update, but lets something fill in more of the inputs right before each tick,
+   * going by the world as it is then. This is how bots get to react to every tick, even when a
+   * single update runs several.
+   */
+  pub fn update_with(
+    &mut self, world: &mut dyn World, elapsed: Duration, inputs: &InputFrame,
+    fill: &mut dyn FnMut(&dyn World, &mut InputFrame)
+  ) -> (u32, Vec<WorldEvent>) {
+    let ticks: u32 = self.advance(elapsed);
+    let mut frame: InputFrame = *inputs;
+    let mut events: Vec<WorldEvent> = vec!();
+    for _ in 0..ticks {
+      fill(&*world, &mut frame);
+      events.extend(world.tick_mut(&frame));
+      frame = input::held_frame(&frame);
+    }
+    return (ticks, events);
+  }
+
+  /* Returns how far (from 0 to 1) the real time is between the last tick and the next one.
+   * Renderers should use this to interpolate between the previous and current states, since
+   * drawing the current state alone would stutter whenever the frame rate isn't a multiple of the
+   * tick rate.
+   */
+  pub fn get_alpha(&self) -> f32 {
+    return self.accumulator.as_secs_f32() / self.tick_length.as_secs_f32();
+  }
+
+  pub fn get_tick_length(&self) -> &Duration {
+    return &self.tick_length;
+  }
+}
+
+impl Default for FixedTimestep {
+  fn default() -> FixedTimestep {
+    return FixedTimestep::new(TICK_RATE, Duration::from_millis(250));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn advance_runs_whole_ticks_and_keeps_the_rest() {
+    let mut timestep: FixedTimestep = FixedTimestep::new(100, Duration::from_millis(250));
+    let tick: Duration = *timestep.get_tick_length();
+    assert_eq!(timestep.advance(tick / 2), 0);
+    assert_eq!(timestep.advance(tick / 2), 1);
+    assert_eq!(timestep.advance(tick * 3 + tick / 4), 3);
+    assert_eq!(timestep.advance(tick * 3 / 4), 1);
+  }
+
+  #[test]
+  fn advance_drops_time_past_the_max_frame_time() {
+    let mut timestep: FixedTimestep = FixedTimestep::new(60, Duration::from_millis(100));
+    assert_eq!(timestep.advance(Duration::from_secs(10)), 6);
+    assert_eq!(timestep.advance(Duration::from_secs(0)), 0);
+  }
+
+  #[test]
+  fn alpha_is_how_far_into_the_next_tick_the_time_is() {
+    let mut timestep: FixedTimestep = FixedTimestep::new(50, Duration::from_millis(250));
+    assert_eq!(timestep.get_alpha(), 0.0);
+    timestep.advance(Duration::from_millis(25));
+    assert!((timestep.get_alpha() - 0.25).abs() < 1e-4);
+    timestep.advance(Duration::from_millis(25));
+    assert!((timestep.get_alpha() - 0.5).abs() < 1e-4);
+    timestep.advance(Duration::from_millis(10));
+    assert!(timestep.get_alpha().abs() < 1e-4);
+  }
+}
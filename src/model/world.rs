@@ -1,191 +1,835 @@
 use crate::{
+  common::direction::CARDINALS,
   common::direction::Direction,
   model::{
-    bomb::{Flame, FlameImpl, Blast, BlastImpl, Bomb, BombImpl},
+    bomb::{Blast, BlastImpl, Bomb, BombImpl},
+    event::{DetonationCause, WorldEvent},
+    input::{InputFrame, PlayerId, PlayerInput, MAX_PLAYERS},
+    item::{Item, MAX_SPEED},
     player::{Player},
-    stage::{Tile, Stage, StageImpl}
+    position,
+    prediction::BlastPrediction,
+    rng::Rng,
+    rules::Rules,
+    stage::{Stage, StageImpl, Tile},
+    state::WorldState
   }
 };
-use ndarray::{Array, arr2, Ix2};
-use std::convert::TryFrom;
+
+/* The way a round ended.
+ * A round is won by the last player standing. It's a draw if the last players die at the same
+ * time, or if the clock runs out with more than one of them still alive.
+ */
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Outcome {
+  Win(PlayerId),
+  Draw
+}
 
 /* A struct representing a game world for Bomberman.
  *
+ * Worlds can be ticked in two ways. Tick leaves the world untouched and returns the next one, which
+ * is convenient but means copying everything in it every frame. Tick mut updates the world in
+ * place instead, and doesn't allocate anything unless a bomb detonates, which is what anything
+ * running lots of frames (like a headless simulation) should be using. Callers that need to hang on
+ * to a world while it keeps ticking should take a snapshot of it.
+ *
+ * Either way, a tick takes the inputs of every player for that tick, and nothing else. Players are
+ * only ever moved or made to place bombs through these. It returns everything that happened during
+ * the tick, in the order it happened; see WorldEvent. Ticks where nothing happens don't allocate.
+ *
+ * Items are the power-ups lying on the stage, along with the tile each one is on. They're dropped
+ * by soft walls as they're destroyed, which is the only thing in the round left up to chance. That
+ * chance comes from the world's own random number generator, so the same seed and inputs always
+ * play out the same way.
+ *
+ * Time is how many ticks the round has been going for. Worlds carry on ticking once their round is
+ * over, so it's up to whatever is running one to notice the outcome and move on.
+ *
+ * Worlds, and everything in them, can be sent to other threads, so that lots of them can be run in
+ * parallel.
  */
+pub trait World: Send {
+  fn tick(&self, inputs: &InputFrame) -> (Box<dyn World>, Vec<WorldEvent>);
+
+  fn tick_mut(&mut self, inputs: &InputFrame) -> Vec<WorldEvent>;
+
+  fn snapshot(&self) -> Box<dyn World>;
+
+  fn get_rules(&self) -> &Rules;
+
+  fn get_stage(&self) -> &dyn Stage;
+
+  fn get_players(&self) -> &Vec<Player>;
 
-pub trait World {
-  fn tick(&self) -> Box<dyn World>;
+  fn get_bombs(&self) -> &Vec<Box<dyn Bomb>>;
 
-  fn update(&self, tick: i8) -> Box<dyn World>;
+  fn get_blasts(&self) -> &Vec<Box<dyn Blast>>;
 
-  fn move_player(&self, player: &Player, direction: &Direction) -> Box<dyn World>;
+  fn get_items(&self) -> &Vec<((i8, i8), Item)>;
 
-  fn tick_bombs(&self) -> Box<dyn World>;
+  fn get_rng(&self) -> &Rng;
 
-  fn tick_blasts(&self) -> Box<dyn World>;
+  fn get_time(&self) -> &u32;
 
-  fn check_bombs(&self) -> Box<dyn World>;
+  /* Predicts which tiles are going to be lethal over up to a number of ticks from now, if nobody
+   * does anything; see BlastPrediction. This leaves the world untouched.
+   */
+  fn predict_blasts(&self, horizon: u32) -> BlastPrediction;
+
+  // Returns how the round ended, or nothing if it's still going.
+  fn get_outcome(&self) -> Option<Outcome> {
+    let mut alive = self.get_players().iter().filter(|player| *player.get_alive());
+    match (alive.next(), alive.next()) {
+      (None, _) => return Some(Outcome::Draw),
+      (Some(player), None) if self.get_players().len() > 1 => {
+        return Some(Outcome::Win(*player.get_id()));
+      },
+      _ if self.get_time_left() == 0 => return Some(Outcome::Draw),
+      _ => return None
+    }
+  }
+
+  // Returns how many ticks are left on the round clock.
+  fn get_time_left(&self) -> u32 {
+    return self.get_rules().get_round_time().saturating_sub(*self.get_time());
+  }
+
+  fn is_sudden_death(&self) -> bool {
+    return self.get_time_left() <= *self.get_rules().get_sudden_death_time();
+  }
+
+  // Returns how many of a player's bombs are still on the field.
+  fn count_bombs(&self, id: &PlayerId) -> usize {
+    return self.get_bombs().iter().filter(|bomb| bomb.get_owner() == id).count();
+  }
 }
 
+/* Events are what has happened so far during the tick being run, and are always empty between
+ * ticks.
+ */
 pub struct WorldImpl {
+  rules: Rules,
   stage: Box<dyn Stage>,
 
   players: Vec<Player>,
   bombs: Vec<Box<dyn Bomb>>,
-  blasts: Vec<Box<dyn Blast>>
+  blasts: Vec<Box<dyn Blast>>,
+  items: Vec<((i8, i8), Item)>,
+  rng: Rng,
+  time: u32,
+  events: Vec<WorldEvent>
+}
+
+// Returns whether a flame should be stopped from entering a tile.
+fn blocks_flame(tile: Result<Tile, &str>, piercing: bool) -> bool {
+  match tile {
+    Ok(Tile::Ground) => return false,
+    Ok(Tile::SoftWall) => return !piercing,
+    Ok(Tile::HardWall) => return true,
+    Err(_) => return true
+  }
+}
+
+/* Returns whether each of a blast's flames will be stopped by a wall (or the edge of the stage) if
+ * it spreads, in the same order as the blast's flames.
+ */
+pub fn flames_hit_wall(stage: &dyn Stage, blast: &dyn Blast) -> [bool; 4] {
+  let mut hit_wall: [bool; 4] = [false; 4];
+  for (i, flame) in blast.get_flames().iter().enumerate() {
+    hit_wall[i] = blocks_flame(stage.get_tile(&flame.next_position()), *blast.get_piercing());
+  }
+  return hit_wall;
+}
+
+/* Returns the soft walls a blast's flames are about to spread into, in the same order as the
+ * blast's flames. These have to be destroyed after working out which flames hit a wall, otherwise
+ * the flames would carry on through the walls they just destroyed.
+ */
+fn soft_walls_burning(stage: &dyn Stage, blast: &dyn Blast) -> [Option<(i8, i8)>; 4] {
+  let mut burning: [Option<(i8, i8)>; 4] = [None; 4];
+  if blast.is_spreading() {
+    for (i, flame) in blast.get_flames().iter().enumerate() {
+      let next_position: (i8, i8) = flame.next_position();
+      if flame.get_spread_range() > &0 && stage.get_tile(&next_position) == Ok(Tile::SoftWall) {
+        burning[i] = Some(next_position);
+      }
+    }
+  }
+  return burning;
+}
+
+// Returns where the end of each of a blast's flames is, in the same order as the blast's flames.
+fn flame_ends(blast: &dyn Blast) -> [Option<(i8, i8)>; 4] {
+  let mut ends: [Option<(i8, i8)>; 4] = [None; 4];
+  for (i, flame) in blast.get_flames().iter().enumerate() {
+    ends[i] = Some(*flame.get_end());
+  }
+  return ends;
+}
+
+/* Moves a coordinate along one axis, stopping it at the center of its tile if the tile ahead is
+ * blocked. A player who is somehow already past the center isn't pushed back, they just can't go
+ * any further.
+ */
+fn move_axis(coordinate: i32, delta: i32, center: i32, blocked_ahead: bool) -> i32 {
+  let next: i32 = coordinate + delta;
+  if !blocked_ahead || delta == 0 {
+    return next;
+  } else if delta > 0 {
+    return next.min(coordinate.max(center));
+  } else {
+    return next.max(coordinate.min(center));
+  }
+}
+
+// Moves a coordinate towards a target by at most a step, without overshooting it.
+fn approach(coordinate: i32, target: i32, step: i32) -> i32 {
+  if coordinate < target {
+    return (coordinate + step).min(target);
+  } else {
+    return (coordinate - step).max(target);
+  }
 }
 
 impl WorldImpl {
   pub fn new(
-    stage: Box<dyn Stage>, players: Vec<Player>,
-    bombs: Vec<Box<dyn Bomb>>, blasts: Vec<Box<dyn Blast>>
+    rules: Rules, stage: Box<dyn Stage>, players: Vec<Player>,
+    bombs: Vec<Box<dyn Bomb>>, blasts: Vec<Box<dyn Blast>>, seed: u64
   ) -> WorldImpl {
     return WorldImpl {
+      rules: rules,
       stage: stage,
       players: players,
       bombs: bombs,
-      blasts: blasts
+      blasts: blasts,
+      items: vec!(),
+      rng: Rng::new(seed),
+      time: 0,
+      events: vec!()
     }
   }
 
-  fn is_wall_or_oob(&self, position: &(i8, i8)) -> bool {
-    match self.stage.get_tile(position) {
-      Ok(tile) => return tile.is_wall(),
-      Err(_) => true
+  // Returns whether players are kept from walking into a tile.
+  fn is_blocked(&self, tile: &(i8, i8)) -> bool {
+    match self.stage.get_tile(tile) {
+      Ok(tile) if tile.is_wall() => return true,
+      Ok(_) => return self.bombs.iter().any(|bomb| bomb.get_position() == tile),
+      Err(_) => return true
     }
   }
 
-  fn tick_all_blasts(&self) -> Vec<Box<dyn Blast>> {
-    let new_blasts: Vec<Box<dyn Blast>> = vec!();
-    for blast in self.blasts {
-      new_blasts.push(blast.tick(self.flames_hit_wall(blast)));
+  /* Works out where a player ends up after moving for a frame.
+   * Each axis is handled separately, x first, and a player can't leave their tile through a side
+   * that's blocked. The y axis goes by whichever tile the x axis left the player on, so a diagonal
+   * move can't slip through the corner between two tiles into a wall.
+   * Players moving straight are also pulled towards the middle of their row or column, which snaps
+   * them onto the grid and lets them slip into gaps without lining up perfectly.
+   */
+  fn resolve_movement(&self, player: &Player) -> (i32, i32) {
+    let current: &(i32, i32) = player.get_position();
+    let velocity: (i32, i32) = player.get_velocity();
+    let tile: (i8, i8) = player.get_tile();
+    let center: (i32, i32) = position::from_tile(&tile);
+
+    let ahead_x: (i8, i8) = (tile.0 + velocity.0.signum() as i8, tile.1);
+    let mut x: i32 = move_axis(current.0, velocity.0, center.0, self.is_blocked(&ahead_x));
+    let moved: (i8, i8) = position::to_tile(&(x, current.1));
+    let ahead_y: (i8, i8) = (moved.0, moved.1 + velocity.1.signum() as i8);
+    let mut y: i32 = move_axis(current.1, velocity.1, center.1, self.is_blocked(&ahead_y));
+
+    if velocity.0 == 0 && velocity.1 != 0 {
+      x = approach(x, center.0, *player.get_speed());
+    } else if velocity.1 == 0 && velocity.0 != 0 {
+      y = approach(y, center.1, *player.get_speed());
     }
-    return new_blasts;
+    return (x, y);
   }
 
-  fn flames_hit_wall(&self, blast: Box<dyn Blast>) -> Vec<bool> {
-    let hit_wall: Vec<bool> = vec!();
-    for position in blast.next_positions() {
-      hit_wall.push(self.is_wall_or_oob(&position));
-    }
-    return hit_wall;
+  /* Starts a round on a stage, with a player on each of the first few of its spawns.
+   * Player IDs line up with spawns, so player 0 always starts on spawn 0 and so on. The seed
+   * decides which items soft walls drop.
+   */
+  pub fn start(rules: Rules, stage: Box<dyn Stage>, player_count: usize, seed: u64) -> WorldImpl {
+    let players: Vec<Player> = stage.get_spawns().iter().take(player_count).enumerate()
+      .map(|(id, spawn)| {
+        Player::new(
+          id as PlayerId, position::from_tile(spawn), Direction::South, rules.get_starting_stats()
+        )
+      })
+      .collect();
+    return WorldImpl::new(rules, stage, players, vec!(), vec!(), seed);
   }
 
-  fn clone(&self) -> Box<dyn World> {
-    return Box::new(
+  /* Rebuilds a world from its state, checking that it makes sense first; see WorldState. Player IDs
+   * have to line up with where players are in the list, like they do in worlds that get started.
+   * Players have to be on the stage, with stats they could have gotten from the rules and items.
+   * Bombs, blasts and items have to be on the stage too.
+   */
+  pub fn from_state(state: &WorldState) -> Result<WorldImpl, String> {
+    let rules: Rules = state.get_rules().validate()?;
+    let stage: StageImpl = StageImpl::from_state(state.get_stage())?;
+    if state.get_players().len() > MAX_PLAYERS {
+      return Err(format!("Worlds can't have {} players.", state.get_players().len()));
+    }
+    // Players can't get any further than the center of a tile on the edge of the stage.
+    let dimensions: &(i8, i8) = stage.get_dimensions();
+    let furthest: (i32, i32) = position::from_tile(&(dimensions.0 - 1, dimensions.1 - 1));
+    let on_stage = |position: &(i32, i32)| -> bool {
+      return (0..=furthest.0).contains(&position.0) && (0..=furthest.1).contains(&position.1);
+    };
+    for (id, player) in state.get_players().iter().enumerate() {
+      if *player.get_id() as usize != id {
+        return Err(format!("Player {} is where player {} should be.", player.get_id(), id));
+      } else if !on_stage(player.get_position()) || !on_stage(player.get_previous_position()) {
+        return Err(format!("Player {} isn't on the stage.", id));
+      } else if !(1..=MAX_SPEED).contains(player.get_speed()) {
+        return Err(format!("Player {} has to have a speed between 1 and {}.", id, MAX_SPEED));
+      } else if *player.get_bombs() < 1 {
+        return Err(format!("Player {} has to have at least 1 bomb.", id));
+      } else if *player.get_range() < 1 {
+        return Err(format!("Player {} has to have at least 1 tile of range.", id));
+      }
+    }
+    let mut bombs: Vec<Box<dyn Bomb>> = vec!();
+    for bomb in state.get_bombs() {
+      bombs.push(Box::new(BombImpl::from_state(bomb, dimensions)?));
+    }
+    let mut blasts: Vec<Box<dyn Blast>> = vec!();
+    for blast in state.get_blasts() {
+      blasts.push(Box::new(BlastImpl::from_state(blast, dimensions)?));
+    }
+    for (tile, item) in state.get_items() {
+      if stage.get_tile(tile).is_err() {
+        return Err(format!("{:?} at {:?} isn't on the stage.", item, tile));
+      }
+    }
+    return Ok(
       WorldImpl {
-        stage: self.stage,
-        players: self.players,
-        bombs: self.bombs,
-        blasts: self.blasts
+        rules: rules,
+        stage: Box::new(stage),
+        players: state.get_players().clone(),
+        bombs: bombs,
+        blasts: blasts,
+        items: state.get_items().clone(),
+        // Generators carry on from whatever state they're seeded with.
+        rng: Rng::new(*state.get_rng()),
+        time: *state.get_time(),
+        events: vec!()
       }
-    )
+    );
   }
-}
 
-impl World for WorldImpl {
-  fn tick(&self) -> Box<dyn World> {
-    return self
-      .tick_bombs()
-      .tick_blasts()
-      .check_bombs();
+  // Returns this world with other players in it, for drawing it differently from how it is.
+  pub fn set_players(&self, players: Vec<Player>) -> WorldImpl {
+    return WorldImpl {
+      players: players,
+      ..self.clone()
+    }
   }
 
-  fn update(&self, dt: i8) -> Box<dyn World> {
-    let mut new_world: Box<dyn World> = self.clone();
-    for i in 0..dt {
-      new_world = new_world.tick();
+  fn apply_inputs(&mut self, inputs: &InputFrame) {
+    for i in 0..self.players.len() {
+      self.players[i] = self.players[i].set_previous_position();
+      if !self.players[i].get_alive() {
+        continue;
+      }
+      let input: PlayerInput = inputs[*self.players[i].get_id() as usize];
+      if let Some(direction) = input.get_direction() {
+        self.move_player(i, direction);
+      }
+      if *input.get_place_bomb() {
+        self.place_bomb(i, &self.players[i].get_tile());
+      }
+      if *input.get_detonate() {
+        self.detonate_remote(i);
+      }
+      if *input.get_special() {
+        self.place_line_bomb(i);
+      }
     }
-    return new_world;
   }
 
-  fn move_player(&self, player: &Player, direction: &Direction) -> Box<dyn World> {
-    return Box::new(
-      WorldImpl {
-        stage: self.stage,
-        players: self.players.into_iter().map(|p| {
-          if p == *player {
-            return p.set_direction(*direction).set_next_position();
-          } else {
-            return p;
-          }
-        }).collect(),
-        bombs: self.bombs,
-        blasts: self.blasts
-      }
-    )
-  }
-
-  fn tick_bombs(&self) -> Box<dyn World> {
-    return Box::new(
-      WorldImpl {
-        stage: self.stage.copy(),
-        players: self.players.clone(),
-        bombs: self.bombs.into_iter().map(|b| {
-          b.tick()
-        }).collect(),
-        blasts: self.blasts
+  fn move_player(&mut self, index: usize, direction: &Direction) {
+    let turned: Player = self.players[index].set_direction(*direction);
+    self.players[index] = turned.set_position(self.resolve_movement(&turned));
+  }
+
+  /* Has a player place a bomb on a tile, returning whether they could.
+   * Players can't place more bombs than they're allowed, and can't place them anywhere that's
+   * already blocked.
+   */
+  fn place_bomb(&mut self, index: usize, tile: &(i8, i8)) -> bool {
+    let player: Player = self.players[index];
+    if self.count_bombs(player.get_id()) >= *player.get_bombs() as usize || self.is_blocked(tile) {
+      return false;
+    }
+    self.events.push(WorldEvent::BombPlaced { owner: *player.get_id(), position: *tile });
+    self.bombs.push(
+      Box::new(
+        BombImpl::new(
+          *tile,
+          *player.get_id(),
+          *self.rules.get_bomb_fuse(),
+          *player.get_abilities().get_remote_control(),
+          *player.get_piercing(),
+          *player.get_range()
+        )
+      )
+    );
+    return true;
+  }
+
+  /* Has a player place as many bombs as they have left in a line, starting from their own tile and
+   * heading the way they're facing. This only works for players with the line bomb ability who are
+   * facing a cardinal direction, and stops at the first blocked tile.
+   */
+  fn place_line_bomb(&mut self, index: usize) {
+    let player: Player = self.players[index];
+    if !*player.get_abilities().get_line_bomb() || !CARDINALS.contains(player.get_direction()) {
+      return;
+    }
+    let mut tile: (i8, i8) = player.get_tile();
+    if self.bombs.iter().any(|bomb| bomb.get_position() == &tile) {
+      tile = player.get_direction().step(&tile);
+    }
+    while self.place_bomb(index, &tile) {
+      tile = player.get_direction().step(&tile);
+    }
+  }
+
+  // Detonates the oldest of a player's remote bombs, if they have one.
+  fn detonate_remote(&mut self, index: usize) {
+    let id: &PlayerId = self.players[index].get_id();
+    let remote_bomb: Option<&mut Box<dyn Bomb>> = self.bombs.iter_mut()
+      .find(|bomb| bomb.get_owner() == id && *bomb.get_remote());
+    if let Some(bomb) = remote_bomb {
+      bomb.detonate();
+    }
+  }
+
+  fn tick_bombs(&mut self) {
+    for bomb in self.bombs.iter_mut() {
+      bomb.tick();
+    }
+  }
+
+  /* Spreads every blast's flames. Soft walls they reach are only destroyed once every blast has
+   * spread, so that every blast sees the same stage, and no blast can carry on through a wall
+   * another one burned on the same tick.
+   */
+  fn tick_blasts(&mut self) {
+    let mut burned: Vec<(i8, i8)> = vec!();
+    for i in 0..self.blasts.len() {
+      let hit_wall: [bool; 4] = flames_hit_wall(self.stage.as_ref(), self.blasts[i].as_ref());
+      let burning: [Option<(i8, i8)>; 4] =
+        soft_walls_burning(self.stage.as_ref(), self.blasts[i].as_ref());
+      for tile in burning.iter().flatten() {
+        if !burned.contains(tile) {
+          burned.push(*tile);
+        }
+      }
+      let ends: [Option<(i8, i8)>; 4] = flame_ends(self.blasts[i].as_ref());
+      self.blasts[i].tick(&hit_wall);
+      let blast: &dyn Blast = self.blasts[i].as_ref();
+      for (end, flame) in ends.iter().zip(blast.get_flames().iter()) {
+        if end.as_ref() != Some(flame.get_end()) {
+          self.events.push(
+            WorldEvent::FlameSpread { owner: *blast.get_owner(), position: *flame.get_end() }
+          );
+        }
       }
-    )
+    }
+    for tile in burned.iter() {
+      self.destroy_soft_wall(tile);
+    }
+    self.blasts.retain(|blast| blast.get_lifetime() > &0);
   }
 
-  fn tick_blasts(&self) -> Box<dyn World> {
-    return Box::new(
-      WorldImpl {
-        stage: self.stage.copy(),
-        players: self.players.clone(),
-        bombs: self.bombs,
-        blasts: self.tick_all_blasts()
-      }
-    )
-  }
-
-  fn check_bombs(&self) -> Box<dyn World> {
-    let mut new_stage: Box<dyn Stage> = self.stage.copy();
-    let mut new_bombs: Vec<Box<dyn Bomb>> = vec!();
-    let mut new_blasts: Vec<Box<dyn Blast>> = vec!();
-    for bomb in self.bombs {
-      if bomb.get_lifetime() == &0 {
-        let center: (i8, i8) = *bomb.get_position();
-        let up_point: (i8, i8) = (center.0, center.1 + 1);
-        let down_point: (i8, i8) = (center.0, center.1 - 1);
-        let left_point: (i8, i8) = (center.0 - 1, center.1);
-        let right_point: (i8, i8) = (center.0 + 1, center.1);
-
-        let up_free: bool = !self.is_wall_or_oob(&up_point);
-        let down_free: bool = !self.is_wall_or_oob(&down_point);
-        let left_free: bool = !self.is_wall_or_oob(&left_point);
-        let right_free: bool = !self.is_wall_or_oob(&right_point);
-
-        if !up_free { new_stage = new_stage.set_tile(&up_point, Tile::Ground) }
-        if !down_free { new_stage = new_stage.set_tile(&down_point, Tile::Ground) }
-        if !left_free { new_stage = new_stage.set_tile(&left_point, Tile::Ground) }
-        if !right_free { new_stage = new_stage.set_tile(&right_point, Tile::Ground) }
-
-        new_blasts.push(
-          Box::new(
-            BlastImpl::new(
-              center,
-              *bomb.get_range(),
-              up_free,
-              down_free,
-              left_free,
-              right_free
-            )
-          )
-        );
-      } else {
-        new_bombs.push(bomb);
-      }
-    }
-
-    return Box::new(
-      WorldImpl {
-        stage: new_stage,
-        players: self.players.clone(),
-        bombs: new_bombs,
-        blasts: new_blasts
+  /* Destroys the soft wall on a tile, which might leave an item behind.
+   * Walls are always rolled for in the order they're destroyed, so that a seed always drops the
+   * same items.
+   */
+  fn destroy_soft_wall(&mut self, tile: &(i8, i8)) {
+    self.stage.set_tile(tile, Tile::Ground);
+    self.events.push(WorldEvent::TileDestroyed { position: *tile });
+    if let Some(item) = Item::roll(self.rules.get_item_drop_rates(), &mut self.rng) {
+      self.items.push((*tile, item));
+      self.events.push(WorldEvent::ItemSpawned { item: item, position: *tile });
+    }
+  }
+
+  /* Returns why a bomb should go off this tick, if it should. Bombs go off when their fuse runs
+   * out (or their owner detonates them), or when they're caught in a blast.
+   */
+  fn detonation_cause(&self, bomb: &dyn Bomb) -> Option<DetonationCause> {
+    if bomb.can_detonate() {
+      match bomb.get_remote() {
+        true => return Some(DetonationCause::Remote),
+        false => return Some(DetonationCause::Fuse)
+      }
+    }
+    return self.blasts.iter()
+      .find(|blast| blast.covers(bomb.get_position()))
+      .map(|blast| {
+        return DetonationCause::Chain { owner: *blast.get_owner(), position: *blast.get_center() };
+      });
+  }
+
+  /* Detonates every bomb that should go off. Each blast can catch more bombs as soon as it appears,
+   * so this carries on until there's nothing left to set off, which lets a whole chain go off on
+   * the same tick.
+   */
+  fn check_bombs(&mut self) {
+    let mut i: usize = 0;
+    while i < self.bombs.len() {
+      match self.detonation_cause(self.bombs[i].as_ref()) {
+        Some(cause) => {
+          let bomb: Box<dyn Bomb> = self.bombs.remove(i);
+          self.events.push(
+            WorldEvent::BombDetonated {
+              owner: *bomb.get_owner(),
+              position: *bomb.get_position(),
+              cause: cause
+            }
+          );
+          self.detonate(bomb.as_ref());
+          // The new blast might have caught a bomb that was already checked.
+          i = 0;
+        },
+        None => i += 1
       }
-    )
+    }
+  }
+
+  // Has any players standing on an item pick it up. Items are only ever picked up by one player.
+  fn check_items(&mut self) {
+    for i in 0..self.players.len() {
+      let tile: (i8, i8) = self.players[i].get_tile();
+      if !*self.players[i].get_alive() {
+        continue;
+      }
+      if let Some(index) = self.items.iter().position(|(position, _)| position == &tile) {
+        let (position, item): ((i8, i8), Item) = self.items.remove(index);
+        self.players[i] = item.apply(&self.players[i]);
+        let id: PlayerId = *self.players[i].get_id();
+        self.events.push(WorldEvent::ItemCollected { id: id, item: item, position: position });
+      }
+    }
+  }
+
+  /* Kills any players standing in a blast. If more than one blast covers them, the blame goes to
+   * the oldest.
+   */
+  fn check_players(&mut self) {
+    for i in 0..self.players.len() {
+      let tile: (i8, i8) = self.players[i].get_tile();
+      if !*self.players[i].get_alive() {
+        continue;
+      }
+      if let Some(blast) = self.blasts.iter().find(|blast| blast.covers(&tile)) {
+        let killer: PlayerId = *blast.get_owner();
+        let id: PlayerId = *self.players[i].get_id();
+        self.players[i] = self.players[i].set_alive(false);
+        self.events.push(WorldEvent::PlayerKilled { id: id, killer: killer });
+      }
+    }
+  }
+
+  /* Replaces a bomb with its blast.
+   * Any soft walls right next to the bomb are destroyed straight away, and only piercing bombs get
+   * to put flames where they were.
+   */
+  fn detonate(&mut self, bomb: &dyn Bomb) {
+    let center: (i8, i8) = *bomb.get_position();
+    let mut free: [bool; 4] = [false; 4];
+    for (i, direction) in CARDINALS.iter().enumerate() {
+      let point: (i8, i8) = direction.step(&center);
+      let tile: Result<Tile, &str> = self.stage.get_tile(&point);
+      free[i] = !blocks_flame(tile, *bomb.get_piercing());
+      if tile == Ok(Tile::SoftWall) {
+        self.destroy_soft_wall(&point);
+      }
+    }
+
+    self.blasts.push(
+      Box::new(
+        BlastImpl::new(
+          center,
+          *bomb.get_owner(),
+          *bomb.get_range(),
+          *bomb.get_piercing(),
+          *self.rules.get_blast_lifetime(),
+          *self.rules.get_flame_spread_rate(),
+          free
+        )
+      )
+    );
+  }
+}
+
+impl Clone for WorldImpl {
+  fn clone(&self) -> WorldImpl {
+    return WorldImpl {
+      rules: self.rules,
+      stage: self.stage.copy(),
+      players: self.players.clone(),
+      bombs: self.bombs.iter().map(|bomb| bomb.copy()).collect(),
+      blasts: self.blasts.iter().map(|blast| blast.copy()).collect(),
+      items: self.items.clone(),
+      rng: self.rng,
+      time: self.time,
+      events: vec!()
+    }
+  }
+}
+
+impl World for WorldImpl {
+  fn tick(&self, inputs: &InputFrame) -> (Box<dyn World>, Vec<WorldEvent>) {
+    let mut new_world: WorldImpl = self.clone();
+    let events: Vec<WorldEvent> = new_world.tick_mut(inputs);
+    return (Box::new(new_world), events);
+  }
+
+  fn tick_mut(&mut self, inputs: &InputFrame) -> Vec<WorldEvent> {
+    // Rounds that start in sudden death get told so on their first tick.
+    let was_sudden_death: bool = self.time > 0 && self.is_sudden_death();
+    let was_over: bool = self.get_outcome().is_some();
+    if self.time == 0 {
+      self.events.push(WorldEvent::RoundStarted);
+    }
+
+    self.apply_inputs(inputs);
+    self.tick_bombs();
+    self.tick_blasts();
+    self.check_bombs();
+    self.check_items();
+    self.check_players();
+    self.time += 1;
+
+    if !was_sudden_death && self.is_sudden_death() {
+      self.events.push(WorldEvent::SuddenDeath);
+    }
+    match self.get_outcome() {
+      Some(outcome) if !was_over => self.events.push(WorldEvent::RoundEnded { outcome: outcome }),
+      _ => {}
+    }
+    return std::mem::take(&mut self.events);
+  }
+
+  fn snapshot(&self) -> Box<dyn World> {
+    return Box::new(self.clone());
+  }
+
+  fn get_rules(&self) -> &Rules {
+    return &self.rules;
+  }
+
+  fn get_stage(&self) -> &dyn Stage {
+    return self.stage.as_ref();
+  }
+
+  fn get_players(&self) -> &Vec<Player> {
+    return &self.players;
+  }
+
+  fn get_bombs(&self) -> &Vec<Box<dyn Bomb>> {
+    return &self.bombs;
+  }
+
+  fn get_blasts(&self) -> &Vec<Box<dyn Blast>> {
+    return &self.blasts;
+  }
+
+  fn get_items(&self) -> &Vec<((i8, i8), Item)> {
+    return &self.items;
+  }
+
+  fn get_rng(&self) -> &Rng {
+    return &self.rng;
+  }
+
+  fn get_time(&self) -> &u32 {
+    return &self.time;
+  }
+
+  fn predict_blasts(&self, horizon: u32) -> BlastPrediction {
+    return BlastPrediction::new(self, horizon);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::model::{input, position::SUBTILE};
+
+  // Starts a round on a stage layout with players at positions, by ID, and any bombs given.
+  fn start(layout: &str, players: &[(i32, i32)], bombs: Vec<BombImpl>) -> WorldImpl {
+    let rules: Rules = Rules::default();
+    let players: Vec<Player> = players.iter().enumerate()
+      .map(|(id, at)| {
+        return Player::new(id as PlayerId, *at, Direction::South, rules.get_starting_stats());
+      })
+      .collect();
+    let bombs: Vec<Box<dyn Bomb>> =
+      bombs.into_iter().map(|bomb| Box::new(bomb) as Box<dyn Bomb>).collect();
+    let stage: StageImpl = StageImpl::from_layout(layout).unwrap();
+    return WorldImpl::new(rules, Box::new(stage), players, bombs, vec!(), 0);
+  }
+
+  // Returns a bomb that's about to go off.
+  fn lit(position: (i8, i8), owner: PlayerId, range: i8) -> BombImpl {
+    return BombImpl::new(position, owner, 1, false, false, range);
+  }
+
+  // Returns a remote bomb nobody is going to set off.
+  fn idle_remote(position: (i8, i8)) -> BombImpl {
+    return BombImpl::new(position, 0, 1, true, false, 1);
+  }
+
+  // Walks the player in a direction for a number of ticks, returning every tile they stood on.
+  fn walk(world: &mut WorldImpl, direction: Direction, ticks: u32) -> Vec<(i8, i8)> {
+    let mut frame: InputFrame = input::idle_frame();
+    frame[0] = PlayerInput::new(Some(direction), false, false, false);
+    let mut tiles: Vec<(i8, i8)> = vec!();
+    for _ in 0..ticks {
+      world.tick_mut(&frame);
+      tiles.push(world.get_players()[0].get_tile());
+    }
+    return tiles;
+  }
+
+  #[test]
+  fn players_stop_in_front_of_walls() {
+    let mut world: WorldImpl = start("0.#.", &[(0, 0)], vec!());
+    walk(&mut world, Direction::East, 60);
+    assert_eq!(world.get_players()[0].get_position(), &position::from_tile(&(1, 0)));
+    walk(&mut world, Direction::West, 60);
+    assert_eq!(world.get_players()[0].get_position(), &position::from_tile(&(0, 0)));
+  }
+
+  #[test]
+  fn players_cant_cut_through_the_corners_of_walls() {
+    let layout: &str = "\
+....
+..#.
+.0..
+....";
+    let mut world: WorldImpl = start(layout, &[position::from_tile(&(1, 1))], vec!());
+    let tiles: Vec<(i8, i8)> = walk(&mut world, Direction::Northeast, 90);
+    // The player has to go around the wall, up the far side of it.
+    assert!(tiles.iter().filter(|tile| tile.1 >= 2).all(|tile| tile.0 == 3));
+    assert_eq!(tiles.last(), Some(&(3, 3)));
+  }
+
+  #[test]
+  fn players_slide_around_corners_into_gaps() {
+    let layout: &str = "\
+.#..
+0...";
+    let mut world: WorldImpl = start(layout, &[(0, SUBTILE / 3)], vec!());
+    walk(&mut world, Direction::East, 60);
+    assert_eq!(world.get_players()[0].get_position(), &position::from_tile(&(3, 0)));
+  }
+
+  #[test]
+  fn bombs_block_players() {
+    let mut world: WorldImpl = start("0...", &[(0, 0)], vec!(idle_remote((2, 0))));
+    walk(&mut world, Direction::East, 60);
+    assert_eq!(world.get_players()[0].get_position(), &position::from_tile(&(1, 0)));
+  }
+
+  #[test]
+  fn placing_a_bomb_is_reported() {
+    let mut world: WorldImpl = start(".0.", &[position::from_tile(&(1, 0))], vec!());
+    let mut frame: InputFrame = input::idle_frame();
+    frame[0] = PlayerInput::new(None, true, false, false);
+    let events: Vec<WorldEvent> = world.tick_mut(&frame);
+    assert_eq!(
+      events, vec!(WorldEvent::RoundStarted, WorldEvent::BombPlaced { owner: 0, position: (1, 0) })
+    );
+  }
+
+  #[test]
+  fn bombs_caught_in_a_blast_chain() {
+    let players: [(i32, i32); 2] = [position::from_tile(&(0, 1)), position::from_tile(&(5, 1))];
+    let bombs: Vec<BombImpl> = vec!(lit((0, 0), 0, 4), idle_remote((3, 0)));
+    let mut world: WorldImpl = start("......\n......", &players, bombs);
+    let mut events: Vec<WorldEvent> = vec!();
+    for _ in 0..10 {
+      events.extend(world.tick_mut(&input::idle_frame()));
+    }
+    let detonations: Vec<WorldEvent> = events.into_iter()
+      .filter(|event| matches!(event, WorldEvent::BombDetonated { .. }))
+      .collect();
+    assert_eq!(
+      detonations,
+      vec!(
+        WorldEvent::BombDetonated { owner: 0, position: (0, 0), cause: DetonationCause::Fuse },
+        WorldEvent::BombDetonated {
+          owner: 0, position: (3, 0), cause: DetonationCause::Chain { owner: 0, position: (0, 0) }
+        }
+      )
+    );
+  }
+
+  #[test]
+  fn burned_soft_walls_drop_items_going_by_the_seed() {
+    let rules: Rules = Rules::default();
+    // The world's generator is seeded with 0, so this is the first thing it rolls.
+    let expected: Option<Item> = Item::roll(rules.get_item_drop_rates(), &mut Rng::new(0));
+    let players: [(i32, i32); 2] = [position::from_tile(&(0, 1)), position::from_tile(&(3, 1))];
+    let mut world: WorldImpl = start("....\n.+..", &players, vec!(lit((2, 0), 1, 1)));
+    let events: Vec<WorldEvent> = world.tick_mut(&input::idle_frame());
+    let burned: Vec<WorldEvent> = events.into_iter()
+      .filter(|event| {
+        return matches!(event, WorldEvent::TileDestroyed { .. } | WorldEvent::ItemSpawned { .. });
+      })
+      .collect();
+    let mut wanted: Vec<WorldEvent> = vec!(WorldEvent::TileDestroyed { position: (1, 0) });
+    wanted.extend(expected.map(|item| WorldEvent::ItemSpawned { item: item, position: (1, 0) }));
+    assert_eq!(burned, wanted);
+    assert_eq!(world.get_stage().get_tile(&(1, 0)), Ok(Tile::Ground));
+  }
+
+  #[test]
+  fn deaths_are_blamed_on_whoever_owned_the_blast() {
+    let players: [(i32, i32); 3] =
+      [position::from_tile(&(0, 0)), position::from_tile(&(2, 0)), position::from_tile(&(5, 0))];
+    let mut world: WorldImpl = start("......", &players, vec!(lit((1, 0), 1, 1)));
+    let events: Vec<WorldEvent> = world.tick_mut(&input::idle_frame());
+    let ending: Vec<WorldEvent> = events.into_iter()
+      .filter(|event| {
+        return matches!(event, WorldEvent::PlayerKilled { .. } | WorldEvent::RoundEnded { .. });
+      })
+      .collect();
+    assert_eq!(
+      ending,
+      vec!(
+        WorldEvent::PlayerKilled { id: 0, killer: 1 },
+        WorldEvent::PlayerKilled { id: 1, killer: 1 },
+        WorldEvent::RoundEnded { outcome: Outcome::Win(2) }
+      )
+    );
+    // Rounds only end once.
+    assert!(!world.tick_mut(&input::idle_frame()).iter()
+      .any(|event| matches!(event, WorldEvent::RoundEnded { .. })));
+  }
+
+  #[test]
+  fn blasts_all_stop_at_a_soft_wall_they_burn_together() {
+    let players: [(i32, i32); 2] = [position::from_tile(&(0, 2)), position::from_tile(&(8, 2))];
+    let bombs: Vec<BombImpl> = vec!(lit((1, 0), 0, 4), lit((5, 0), 1, 4));
+    let mut world: WorldImpl = start(".........\n.........\n...+.....", &players, bombs);
+    let mut events: Vec<WorldEvent> = vec!();
+    for _ in 0..10 {
+      events.extend(world.tick_mut(&input::idle_frame()));
+    }
+    let destroyed: usize = events.iter()
+      .filter(|event| matches!(event, WorldEvent::TileDestroyed { .. }))
+      .count();
+    assert_eq!(destroyed, 1);
+    assert_eq!(world.get_stage().get_tile(&(3, 0)), Ok(Tile::Ground));
+    assert!(!world.get_blasts().iter().any(|blast| blast.covers(&(3, 0))));
   }
 }
@@ -0,0 +1,209 @@
+use crate::{
+  model::{
+    checksum::{self, Checksum},
+    input::{self, InputFrame, PlayerId, PlayerInput},
+    world::{World, WorldImpl}
+  },
+  net::{
+    packet::{Packet, MAX_INPUTS},
+    transport::Transport
+  }
+};
+use std::time::{Duration, Instant};
+
+// The most ticks late local inputs can go in. Anything more wouldn't fit in a packet.
+pub const MAX_INPUT_DELAY: u32 = MAX_INPUTS as u32 / 2;
+
+// How long a peer can go without being heard from before the session gives up on them.
+pub const TIMEOUT: Duration = Duration::from_secs(10);
+
+/* A struct representing every player's inputs for a round, as they come in from peers over a
+ * network, which is what sessions of any kind are built on. Sessions decide what to do with the
+ * world while inputs are missing; this only gets them everywhere they need to be.
+ *
+ * Delay is how many ticks after it's made a local input goes in, which gives it that long to get
+ * to the other peers before they'd be missing it. Nobody has inputs for the first ticks because of
+ * that, so everyone starts out idle for them.
+ * Session is the checksum of the world the round started from and the delay, since peers with
+ * different delays would put their inputs in at different ticks; see Packet.
+ * Inputs are every player's inputs that are in so far, indexed by player ID and then tick. Only
+ * inputs that follow on from the ones already in are kept, since any that are missing in between
+ * are sent again anyway.
+ * Acks are how many of the local player's inputs each peer has, by player ID.
+ * Remote checksums are the latest each peer sent that haven't been compared yet. The first one
+ * that doesn't match the session's means the peers have desynced, which is fatal.
+ */
+pub struct InputExchange {
+  transport: Box<dyn Transport>,
+  local: PlayerId,
+  delay: u32,
+  session: u64,
+  inputs: Vec<Vec<PlayerInput>>,
+  acks: Vec<u32>,
+  remote_checksums: Vec<Option<(u32, u64)>>,
+  last_heard: Vec<Instant>,
+  mismatched: Vec<bool>
+}
+
+impl InputExchange {
+  /* Starts exchanging inputs for a world at the start of its round, which every peer has to start
+   * the same way. Every player in the world other than the local one is played by a peer.
+   */
+  pub fn new(
+    world: &WorldImpl, local: PlayerId, delay: u32, transport: Box<dyn Transport>
+  ) -> Result<InputExchange, String> {
+    let players: usize = world.get_players().len();
+    if *world.get_time() != 0 {
+      return Err(String::from("Sessions have to be started at the start of a round."));
+    } else if local as usize >= players {
+      return Err(format!("There's no player {} in a round of {}.", local, players));
+    } else if delay > MAX_INPUT_DELAY {
+      return Err(format!("Input delay can't be more than {} ticks.", MAX_INPUT_DELAY));
+    }
+    let mut session: Checksum = Checksum::default();
+    session.write_u64(checksum::checksum(world));
+    session.write_u32(delay);
+    return Ok(
+      InputExchange {
+        transport: transport,
+        local: local,
+        delay: delay,
+        session: session.finish(),
+        inputs: vec!(vec!(PlayerInput::default(); delay as usize); players),
+        acks: vec!(0; players),
+        remote_checksums: vec!(None; players),
+        last_heard: vec!(Instant::now(); players),
+        mismatched: vec!(false; players)
+      }
+    );
+  }
+
+  // Returns the IDs of every player played by a peer.
+  fn peers(&self) -> impl Iterator<Item = PlayerId> {
+    let local: PlayerId = self.local;
+    return (0..self.inputs.len() as PlayerId).filter(move |id| *id != local);
+  }
+
+  /* Puts the local player's input in for the tick delay ticks after a time, returning whether it
+   * went in. It doesn't if that tick already has one, in which case the frontend should hang on to
+   * any presses for next time.
+   */
+  pub fn add_local_input(&mut self, input: &PlayerInput, time: u32) -> bool {
+    let local: &mut Vec<PlayerInput> = &mut self.inputs[self.local as usize];
+    if local.len() as u32 > time + self.delay {
+      return false;
+    }
+    local.push(*input);
+    return true;
+  }
+
+  // Takes in a packet from a peer, ignoring it if it's from another round.
+  fn receive_packet(&mut self, packet: &Packet) {
+    let sender: usize = *packet.get_sender() as usize;
+    if sender >= self.inputs.len() || sender == self.local as usize {
+      return;
+    } else if *packet.get_session() != self.session {
+      self.mismatched[sender] = true;
+      return;
+    }
+    self.mismatched[sender] = false;
+    self.last_heard[sender] = Instant::now();
+    self.acks[sender] = self.acks[sender].max(*packet.get_ack());
+    let remote_checksum: &mut Option<(u32, u64)> = &mut self.remote_checksums[sender];
+    if remote_checksum.is_none_or(|(tick, _)| tick < packet.get_checksum().0) {
+      *remote_checksum = Some(*packet.get_checksum());
+    }
+    let inputs: &mut Vec<PlayerInput> = &mut self.inputs[sender];
+    let start: usize = *packet.get_start() as usize;
+    if start <= inputs.len() {
+      let new: &[PlayerInput] = packet.get_inputs().get(inputs.len() - start..).unwrap_or(&[]);
+      inputs.extend_from_slice(new);
+    }
+  }
+
+  // Compares the checksums peers sent against the local ones, for the ticks there are some for.
+  fn check_desyncs(&mut self, checksums: &[u64]) -> Result<(), String> {
+    for peer in 0..self.remote_checksums.len() {
+      if let Some((tick, remote)) = self.remote_checksums[peer] {
+        if let Some(local) = checksums.get(tick as usize) {
+          if *local != remote {
+            return Err(format!("Desynced from player {} at tick {}.", peer, tick));
+          }
+          self.remote_checksums[peer] = None;
+        }
+      }
+    }
+    return Ok(());
+  }
+
+  /* Takes in everything that came in from peers, checks nobody has desynced or gone quiet, and
+   * sends every peer what they need from this one. Checksums are the session's checksums of its
+   * world at every tick it's sure of, and the last of them is sent along.
+   */
+  pub fn poll(&mut self, checksums: &[u64]) -> Result<(), String> {
+    while let Some(bytes) = self.transport.receive()? {
+      // Anything that isn't a packet is just noise on the port.
+      if let Ok(packet) = Packet::from_bytes(&bytes) {
+        self.receive_packet(&packet);
+      }
+    }
+    self.check_desyncs(checksums)?;
+    let latest: (u32, u64) = match checksums.last() {
+      Some(checksum) => (checksums.len() as u32 - 1, *checksum),
+      None => (0, 0)
+    };
+    for peer in self.peers().collect::<Vec<PlayerId>>() {
+      if self.last_heard[peer as usize].elapsed() >= TIMEOUT {
+        return match self.mismatched[peer as usize] {
+          true => Err(format!("Player {} started the round with different settings.", peer)),
+          false => Err(format!("Lost the connection to player {}.", peer))
+        };
+      }
+      let start: usize = self.acks[peer as usize] as usize;
+      let inputs: Vec<PlayerInput> = self.inputs[self.local as usize].iter()
+        .skip(start).take(MAX_INPUTS).copied().collect();
+      let packet: Packet = Packet::new(
+        self.session, self.local, self.inputs[peer as usize].len() as u32, latest, start as u32,
+        inputs
+      );
+      self.transport.send(peer, &packet.to_bytes())?;
+    }
+    return Ok(());
+  }
+
+  // Returns how many ticks every player's input is in for.
+  pub fn get_confirmed(&self) -> u32 {
+    return self.inputs.iter().map(|inputs| inputs.len() as u32).min().unwrap_or(0);
+  }
+
+  // Returns the inputs every player has for a tick, if they're all in.
+  pub fn get_frame(&self, time: u32) -> Option<InputFrame> {
+    let mut frame: InputFrame = input::idle_frame();
+    for (id, inputs) in self.inputs.iter().enumerate() {
+      frame[id] = *inputs.get(time as usize)?;
+    }
+    return Some(frame);
+  }
+
+  // Returns whether every peer has every local input there is, so nobody is waiting on this one.
+  pub fn is_caught_up(&self) -> bool {
+    let sent: u32 = self.inputs[self.local as usize].len() as u32;
+    return self.peers().all(|peer| self.acks[peer as usize] >= sent);
+  }
+
+  pub fn into_transport(self) -> Box<dyn Transport> {
+    return self.transport;
+  }
+
+  pub fn get_local(&self) -> &PlayerId {
+    return &self.local;
+  }
+
+  pub fn get_delay(&self) -> &u32 {
+    return &self.delay;
+  }
+
+  pub fn get_inputs(&self) -> &Vec<Vec<PlayerInput>> {
+    return &self.inputs;
+  }
+}
@@ -0,0 +1,116 @@
+use crate::{
+  model::{
+    checksum,
+    event::WorldEvent,
+    input::{InputFrame, PlayerId, PlayerInput},
+    world::{World, WorldImpl}
+  },
+  net::{
+    exchange::InputExchange,
+    transport::Transport,
+    Session
+  }
+};
+
+// How many ticks late local inputs go in by default, which is 50 milliseconds.
+pub const DEFAULT_INPUT_DELAY: u32 = 3;
+
+/* A struct representing a round played in lockstep with peers over a network.
+ * Worlds always play out the same way from the same start and inputs, so every peer runs the whole
+ * world itself and only inputs go over the network. The world only ticks once every player's input
+ * for the tick is in, so everyone ticks through exactly the same frames, and waits if someone's
+ * inputs are running later than the input delay.
+ *
+ * Checksums are of the world at every tick so far; see InputExchange.
+ * Stalls are how many times the world couldn't tick because someone's inputs weren't in yet.
+ */
+pub struct LockstepSession {
+  world: WorldImpl,
+  exchange: InputExchange,
+  checksums: Vec<u64>,
+  stalls: u32
+}
+
+impl LockstepSession {
+  /* Starts a session from a world at the start of its round, which every peer has to start the same
+   * way. Every player in the world other than the local one is played by a peer.
+   */
+  pub fn new(
+    world: WorldImpl, local: PlayerId, delay: u32, transport: Box<dyn Transport>
+  ) -> Result<LockstepSession, String> {
+    let exchange: InputExchange = InputExchange::new(&world, local, delay, transport)?;
+    let start: u64 = checksum::checksum(&world);
+    return Ok(
+      LockstepSession {
+        world: world,
+        exchange: exchange,
+        checksums: vec!(start),
+        stalls: 0
+      }
+    );
+  }
+}
+
+impl Session for LockstepSession {
+  fn add_local_input(&mut self, input: &PlayerInput) -> bool {
+    return self.exchange.add_local_input(input, *self.world.get_time());
+  }
+
+  fn poll(&mut self) -> Result<(), String> {
+    return self.exchange.poll(&self.checksums);
+  }
+
+  fn advance(&mut self) -> Option<Vec<WorldEvent>> {
+    let frame: InputFrame = match self.exchange.get_frame(*self.world.get_time()) {
+      Some(frame) => frame,
+      None => {
+        self.stalls += 1;
+        return None;
+      }
+    };
+    let events: Vec<WorldEvent> = self.world.tick_mut(&frame);
+    self.checksums.push(checksum::checksum(&self.world));
+    return Some(events);
+  }
+
+  fn is_ready(&self) -> bool {
+    return self.exchange.get_confirmed() > *self.world.get_time();
+  }
+
+  fn is_confirmed(&self) -> bool {
+    return true;
+  }
+
+  fn is_caught_up(&self) -> bool {
+    return self.exchange.is_caught_up();
+  }
+
+  fn into_transport(self: Box<Self>) -> Box<dyn Transport> {
+    return self.exchange.into_transport();
+  }
+
+  fn get_world(&self) -> &WorldImpl {
+    return &self.world;
+  }
+
+  fn get_display_world(&self) -> WorldImpl {
+    return self.world.clone();
+  }
+
+  fn get_exchange(&self) -> &InputExchange {
+    return &self.exchange;
+  }
+
+  fn get_checksums(&self) -> &Vec<u64> {
+    return &self.checksums;
+  }
+
+  fn get_stalls(&self) -> &u32 {
+    return &self.stalls;
+  }
+
+  // Lockstep never has to correct itself.
+  fn get_rollbacks(&self) -> &u32 {
+    return &0;
+  }
+}
@@ -0,0 +1,323 @@
+use crate::{
+  model::{
+    event::WorldEvent,
+    input::{PlayerId, PlayerInput},
+    world::WorldImpl
+  },
+  net::{
+    exchange::InputExchange,
+    lockstep::LockstepSession,
+    rollback::RollbackSession,
+    transport::Transport
+  }
+};
+
+pub mod exchange;
+pub mod lockstep;
+pub mod packet;
+pub mod rollback;
+pub mod transport;
+
+/* A trait representing a round being played with peers over a network, whichever way the world is
+ * kept in step with theirs. Frontends poll a session every frame, put the local player's input in,
+ * and advance it however many ticks are due, drawing its display world afterwards.
+ */
+pub trait Session: Send {
+  /* Puts the local player's input in for the next tick it can go in for, returning whether it went
+   * in. If it didn't, the frontend should hang on to any presses for next time.
+   */
+  fn add_local_input(&mut self, input: &PlayerInput) -> bool;
+
+  /* Takes in everything that came in from peers and sends them what they need from this one,
+   * failing if the peers have desynced or gone quiet. This should be called every frame, even while
+   * the world is waiting on someone, or after the round is over until the peers have caught up.
+   */
+  fn poll(&mut self) -> Result<(), String>;
+
+  /* Ticks the world if it can, returning everything that happened during the tick, or nothing if
+   * it has to wait on someone's inputs.
+   */
+  fn advance(&mut self) -> Option<Vec<WorldEvent>>;
+
+  // Returns whether the world can tick right now.
+  fn is_ready(&self) -> bool;
+
+  /* Returns whether the world has only been ticked with inputs that are in, so nothing that comes
+   * in later can change it.
+   */
+  fn is_confirmed(&self) -> bool;
+
+  // Returns whether every peer has every local input there is, so nobody is waiting on this one.
+  fn is_caught_up(&self) -> bool;
+
+  // Ends the session, handing back its transport for the next round to use.
+  fn into_transport(self: Box<Self>) -> Box<dyn Transport>;
+
+  fn get_world(&self) -> &WorldImpl;
+
+  /* Returns the world as it should be drawn, which can be smoothed over so that corrections don't
+   * make anything jump.
+   */
+  fn get_display_world(&self) -> WorldImpl;
+
+  fn get_exchange(&self) -> &InputExchange;
+
+  // Returns the checksums of the world at every tick the session is sure of so far.
+  fn get_checksums(&self) -> &Vec<u64>;
+
+  // Returns how many times the world couldn't tick because it was waiting on someone.
+  fn get_stalls(&self) -> &u32;
+
+  // Returns how many times the world had to be corrected after inputs came in.
+  fn get_rollbacks(&self) -> &u32;
+}
+
+/* An enum representing the ways sessions can keep worlds in step.
+ * Lockstep waits for every input before ticking, so it plays late by the input delay and stalls
+ * whenever inputs are later than that. Rollback guesses at inputs that aren't in yet and corrects
+ * itself when they turn out different, so it only ever waits when it's guessed too far ahead.
+ */
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Netcode {
+  Lockstep,
+  Rollback
+}
+
+impl Netcode {
+  // Returns a netcode by its name, ignoring case.
+  pub fn from_name(name: &str) -> Result<Netcode, String> {
+    match name.to_lowercase().as_str() {
+      "lockstep" => return Ok(Netcode::Lockstep),
+      "rollback" => return Ok(Netcode::Rollback),
+      _ => return Err(format!("{} isn't a netcode; use lockstep or rollback.", name))
+    }
+  }
+
+  // Returns the input delay that works best with a netcode, in ticks.
+  pub fn default_delay(&self) -> u32 {
+    match self {
+      Netcode::Lockstep => return lockstep::DEFAULT_INPUT_DELAY,
+      Netcode::Rollback => return rollback::DEFAULT_INPUT_DELAY
+    }
+  }
+
+  // Starts a session with this netcode; see LockstepSession::new and RollbackSession::new.
+  pub fn start(
+    &self, world: WorldImpl, local: PlayerId, delay: u32, transport: Box<dyn Transport>
+  ) -> Result<Box<dyn Session>, String> {
+    match self {
+      Netcode::Lockstep => {
+        return Ok(Box::new(LockstepSession::new(world, local, delay, transport)?));
+      },
+      Netcode::Rollback => {
+        return Ok(Box::new(RollbackSession::new(world, local, delay, transport)?));
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    common::direction::{Direction, CARDINALS},
+    model::{
+      checksum,
+      input::{self, InputFrame},
+      rules::Rules,
+      stage::StageImpl,
+      state::WorldState,
+      world::World
+    },
+    net::{
+      packet::Packet,
+      rollback::MAX_PREDICTION,
+      transport::{ChannelTransport, Conditions, ShimTransport}
+    }
+  };
+  use std::{
+    thread,
+    time::{Duration, Instant}
+  };
+
+  const PLAYERS: usize = 2;
+  const TICKS: u32 = 240;
+  const DELAY: u32 = 2;
+
+  // How long peers are given to finish the round before they're taken to be stuck.
+  const TIME_LIMIT: Duration = Duration::from_secs(60);
+
+  fn start_world() -> WorldImpl {
+    return WorldImpl::start(Rules::default(), Box::new(StageImpl::classic()), PLAYERS, 0);
+  }
+
+  /* What a player presses on a tick, which is walking a different way every half a second and
+   * placing a bomb every so often. It only depends on the tick the input goes in for, so every
+   * session plays the same round whenever it gets round to putting its inputs in.
+   */
+  fn script(id: PlayerId, tick: u32) -> PlayerInput {
+    let direction: Direction = CARDINALS[((tick / 30 + id as u32) % 4) as usize];
+    return PlayerInput::new(Some(direction), tick % 50 == 25, false, false);
+  }
+
+  // Returns whether a round is over, either because it ended or because enough ticks were played.
+  fn is_over(world: &WorldImpl) -> bool {
+    return world.get_outcome().is_some() || *world.get_time() >= TICKS;
+  }
+
+  // Returns the checksums of the round played locally with the scripted inputs, at every tick.
+  fn play_locally() -> Vec<u64> {
+    let mut world: WorldImpl = start_world();
+    let mut checksums: Vec<u64> = vec!(checksum::checksum(&world));
+    while !is_over(&world) {
+      let time: u32 = *world.get_time();
+      let mut frame: InputFrame = input::idle_frame();
+      if time >= DELAY {
+        for (id, input) in frame.iter_mut().enumerate().take(PLAYERS) {
+          *input = script(id as PlayerId, time);
+        }
+      }
+      world.tick_mut(&frame);
+      checksums.push(checksum::checksum(&world));
+    }
+    return checksums;
+  }
+
+  // Connects every peer to the others over a network with some conditions.
+  fn connect(conditions: Conditions) -> Vec<Box<dyn Transport>> {
+    return ChannelTransport::connect(PLAYERS).into_iter().enumerate()
+      .map(|(id, transport)| {
+        let shim: ShimTransport = ShimTransport::new(Box::new(transport), conditions, id as u64);
+        return Box::new(shim) as Box<dyn Transport>;
+      })
+      .collect();
+  }
+
+  /* Plays the round with a session for each of the peers, which each put their scripted inputs in
+   * and tick as fast as they can, until every peer is sure of the whole round and every other peer
+   * has all of its inputs. Fails if any of them does, or if they never get there.
+   */
+  fn play(
+    netcode: Netcode, transports: Vec<Box<dyn Transport>>
+  ) -> Result<Vec<Box<dyn Session>>, String> {
+    let mut sessions: Vec<Box<dyn Session>> = transports.into_iter().enumerate()
+      .map(|(id, transport)| netcode.start(start_world(), id as PlayerId, DELAY, transport))
+      .collect::<Result<Vec<Box<dyn Session>>, String>>()?;
+    let start: Instant = Instant::now();
+    while start.elapsed() < TIME_LIMIT {
+      let mut done: bool = true;
+      for session in sessions.iter_mut() {
+        session.poll()?;
+        if !is_over(session.get_world()) {
+          let local: PlayerId = *session.get_exchange().get_local();
+          let tick: u32 = session.get_exchange().get_inputs()[local as usize].len() as u32;
+          session.add_local_input(&script(local, tick));
+          session.advance();
+        }
+        done &= is_over(session.get_world()) && session.is_confirmed() && session.is_caught_up();
+      }
+      if done {
+        return Ok(sessions);
+      }
+      thread::sleep(Duration::from_millis(1));
+    }
+    return Err(String::from("The peers never finished the round."));
+  }
+
+  #[test]
+  fn lockstep_peers_end_up_with_the_same_round() {
+    let conditions: Conditions =
+      Conditions::new(0.2, Duration::from_millis(10), Duration::from_millis(10)).unwrap();
+    let sessions: Vec<Box<dyn Session>> = play(Netcode::Lockstep, connect(conditions)).unwrap();
+    let checksums: Vec<u64> = play_locally();
+    for session in sessions.iter() {
+      assert_eq!(session.get_checksums(), &checksums);
+    }
+  }
+
+  #[test]
+  fn rollback_with_late_inputs_ends_up_like_lockstep() {
+    let conditions: Conditions =
+      Conditions::new(0.2, Duration::from_millis(10), Duration::from_millis(10)).unwrap();
+    let lockstep: Vec<Box<dyn Session>> = play(Netcode::Lockstep, connect(conditions)).unwrap();
+    let rollback: Vec<Box<dyn Session>> = play(Netcode::Rollback, connect(conditions)).unwrap();
+    for session in rollback.iter() {
+      assert_eq!(session.get_checksums(), lockstep[0].get_checksums());
+      assert_eq!(
+        WorldState::capture(session.get_world()), WorldState::capture(lockstep[0].get_world())
+      );
+    }
+    // Peers race ahead of each other's inputs, so they must have guessed wrong along the way.
+    assert!(rollback.iter().any(|session| *session.get_rollbacks() > 0));
+  }
+
+  #[test]
+  fn rollback_stalls_once_its_guessed_as_far_ahead_as_it_can() {
+    let mut sessions: Vec<Box<dyn Session>> = ChannelTransport::connect(PLAYERS).into_iter()
+      .enumerate()
+      .map(|(id, transport)| {
+        let world: WorldImpl = start_world();
+        return Netcode::Rollback.start(world, id as PlayerId, DELAY, Box::new(transport)).unwrap();
+      })
+      .collect();
+    // Player 1 never puts anything in, so only the ticks everyone starts out idle for are certain.
+    let tries: u32 = 2 * MAX_PREDICTION;
+    for _ in 0..tries {
+      sessions[0].poll().unwrap();
+      sessions[0].add_local_input(&PlayerInput::default());
+      sessions[0].advance();
+    }
+    assert_eq!(*sessions[0].get_world().get_time(), DELAY + MAX_PREDICTION);
+    assert_eq!(*sessions[0].get_stalls(), tries - (DELAY + MAX_PREDICTION));
+    assert!(!sessions[0].is_ready());
+
+    // Once player 1's inputs come in, it can carry on.
+    sessions[1].add_local_input(&PlayerInput::default());
+    sessions[1].poll().unwrap();
+    sessions[0].poll().unwrap();
+    assert!(sessions[0].is_ready());
+  }
+
+  /* A struct representing a transport that lies about the sender's checksums from a tick onwards,
+   * like a peer whose world has started playing out differently would send.
+   */
+  struct Desynced {
+    inner: Box<dyn Transport>,
+    from: u32
+  }
+
+  impl Transport for Desynced {
+    fn send(&mut self, peer: PlayerId, bytes: &[u8]) -> Result<(), String> {
+      let packet: Packet = Packet::from_bytes(bytes)?;
+      let (tick, checksum): (u32, u64) = *packet.get_checksum();
+      let checksum: u64 = match tick >= self.from {
+        true => checksum ^ 1,
+        false => checksum
+      };
+      let lie: Packet = Packet::new(
+        *packet.get_session(), *packet.get_sender(), *packet.get_ack(), (tick, checksum),
+        *packet.get_start(), packet.get_inputs().clone()
+      );
+      return self.inner.send(peer, &lie.to_bytes());
+    }
+
+    fn receive(&mut self) -> Result<Option<Vec<u8>>, String> {
+      return self.inner.receive();
+    }
+  }
+
+  #[test]
+  fn desyncs_are_caught() {
+    for netcode in [Netcode::Lockstep, Netcode::Rollback] {
+      let mut transports: Vec<Box<dyn Transport>> = connect(Conditions::default());
+      let honest: Box<dyn Transport> = transports.remove(1);
+      transports.push(Box::new(Desynced { inner: honest, from: 60 }));
+      let error: String = play(netcode, transports).err().expect("Nobody noticed the desync");
+      let tick: u32 = error.strip_prefix("Desynced from player 1 at tick ")
+        .and_then(|rest| rest.strip_suffix('.'))
+        .and_then(|tick| tick.parse().ok())
+        .unwrap_or_else(|| panic!("{:?} isn't about the desync", error));
+      assert!(tick >= 60, "{:?} found a desync at tick {}", netcode, tick);
+    }
+  }
+}
@@ -0,0 +1,182 @@
+use crate::model::input::{PlayerId, PlayerInput, MAX_PLAYERS};
+
+// What every packet starts with, so that anything else that turns up on the port gets ignored.
+const MAGIC: &[u8; 4] = b"OBNP";
+
+/* The version of the packet format this build speaks. It has to go up whenever the format changes,
+ * and whenever the same inputs stop playing out the same way, since peers on different versions
+ * would only end up desyncing.
+ */
+pub const NET_VERSION: u16 = 1;
+
+// The most inputs a single packet carries.
+pub const MAX_INPUTS: usize = 64;
+
+// How long a packet is before its inputs.
+const HEADER_LENGTH: usize = 4 + 2 + 8 + 1 + 4 + 4 + 8 + 4 + 1;
+
+/* A struct representing a packet one peer sends another every frame.
+ *
+ * Session is the checksum of the world the sender's round started from, so that packets from
+ * another round, or from a peer started with different settings, can be told apart.
+ * Ack is how many of the receiver's inputs the sender has, so the receiver knows where to start
+ * sending from.
+ * Checksum is the sender's checksum of its world at a tick, for the receiver to compare against
+ * its own once it gets there.
+ * Inputs are the sender's own inputs for the ticks from start onwards. Everything the receiver
+ * hasn't acked yet is sent again in every packet, so losing a packet only costs a frame.
+ *
+ * Everything is little endian, and inputs are packed into a byte each; see PlayerInput::to_byte.
+ */
+#[derive(Clone, PartialEq, Debug)]
+pub struct Packet {
+  session: u64,
+  sender: PlayerId,
+  ack: u32,
+  checksum: (u32, u64),
+  start: u32,
+  inputs: Vec<PlayerInput>
+}
+
+impl Packet {
+  pub fn new(
+    session: u64, sender: PlayerId, ack: u32, checksum: (u32, u64), start: u32,
+    inputs: Vec<PlayerInput>
+  ) -> Packet {
+    return Packet {
+      session: session,
+      sender: sender,
+      ack: ack,
+      checksum: checksum,
+      start: start,
+      inputs: inputs
+    }
+  }
+
+  pub fn to_bytes(&self) -> Vec<u8> {
+    let mut bytes: Vec<u8> = MAGIC.to_vec();
+    bytes.extend_from_slice(&NET_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&self.session.to_le_bytes());
+    bytes.push(self.sender);
+    bytes.extend_from_slice(&self.ack.to_le_bytes());
+    bytes.extend_from_slice(&self.checksum.0.to_le_bytes());
+    bytes.extend_from_slice(&self.checksum.1.to_le_bytes());
+    bytes.extend_from_slice(&self.start.to_le_bytes());
+    bytes.push(self.inputs.len().min(MAX_INPUTS) as u8);
+    bytes.extend(self.inputs.iter().take(MAX_INPUTS).map(PlayerInput::to_byte));
+    return bytes;
+  }
+
+  // Reads a packet, failing on anything that isn't one or that came from another version.
+  pub fn from_bytes(bytes: &[u8]) -> Result<Packet, String> {
+    if bytes.len() < HEADER_LENGTH || &bytes[..MAGIC.len()] != MAGIC {
+      return Err(String::from("That isn't a packet."));
+    }
+    let read = |at: usize, length: usize| {
+      let mut value: [u8; 8] = [0; 8];
+      value[..length].copy_from_slice(&bytes[at..at + length]);
+      return u64::from_le_bytes(value);
+    };
+    let version: u16 = read(4, 2) as u16;
+    if version != NET_VERSION {
+      return Err(
+        format!("Packet is version {}, but only version {} can be read.", version, NET_VERSION)
+      );
+    }
+    let sender: PlayerId = bytes[14];
+    if sender as usize >= MAX_PLAYERS {
+      return Err(format!("Packet is from player {}, who can't exist.", sender));
+    }
+    let count: usize = bytes[HEADER_LENGTH - 1] as usize;
+    if bytes.len() != HEADER_LENGTH + count {
+      return Err(format!("Packet should have {} inputs, but doesn't.", count));
+    }
+    let inputs: Vec<PlayerInput> = bytes[HEADER_LENGTH..].iter()
+      .map(|byte| PlayerInput::from_byte(*byte))
+      .collect::<Result<Vec<PlayerInput>, String>>()?;
+    return Ok(
+      Packet {
+        session: read(6, 8),
+        sender: sender,
+        ack: read(15, 4) as u32,
+        checksum: (read(19, 4) as u32, read(23, 8)),
+        start: read(31, 4) as u32,
+        inputs: inputs
+      }
+    );
+  }
+
+  pub fn get_session(&self) -> &u64 {
+    return &self.session;
+  }
+
+  pub fn get_sender(&self) -> &PlayerId {
+    return &self.sender;
+  }
+
+  pub fn get_ack(&self) -> &u32 {
+    return &self.ack;
+  }
+
+  pub fn get_checksum(&self) -> &(u32, u64) {
+    return &self.checksum;
+  }
+
+  pub fn get_start(&self) -> &u32 {
+    return &self.start;
+  }
+
+  pub fn get_inputs(&self) -> &Vec<PlayerInput> {
+    return &self.inputs;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::common::direction::Direction;
+
+  fn make_packet(inputs: usize) -> Packet {
+    let input: PlayerInput = PlayerInput::new(Some(Direction::North), true, false, false);
+    return Packet::new(0x0123456789abcdef, 3, 70000, (12, u64::MAX), 42, vec!(input; inputs));
+  }
+
+  #[test]
+  fn packets_survive_being_sent() {
+    for inputs in [0, 1, MAX_INPUTS] {
+      let packet: Packet = make_packet(inputs);
+      let bytes: Vec<u8> = packet.to_bytes();
+      assert_eq!(bytes.len(), HEADER_LENGTH + inputs);
+      assert_eq!(Packet::from_bytes(&bytes), Ok(packet));
+    }
+  }
+
+  #[test]
+  fn only_the_first_inputs_that_fit_are_sent() {
+    let bytes: Vec<u8> = make_packet(MAX_INPUTS + 10).to_bytes();
+    assert_eq!(Packet::from_bytes(&bytes), Ok(make_packet(MAX_INPUTS)));
+  }
+
+  #[test]
+  fn anything_else_is_rejected() {
+    let bytes: Vec<u8> = make_packet(4).to_bytes();
+    assert!(Packet::from_bytes(&bytes[..HEADER_LENGTH - 1]).is_err());
+    assert!(Packet::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+
+    let mut wrong_magic: Vec<u8> = bytes.clone();
+    wrong_magic[0] = b'X';
+    assert!(Packet::from_bytes(&wrong_magic).is_err());
+
+    let mut wrong_version: Vec<u8> = bytes.clone();
+    wrong_version[4] = wrong_version[4].wrapping_add(1);
+    assert!(Packet::from_bytes(&wrong_version).is_err());
+
+    let mut wrong_sender: Vec<u8> = bytes.clone();
+    wrong_sender[14] = MAX_PLAYERS as u8;
+    assert!(Packet::from_bytes(&wrong_sender).is_err());
+
+    let mut wrong_input: Vec<u8> = bytes;
+    wrong_input[HEADER_LENGTH] = 0x0f;
+    assert!(Packet::from_bytes(&wrong_input).is_err());
+  }
+}
@@ -0,0 +1,246 @@
+use crate::{
+  model::{
+    checksum,
+    event::WorldEvent,
+    input::{self, InputFrame, PlayerId, PlayerInput},
+    player::Player,
+    position::SUBTILE,
+    world::{World, WorldImpl}
+  },
+  net::{
+    exchange::InputExchange,
+    transport::Transport,
+    Session
+  }
+};
+use std::collections::VecDeque;
+
+/* How many ticks late local inputs go in by default. Rollback hides latency by itself, so this is
+ * only enough to save correcting for the very smallest delays.
+ */
+pub const DEFAULT_INPUT_DELAY: u32 = 1;
+
+/* How many ticks the world can be guessed ahead of the last tick every input is in for, which is
+ * also the furthest back it ever has to go to correct itself.
+ */
+pub const MAX_PREDICTION: u32 = 8;
+
+// How much of a correction is left to smooth over after each tick, as a fraction.
+const SMOOTHING: (i32, i32) = (3, 4);
+
+// The furthest correction that gets smoothed over, in subtiles. Players just jump any further.
+const MAX_SMOOTHING: i32 = 2 * SUBTILE;
+
+/* A struct representing a round played with peers over a network, rolling back when it guesses
+ * wrong.
+ * Like lockstep, every peer runs the whole world itself and only inputs go over the network. But
+ * rather than waiting for inputs that aren't in yet, the world guesses them and carries on: players
+ * are guessed to keep holding whichever direction they last were, without pressing anything. When
+ * someone's inputs come in different to the guess, the world is put back the way it was at the
+ * first tick it guessed wrong and played forward again with what really happened.
+ *
+ * History is a snapshot of the world at every tick from the last one every input is in for, with
+ * the inputs it was ticked with, so the world at time t is history[t - start] for however far back
+ * the history starts. Nothing can ever change before it starts, so rolling back never has to go
+ * further. Checked is how many of each player's inputs have been compared with what was guessed.
+ * Offsets are how far from where they are each player should be drawn, by player ID, so that they
+ * slide to where a correction puts them rather than jumping there. They shrink every tick.
+ * Checksums are of the world at every tick every input is in for; see InputExchange.
+ * Stalls are how many times the world couldn't tick because it was as far ahead as it can guess.
+ * Rollbacks are how many times the world was corrected.
+ */
+pub struct RollbackSession {
+  world: WorldImpl,
+  exchange: InputExchange,
+  history: VecDeque<(WorldImpl, InputFrame)>,
+  checked: Vec<u32>,
+  offsets: Vec<(i32, i32)>,
+  checksums: Vec<u64>,
+  stalls: u32,
+  rollbacks: u32
+}
+
+impl RollbackSession {
+  /* Starts a session from a world at the start of its round, which every peer has to start the same
+   * way. Every player in the world other than the local one is played by a peer.
+   */
+  pub fn new(
+    world: WorldImpl, local: PlayerId, delay: u32, transport: Box<dyn Transport>
+  ) -> Result<RollbackSession, String> {
+    let exchange: InputExchange = InputExchange::new(&world, local, delay, transport)?;
+    let players: usize = world.get_players().len();
+    let start: u64 = checksum::checksum(&world);
+    return Ok(
+      RollbackSession {
+        world: world,
+        exchange: exchange,
+        history: VecDeque::new(),
+        checked: vec!(0; players),
+        offsets: vec!((0, 0); players),
+        checksums: vec!(start),
+        stalls: 0,
+        rollbacks: 0
+      }
+    );
+  }
+
+  // Returns the tick the history starts at.
+  fn get_history_start(&self) -> u32 {
+    return self.world.get_time() - self.history.len() as u32;
+  }
+
+  // Returns a player's input for a tick if it's in, or a guess at it otherwise.
+  fn predict(&self, id: usize, time: u32) -> PlayerInput {
+    let inputs: &Vec<PlayerInput> = &self.exchange.get_inputs()[id];
+    match inputs.get(time as usize) {
+      Some(input) => return *input,
+      None => return inputs.last().map(PlayerInput::held).unwrap_or_default()
+    }
+  }
+
+  // Ticks the world with whatever inputs there are for the tick, keeping a snapshot of it first.
+  fn simulate(&mut self) -> Vec<WorldEvent> {
+    let time: u32 = *self.world.get_time();
+    let mut frame: InputFrame = input::idle_frame();
+    for (id, input) in frame.iter_mut().enumerate().take(self.offsets.len()) {
+      *input = self.predict(id, time);
+    }
+    self.history.push_back((self.world.clone(), frame));
+    return self.world.tick_mut(&frame);
+  }
+
+  // Returns the earliest tick that was guessed wrong, going by inputs that came in since last time.
+  fn find_misprediction(&mut self) -> Option<u32> {
+    let start: u32 = self.get_history_start();
+    let time: u32 = *self.world.get_time();
+    let mut earliest: Option<u32> = None;
+    for id in 0..self.checked.len() {
+      let inputs: &Vec<PlayerInput> = &self.exchange.get_inputs()[id];
+      let known: u32 = (inputs.len() as u32).min(time);
+      let wrong: Option<u32> = (self.checked[id]..known)
+        .find(|tick| self.history[(tick - start) as usize].1[id] != inputs[*tick as usize]);
+      if let Some(tick) = wrong {
+        earliest = Some(earliest.map_or(tick, |earliest| earliest.min(tick)));
+      }
+      self.checked[id] = known;
+    }
+    return earliest;
+  }
+
+  /* Puts the world back the way it was at a tick and plays it forward again to where it was, with
+   * what's known now. If the round really ended before then, it stops there instead, since peers
+   * stop putting inputs in once the round is over. Nothing that happens on the way is reported,
+   * since it's all been reported once already, going by the guesses.
+   */
+  fn roll_back(&mut self, to: u32) {
+    let displayed: Vec<(i32, i32)> = self.world.get_players().iter().zip(self.offsets.iter())
+      .map(|(player, offset)| *player.shift(*offset).get_position())
+      .collect();
+    let time: u32 = *self.world.get_time();
+    let index: usize = (to - self.get_history_start()) as usize;
+    self.world = self.history.drain(index..).next().unwrap().0;
+    while *self.world.get_time() < time && self.world.get_outcome().is_none() {
+      self.simulate();
+    }
+    self.rollbacks += 1;
+
+    for (offset, (player, displayed)) in
+      self.offsets.iter_mut().zip(self.world.get_players().iter().zip(displayed.iter()))
+    {
+      let position: &(i32, i32) = player.get_position();
+      let correction: (i32, i32) = (displayed.0 - position.0, displayed.1 - position.1);
+      *offset = match correction.0.abs().max(correction.1.abs()) <= MAX_SMOOTHING {
+        true => correction,
+        false => (0, 0)
+      };
+    }
+  }
+}
+
+impl Session for RollbackSession {
+  fn add_local_input(&mut self, input: &PlayerInput) -> bool {
+    return self.exchange.add_local_input(input, *self.world.get_time());
+  }
+
+  /* Corrects the world if any inputs that came in turned out different to what was guessed, then
+   * works out checksums for every tick that's certain now, and lets go of snapshots from before
+   * the last of them.
+   */
+  fn poll(&mut self) -> Result<(), String> {
+    self.exchange.poll(&self.checksums)?;
+    if let Some(tick) = self.find_misprediction() {
+      self.roll_back(tick);
+    }
+    let time: u32 = *self.world.get_time();
+    let confirmed: u32 = self.exchange.get_confirmed().min(time);
+    let start: u32 = self.get_history_start();
+    while self.checksums.len() as u32 <= confirmed {
+      let tick: u32 = self.checksums.len() as u32;
+      let world: &WorldImpl = match tick == time {
+        true => &self.world,
+        false => &self.history[(tick - start) as usize].0
+      };
+      self.checksums.push(checksum::checksum(world));
+    }
+    self.history.drain(..(confirmed - start) as usize);
+    return Ok(());
+  }
+
+  fn advance(&mut self) -> Option<Vec<WorldEvent>> {
+    if !self.is_ready() {
+      self.stalls += 1;
+      return None;
+    }
+    let events: Vec<WorldEvent> = self.simulate();
+    for offset in self.offsets.iter_mut() {
+      *offset = (offset.0 * SMOOTHING.0 / SMOOTHING.1, offset.1 * SMOOTHING.0 / SMOOTHING.1);
+    }
+    return Some(events);
+  }
+
+  // The world can tick as long as the local input is in, and it isn't too far ahead of the rest.
+  fn is_ready(&self) -> bool {
+    let time: u32 = *self.world.get_time();
+    let local: &Vec<PlayerInput> = &self.exchange.get_inputs()[*self.exchange.get_local() as usize];
+    return local.len() as u32 > time && time < self.exchange.get_confirmed() + MAX_PREDICTION;
+  }
+
+  fn is_confirmed(&self) -> bool {
+    return self.checksums.len() as u32 > *self.world.get_time();
+  }
+
+  fn is_caught_up(&self) -> bool {
+    return self.exchange.is_caught_up();
+  }
+
+  fn into_transport(self: Box<Self>) -> Box<dyn Transport> {
+    return self.exchange.into_transport();
+  }
+
+  fn get_world(&self) -> &WorldImpl {
+    return &self.world;
+  }
+
+  fn get_display_world(&self) -> WorldImpl {
+    let players: Vec<Player> = self.world.get_players().iter().zip(self.offsets.iter())
+      .map(|(player, offset)| player.shift(*offset))
+      .collect();
+    return self.world.set_players(players);
+  }
+
+  fn get_exchange(&self) -> &InputExchange {
+    return &self.exchange;
+  }
+
+  fn get_checksums(&self) -> &Vec<u64> {
+    return &self.checksums;
+  }
+
+  fn get_stalls(&self) -> &u32 {
+    return &self.stalls;
+  }
+
+  fn get_rollbacks(&self) -> &u32 {
+    return &self.rollbacks;
+  }
+}
@@ -0,0 +1,204 @@
+use crate::model::{input::PlayerId, rng::Rng};
+use std::{
+  io::ErrorKind,
+  net::{SocketAddr, UdpSocket},
+  sync::mpsc::{self, Receiver, Sender, TryRecvError},
+  time::{Duration, Instant}
+};
+
+// The biggest packet a transport will take in; anything bigger isn't ours.
+const MAX_PACKET_SIZE: usize = 1024;
+
+/* A trait representing a way of getting packets to and from the other peers in a session.
+ * Packets can go missing, turn up late or turn up out of order, so sessions have to cope with all
+ * of that. Receiving never blocks.
+ */
+pub trait Transport: Send {
+  // Sends a packet to the peer playing as a player, doing nothing if there isn't one.
+  fn send(&mut self, peer: PlayerId, bytes: &[u8]) -> Result<(), String>;
+
+  // Returns the next packet that came in from anyone, if there is one.
+  fn receive(&mut self) -> Result<Option<Vec<u8>>, String>;
+}
+
+/* A struct representing a transport over UDP.
+ * Peers are the address of every player's peer, indexed by player ID, including this one's own.
+ */
+pub struct UdpTransport {
+  socket: UdpSocket,
+  peers: Vec<SocketAddr>
+}
+
+impl UdpTransport {
+  // Binds to the address of the local player's peer, out of the peers.
+  pub fn new(local: PlayerId, peers: Vec<SocketAddr>) -> Result<UdpTransport, String> {
+    let address: &SocketAddr = peers.get(local as usize)
+      .ok_or_else(|| format!("There's no address for player {}.", local))?;
+    let socket: UdpSocket = UdpSocket::bind(address)
+      .map_err(|e| format!("Couldn't listen on {}: {}", address, e))?;
+    socket.set_nonblocking(true).map_err(|e| e.to_string())?;
+    return Ok(UdpTransport { socket: socket, peers: peers });
+  }
+}
+
+impl Transport for UdpTransport {
+  fn send(&mut self, peer: PlayerId, bytes: &[u8]) -> Result<(), String> {
+    let address: SocketAddr = match self.peers.get(peer as usize) {
+      Some(address) => *address,
+      None => return Ok(())
+    };
+    match self.socket.send_to(bytes, address) {
+      Ok(_) => return Ok(()),
+      // A peer that isn't listening yet is just like one whose packets got lost.
+      Err(e) if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::ConnectionRefused => {
+        return Ok(());
+      },
+      Err(e) => return Err(format!("Couldn't send to {}: {}", address, e))
+    }
+  }
+
+  fn receive(&mut self) -> Result<Option<Vec<u8>>, String> {
+    let mut buffer: [u8; MAX_PACKET_SIZE] = [0; MAX_PACKET_SIZE];
+    loop {
+      match self.socket.recv_from(&mut buffer) {
+        Ok((length, _)) => return Ok(Some(buffer[..length].to_vec())),
+        Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(None),
+        // Some platforms report peers that weren't listening when they're next read from.
+        Err(e) if e.kind() == ErrorKind::ConnectionReset
+          || e.kind() == ErrorKind::ConnectionRefused => continue,
+        Err(e) => return Err(format!("Couldn't receive: {}", e))
+      }
+    }
+  }
+}
+
+/* A struct representing a transport between peers in the same process, which never loses
+ * anything, for playing sessions against each other without a network. Peers are where to send to
+ * every player's peer, indexed by player ID.
+ */
+pub struct ChannelTransport {
+  peers: Vec<Sender<Vec<u8>>>,
+  receiver: Receiver<Vec<u8>>
+}
+
+impl ChannelTransport {
+  // Connects a number of peers to each other, returning the transport of each one by player ID.
+  pub fn connect(count: usize) -> Vec<ChannelTransport> {
+    let mut senders: Vec<Sender<Vec<u8>>> = vec!();
+    let mut receivers: Vec<Receiver<Vec<u8>>> = vec!();
+    for _ in 0..count {
+      let (sender, receiver): (Sender<Vec<u8>>, Receiver<Vec<u8>>) = mpsc::channel();
+      senders.push(sender);
+      receivers.push(receiver);
+    }
+    return receivers.into_iter()
+      .map(|receiver| ChannelTransport { peers: senders.clone(), receiver: receiver })
+      .collect();
+  }
+}
+
+impl Transport for ChannelTransport {
+  fn send(&mut self, peer: PlayerId, bytes: &[u8]) -> Result<(), String> {
+    // A peer that's gone is just like one whose packets got lost.
+    if let Some(sender) = self.peers.get(peer as usize) {
+      let _ = sender.send(bytes.to_vec());
+    }
+    return Ok(());
+  }
+
+  fn receive(&mut self) -> Result<Option<Vec<u8>>, String> {
+    match self.receiver.try_recv() {
+      Ok(bytes) => return Ok(Some(bytes)),
+      Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => return Ok(None)
+    }
+  }
+}
+
+/* A struct representing how bad a network is, for trying netplay out on one machine.
+ * Loss is the chance of any packet going missing, from 0 to 1.
+ * Latency is how long packets take to get anywhere, one way, and jitter is how much longer than
+ * that any packet can randomly take. Packets that take longer can overtake each other.
+ */
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct Conditions {
+  loss: f32,
+  latency: Duration,
+  jitter: Duration
+}
+
+impl Conditions {
+  pub fn new(loss: f32, latency: Duration, jitter: Duration) -> Result<Conditions, String> {
+    if !(0.0..=1.0).contains(&loss) {
+      return Err(format!("Loss has to be between 0 and 1, not {}.", loss));
+    }
+    return Ok(Conditions { loss: loss, latency: latency, jitter: jitter });
+  }
+
+  pub fn get_loss(&self) -> &f32 {
+    return &self.loss;
+  }
+
+  pub fn get_latency(&self) -> &Duration {
+    return &self.latency;
+  }
+
+  pub fn get_jitter(&self) -> &Duration {
+    return &self.jitter;
+  }
+}
+
+/* A struct representing a transport that makes another one worse on purpose, dropping and delaying
+ * what gets sent through it according to some conditions.
+ * In flight is every packet that's been delayed, with when it's due to actually be sent. They only
+ * go out when the shim is next sent or received through, which sessions do every frame.
+ */
+pub struct ShimTransport {
+  inner: Box<dyn Transport>,
+  conditions: Conditions,
+  rng: Rng,
+  in_flight: Vec<(Instant, PlayerId, Vec<u8>)>
+}
+
+impl ShimTransport {
+  pub fn new(inner: Box<dyn Transport>, conditions: Conditions, seed: u64) -> ShimTransport {
+    return ShimTransport {
+      inner: inner,
+      conditions: conditions,
+      rng: Rng::new(seed),
+      in_flight: vec!()
+    }
+  }
+
+  // Sends everything that's due.
+  fn flush(&mut self) -> Result<(), String> {
+    let now: Instant = Instant::now();
+    let mut index: usize = 0;
+    while index < self.in_flight.len() {
+      if self.in_flight[index].0 <= now {
+        let (_, peer, bytes): (Instant, PlayerId, Vec<u8>) = self.in_flight.swap_remove(index);
+        self.inner.send(peer, &bytes)?;
+      } else {
+        index += 1;
+      }
+    }
+    return Ok(());
+  }
+}
+
+impl Transport for ShimTransport {
+  fn send(&mut self, peer: PlayerId, bytes: &[u8]) -> Result<(), String> {
+    let roll: f32 = self.rng.below(1 << 24) as f32 / (1 << 24) as f32;
+    if roll >= self.conditions.loss {
+      let jitter: u32 = self.conditions.jitter.as_micros() as u32;
+      let delay: Duration = self.conditions.latency
+        + Duration::from_micros(self.rng.below(jitter + 1) as u64);
+      self.in_flight.push((Instant::now() + delay, peer, bytes.to_vec()));
+    }
+    return self.flush();
+  }
+
+  fn receive(&mut self) -> Result<Option<Vec<u8>>, String> {
+    self.flush()?;
+    return self.inner.receive();
+  }
+}
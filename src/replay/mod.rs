@@ -0,0 +1,487 @@
+use crate::{
+  model::{
+    checksum,
+    input::{self, InputFrame, PlayerInput, MAX_PLAYERS},
+    rules::Rules,
+    stage::{self, Stage, StageImpl},
+    timestep::TICK_RATE,
+    world::{World, WorldImpl}
+  }
+};
+use std::{fs, path::Path};
+
+pub mod viewer;
+
+// What every replay file starts with, so that anything else gets turned away up front.
+const MAGIC: &[u8; 4] = b"OBRP";
+
+/* The version of the replay format this build reads and writes. It has to go up whenever the
+ * format changes, and whenever the same inputs stop playing out the same way, since replays from
+ * before then wouldn't play back as they were recorded anymore.
+ */
+pub const REPLAY_VERSION: u16 = 4;
+
+// How often a replay keeps a checksum of the round, in ticks.
+pub const CHECKSUM_INTERVAL: u32 = TICK_RATE;
+
+// The extension replay files are saved with.
+pub const REPLAY_EXTENSION: &str = "obr";
+
+
+/* A struct representing a recording of a round: everything the round was started with, and what
+ * every player did on every tick after that. Worlds always play out the same way from the same
+ * start and inputs, so that's all it takes to play a round back exactly as it went.
+ *
+ * Stage is the layout the round started on; see StageImpl::from_layout.
+ * Frames are the inputs for each tick, so frame n is what took the world from time n to n + 1.
+ * Checksums are of the world every checksum interval ticks, so checksum n is of the world at time
+ * n times the interval; see checksum::checksum. End is the checksum of the world after the last
+ * frame, once the recording is finished. Playing the replay back should end up with the same ones,
+ * and the first one that doesn't match says roughly when it started playing out differently.
+ *
+ * Replays are saved in a compact binary format. After the magic bytes and the version, it has the
+ * seed, the player count, the stage layout and the rules (in RON), then the frames, then the
+ * checksums, then the end.
+ * Inputs are packed into a byte each (see PlayerInput::to_byte), and only players in the round
+ * get one. Frames are run-length encoded, since players mostly hold the same direction for a while.
+ */
+#[derive(Clone, PartialEq, Debug)]
+pub struct Replay {
+  rules: Rules,
+  stage: String,
+  player_count: usize,
+  seed: u64,
+  frames: Vec<InputFrame>,
+  checksums: Vec<u64>,
+  end: Option<u64>
+}
+
+// Writes a number in as few bytes as it fits in, 7 bits at a time.
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+  while value >= 0x80 {
+    bytes.push(value as u8 | 0x80);
+    value >>= 7;
+  }
+  bytes.push(value as u8);
+}
+
+fn write_string(bytes: &mut Vec<u8>, string: &str) {
+  write_varint(bytes, string.len() as u64);
+  bytes.extend_from_slice(string.as_bytes());
+}
+
+// A struct representing a read through the bytes of a replay file.
+struct Reader<'a> {
+  bytes: &'a [u8],
+  at: usize
+}
+
+impl<'a> Reader<'a> {
+  fn take(&mut self, count: usize) -> Result<&'a [u8], String> {
+    if self.bytes.len() - self.at < count {
+      return Err(String::from("Replay ends too early."));
+    }
+    self.at += count;
+    return Ok(&self.bytes[self.at - count..self.at]);
+  }
+
+  fn read_u8(&mut self) -> Result<u8, String> {
+    return Ok(self.take(1)?[0]);
+  }
+
+  fn read_u16(&mut self) -> Result<u16, String> {
+    let mut bytes: [u8; 2] = [0; 2];
+    bytes.copy_from_slice(self.take(2)?);
+    return Ok(u16::from_le_bytes(bytes));
+  }
+
+  fn read_u64(&mut self) -> Result<u64, String> {
+    let mut bytes: [u8; 8] = [0; 8];
+    bytes.copy_from_slice(self.take(8)?);
+    return Ok(u64::from_le_bytes(bytes));
+  }
+
+  fn read_varint(&mut self) -> Result<u64, String> {
+    let mut value: u64 = 0;
+    for shift in (0..64).step_by(7) {
+      let byte: u8 = self.read_u8()?;
+      value |= ((byte & 0x7f) as u64) << shift;
+      if byte & 0x80 == 0 {
+        return Ok(value);
+      }
+    }
+    return Err(String::from("Replay has a number that's too long."));
+  }
+
+  fn read_string(&mut self) -> Result<String, String> {
+    let length: usize = self.read_varint()? as usize;
+    return String::from_utf8(self.take(length)?.to_vec())
+      .map_err(|_| String::from("Replay has text that isn't UTF-8."));
+  }
+}
+
+impl Replay {
+  /* Starts recording a round from its world, which has to be at the very start of the round.
+   * Nothing has been drawn from a world's random number generator before its first tick, so its
+   * state is still the seed the round was started with.
+   */
+  pub fn new(world: &dyn World) -> Result<Replay, String> {
+    if *world.get_time() != 0 {
+      return Err(String::from("Replays have to be started at the start of a round."));
+    }
+    return Ok(
+      Replay {
+        rules: *world.get_rules(),
+        stage: stage::to_layout(world.get_stage()),
+        player_count: world.get_players().len(),
+        seed: *world.get_rng().get_state(),
+        frames: vec!(),
+        checksums: vec!(),
+        end: None
+      }
+    );
+  }
+
+  /* Adds the inputs of the next tick to the recording, along with the world they're about to tick,
+   * which should be the world after every frame recorded so far.
+   */
+  pub fn record(&mut self, world: &dyn World, frame: &InputFrame) {
+    if world.get_time().is_multiple_of(CHECKSUM_INTERVAL) {
+      self.checksums.push(checksum::checksum(world));
+    }
+    self.frames.push(*frame);
+  }
+
+  // Finishes the recording with the world it ended on, after the last of its frames.
+  pub fn finish(&mut self, world: &dyn World) {
+    self.end = Some(checksum::checksum(world));
+  }
+
+  // Starts the round over again, as it was before the first frame.
+  pub fn start(&self) -> Result<WorldImpl, String> {
+    let stage: StageImpl = StageImpl::from_layout(&self.stage)?;
+    if self.player_count > stage.get_spawns().len() {
+      return Err(
+        format!(
+          "Replay has {} players, but its stage only fits {}.",
+          self.player_count, stage.get_spawns().len()
+        )
+      );
+    }
+    return Ok(WorldImpl::start(self.rules, Box::new(stage), self.player_count, self.seed));
+  }
+
+  /* Plays the whole round back, returning the world it ended on. Fails at the first checksum that
+   * doesn't match, saying which ticks it started playing out differently between, which means
+   * something about how worlds play out has changed since it was recorded.
+   */
+  pub fn verify(&self) -> Result<WorldImpl, String> {
+    let mut world: WorldImpl = self.start()?;
+    let mut checksums = self.checksums.iter();
+    // The last tick the world was known to be the same at.
+    let mut matched: u32 = 0;
+    let mut check = |world: &WorldImpl, expected: Option<&u64>| {
+      if let Some(expected) = expected {
+        if *expected != checksum::checksum(world) {
+          return Err(
+            format!(
+              "Replay played back differently, somewhere between tick {} and tick {}.",
+              matched, world.get_time()
+            )
+          );
+        }
+        matched = *world.get_time();
+      }
+      return Ok(());
+    };
+    for frame in self.frames.iter() {
+      if world.get_time().is_multiple_of(CHECKSUM_INTERVAL) {
+        check(&world, checksums.next())?;
+      }
+      world.tick_mut(frame);
+    }
+    check(&world, self.end.as_ref())?;
+    return Ok(world);
+  }
+
+  pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+    let rules: String = ron::to_string(&self.rules).map_err(|e| e.to_string())?;
+    let mut bytes: Vec<u8> = MAGIC.to_vec();
+    bytes.extend_from_slice(&REPLAY_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&self.seed.to_le_bytes());
+    bytes.push(self.player_count as u8);
+    write_string(&mut bytes, &self.stage);
+    write_string(&mut bytes, &rules);
+
+    write_varint(&mut bytes, self.frames.len() as u64);
+    let mut frames = self.frames.iter().peekable();
+    while let Some(frame) = frames.next() {
+      let mut run: u64 = 1;
+      while frames.next_if_eq(&frame).is_some() {
+        run += 1;
+      }
+      write_varint(&mut bytes, run);
+      bytes.extend(frame.iter().take(self.player_count).map(PlayerInput::to_byte));
+    }
+
+    write_varint(&mut bytes, self.checksums.len() as u64);
+    for checksum in self.checksums.iter() {
+      bytes.extend_from_slice(&checksum.to_le_bytes());
+    }
+    match self.end {
+      Some(end) => {
+        bytes.push(1);
+        bytes.extend_from_slice(&end.to_le_bytes());
+      },
+      None => bytes.push(0)
+    }
+    return Ok(bytes);
+  }
+
+  pub fn from_bytes(bytes: &[u8]) -> Result<Replay, String> {
+    let mut reader: Reader = Reader { bytes: bytes, at: 0 };
+    if reader.take(MAGIC.len()).ok() != Some(&MAGIC[..]) {
+      return Err(String::from("That isn't a replay."));
+    }
+    let version: u16 = reader.read_u16()?;
+    if version != REPLAY_VERSION {
+      return Err(
+        format!(
+          "Replay is version {}, but only version {} can be played back.",
+          version, REPLAY_VERSION
+        )
+      );
+    }
+    let seed: u64 = reader.read_u64()?;
+    let player_count: usize = reader.read_u8()? as usize;
+    if player_count > MAX_PLAYERS {
+      return Err(format!("Replay has {} players, which is too many.", player_count));
+    }
+    let stage: String = reader.read_string()?;
+    let rules: Rules = Rules::from_ron(&reader.read_string()?)?;
+
+    // Rounds end once their time is up, so nothing longer could have been recorded.
+    let length: usize = reader.read_varint()? as usize;
+    if length > *rules.get_round_time() as usize {
+      return Err(String::from("Replay has more frames than its round could last."));
+    }
+    let mut frames: Vec<InputFrame> = vec!();
+    while frames.len() < length {
+      let run: usize = reader.read_varint()? as usize;
+      let mut frame: InputFrame = input::idle_frame();
+      for input in frame.iter_mut().take(player_count) {
+        *input = PlayerInput::from_byte(reader.read_u8()?)?;
+      }
+      if run == 0 || run > length - frames.len() {
+        return Err(String::from("Replay has more frames than it says it does."));
+      }
+      frames.resize(frames.len() + run, frame);
+    }
+
+    let checksum_count: usize = reader.read_varint()? as usize;
+    if checksum_count > frames.len() / CHECKSUM_INTERVAL as usize + 1 {
+      return Err(String::from("Replay has more checksums than frames to go with them."));
+    }
+    let mut checksums: Vec<u64> = vec!();
+    for _ in 0..checksum_count {
+      checksums.push(reader.read_u64()?);
+    }
+    let end: Option<u64> = match reader.read_u8()? {
+      0 => None,
+      _ => Some(reader.read_u64()?)
+    };
+    if reader.at != bytes.len() {
+      return Err(String::from("Replay has something after its end."));
+    }
+    let replay: Replay = Replay {
+      rules: rules,
+      stage: stage,
+      player_count: player_count,
+      seed: seed,
+      frames: frames,
+      checksums: checksums,
+      end: end
+    };
+    // Catches stages that can't be built, or that don't fit the players, up front.
+    replay.start()?;
+    return Ok(replay);
+  }
+
+  pub fn load(path: &Path) -> Result<Replay, String> {
+    let bytes: Vec<u8> = fs::read(path)
+      .map_err(|e| format!("Couldn't read a replay from {}: {}", path.display(), e))?;
+    return Replay::from_bytes(&bytes).map_err(|e| format!("{}: {}", path.display(), e));
+  }
+
+  pub fn save(&self, path: &Path) -> Result<(), String> {
+    return fs::write(path, self.to_bytes()?)
+      .map_err(|e| format!("Couldn't save a replay to {}: {}", path.display(), e));
+  }
+
+  pub fn get_rules(&self) -> &Rules {
+    return &self.rules;
+  }
+
+  pub fn get_stage(&self) -> &String {
+    return &self.stage;
+  }
+
+  pub fn get_player_count(&self) -> &usize {
+    return &self.player_count;
+  }
+
+  pub fn get_seed(&self) -> &u64 {
+    return &self.seed;
+  }
+
+  pub fn get_frames(&self) -> &Vec<InputFrame> {
+    return &self.frames;
+  }
+
+  pub fn get_checksums(&self) -> &Vec<u64> {
+    return &self.checksums;
+  }
+
+  pub fn get_end(&self) -> &Option<u64> {
+    return &self.end;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::common::direction::Direction;
+
+  const TICKS: u32 = 5 * TICK_RATE;
+
+  /* Records a round on the first stage where player 0 walks about and places a bomb now and then,
+   * while everyone else stands still.
+   */
+  fn record() -> Replay {
+    let stage: StageImpl = StageImpl::from_layout(stage::STAGES[0].1).unwrap();
+    let mut world: WorldImpl = WorldImpl::start(Rules::default(), Box::new(stage), 4, 3);
+    let mut replay: Replay = Replay::new(&world).unwrap();
+    for tick in 0..TICKS {
+      let mut frame: InputFrame = input::idle_frame();
+      let direction: Direction = match tick / 40 % 2 {
+        0 => Direction::East,
+        _ => Direction::South
+      };
+      frame[0] = PlayerInput::new(Some(direction), tick % 90 == 0, false, false);
+      replay.record(&world, &frame);
+      world.tick_mut(&frame);
+    }
+    replay.finish(&world);
+    return replay;
+  }
+
+  // Returns the bytes of a replay up to where its frames start.
+  fn header(replay: &Replay) -> Vec<u8> {
+    let empty: Replay = Replay {
+      frames: vec!(),
+      checksums: vec!(),
+      end: None,
+      ..replay.clone()
+    };
+    let mut bytes: Vec<u8> = empty.to_bytes().unwrap();
+    // Leaves off the frame count, the checksum count and the end.
+    bytes.truncate(bytes.len() - 3);
+    return bytes;
+  }
+
+  #[test]
+  fn replays_survive_being_saved() {
+    let replay: Replay = record();
+    assert_eq!(replay.get_frames().len(), TICKS as usize);
+    assert_eq!(replay.get_checksums().len(), (TICKS / CHECKSUM_INTERVAL) as usize);
+    assert_eq!(Replay::from_bytes(&replay.to_bytes().unwrap()), Ok(replay));
+  }
+
+  #[test]
+  fn recorded_rounds_play_back_the_same_way() {
+    let replay: Replay = record();
+    let world: WorldImpl = replay.verify().unwrap();
+    assert_eq!(*world.get_time(), TICKS);
+    assert_eq!(Some(checksum::checksum(&world)), *replay.get_end());
+  }
+
+  #[test]
+  fn changed_frames_are_found_between_the_checksums_around_them() {
+    let mut replay: Replay = record();
+    replay.frames[130][1] = PlayerInput::new(None, true, false, false);
+    assert_eq!(
+      replay.verify().err(),
+      Some(String::from("Replay played back differently, somewhere between tick 120 and tick 180."))
+    );
+  }
+
+  #[test]
+  fn changed_checksums_are_found_where_they_were_taken() {
+    let mut replay: Replay = record();
+    replay.checksums[2] ^= 1;
+    assert_eq!(
+      replay.verify().err(),
+      Some(String::from("Replay played back differently, somewhere between tick 60 and tick 120."))
+    );
+
+    let mut replay: Replay = record();
+    replay.end = replay.end.map(|end| end ^ 1);
+    let last: String =
+      format!("Replay played back differently, somewhere between tick 240 and tick {}.", TICKS);
+    assert_eq!(replay.verify().err(), Some(last));
+  }
+
+  #[test]
+  fn broken_replays_are_rejected() {
+    let replay: Replay = record();
+    let bytes: Vec<u8> = replay.to_bytes().unwrap();
+
+    let mut magic: Vec<u8> = bytes.clone();
+    magic[0] = b'X';
+    assert_eq!(Replay::from_bytes(&magic), Err(String::from("That isn't a replay.")));
+
+    let mut version: Vec<u8> = bytes.clone();
+    version[MAGIC.len()..MAGIC.len() + 2].copy_from_slice(&(REPLAY_VERSION + 1).to_le_bytes());
+    assert!(Replay::from_bytes(&version).unwrap_err().starts_with("Replay is version"));
+
+    for length in [0, MAGIC.len(), bytes.len() / 2, bytes.len() - 1] {
+      assert!(Replay::from_bytes(&bytes[..length]).is_err(), "{} bytes were let through", length);
+    }
+
+    let mut trailing: Vec<u8> = bytes.clone();
+    trailing.push(0);
+    assert_eq!(
+      Replay::from_bytes(&trailing),
+      Err(String::from("Replay has something after its end."))
+    );
+  }
+
+  #[test]
+  fn replays_with_too_many_frames_are_rejected() {
+    let replay: Replay = record();
+    let too_long: Replay = Replay {
+      frames: vec!(input::idle_frame(); *replay.get_rules().get_round_time() as usize + 1),
+      checksums: vec!(),
+      end: None,
+      ..replay.clone()
+    };
+    assert_eq!(
+      Replay::from_bytes(&too_long.to_bytes().unwrap()),
+      Err(String::from("Replay has more frames than its round could last."))
+    );
+
+    // Runs that go past the frame count, including ones big enough to overflow adding them up.
+    for runs in [vec!(3), vec!(1, usize::MAX as u64)] {
+      let mut bytes: Vec<u8> = header(&replay);
+      write_varint(&mut bytes, 2);
+      for run in runs {
+        write_varint(&mut bytes, run);
+        bytes.extend_from_slice(&[0; 4]);
+      }
+      assert_eq!(
+        Replay::from_bytes(&bytes),
+        Err(String::from("Replay has more frames than it says it does."))
+      );
+    }
+  }
+}
@@ -0,0 +1,166 @@
+use crate::{
+  model::{
+    event::WorldEvent,
+    timestep::{FixedTimestep, TICK_RATE},
+    world::{World, WorldImpl}
+  },
+  replay::Replay
+};
+use std::time::Duration;
+
+// How often a viewer keeps a snapshot of the round to seek from, in ticks.
+pub const SNAPSHOT_INTERVAL: u32 = 5 * TICK_RATE;
+
+// The speeds a viewer can play at, from slowest to fastest.
+pub const SPEEDS: [f32; 7] = [0.125, 0.25, 0.5, 1.0, 2.0, 4.0, 8.0];
+
+// Where normal speed is in the speeds.
+const NORMAL_SPEED: usize = 3;
+
+/* A struct representing a replay being watched, which can be paused, stepped through a tick at a
+ * time, sped up or slowed down, and seeked to any tick in it.
+ *
+ * The whole round is played through once up front, taking a snapshot every snapshot interval
+ * ticks, so that seeking only has to play forward from the nearest snapshot before the tick that
+ * was asked for. Snapshot n is the world at tick n times the interval.
+ *
+ * World is the world at the tick being watched, and timestep is how far the real time is past it,
+ * scaled by the speed. Speed is an index into the speeds.
+ */
+pub struct ReplayViewer {
+  replay: Replay,
+  snapshots: Vec<WorldImpl>,
+  world: WorldImpl,
+  timestep: FixedTimestep,
+  paused: bool,
+  speed: usize
+}
+
+impl ReplayViewer {
+  pub fn new(replay: Replay) -> Result<ReplayViewer, String> {
+    let start: WorldImpl = replay.start()?;
+    let mut snapshots: Vec<WorldImpl> = vec!(start.clone());
+    let mut world: WorldImpl = start.clone();
+    for frame in replay.get_frames().iter() {
+      world.tick_mut(frame);
+      if world.get_time().is_multiple_of(SNAPSHOT_INTERVAL) {
+        snapshots.push(world.clone());
+      }
+    }
+    return Ok(
+      ReplayViewer {
+        replay: replay,
+        snapshots: snapshots,
+        world: start,
+        timestep: FixedTimestep::default(),
+        paused: false,
+        speed: NORMAL_SPEED
+      }
+    );
+  }
+
+  // Plays the next tick of the replay, if there is one.
+  fn tick(&mut self) -> Vec<WorldEvent> {
+    match self.replay.get_frames().get(*self.world.get_time() as usize) {
+      Some(frame) => return self.world.tick_mut(frame),
+      None => return vec!()
+    }
+  }
+
+  /* Plays however many ticks are due after some real time, at the viewer's speed, returning
+   * everything that happened during them. Nothing plays while the viewer is paused, and the viewer
+   * pauses itself once it gets to the end.
+   */
+  pub fn update(&mut self, elapsed: Duration) -> Vec<WorldEvent> {
+    if self.paused {
+      return vec!();
+    }
+    let mut events: Vec<WorldEvent> = vec!();
+    for _ in 0..self.timestep.advance(elapsed.mul_f32(SPEEDS[self.speed])) {
+      events.extend(self.tick());
+    }
+    if self.is_finished() {
+      self.paused = true;
+    }
+    return events;
+  }
+
+  // Pauses the viewer and plays a single tick.
+  pub fn step(&mut self) -> Vec<WorldEvent> {
+    self.paused = true;
+    return self.tick();
+  }
+
+  /* Jumps to a tick of the replay, forwards or backwards, clamped to the length of it. Nothing
+   * that happens on the way there is reported.
+   */
+  pub fn seek(&mut self, time: u32) {
+    let time: u32 = time.min(self.get_length());
+    let snapshot: &WorldImpl =
+      &self.snapshots[((time / SNAPSHOT_INTERVAL) as usize).min(self.snapshots.len() - 1)];
+    // Playing on from the current tick is quicker, unless there's a snapshot closer than it.
+    if time < *self.world.get_time() || snapshot.get_time() > self.world.get_time() {
+      self.world = snapshot.clone();
+    }
+    while *self.world.get_time() < time {
+      self.tick();
+    }
+    self.timestep = FixedTimestep::default();
+  }
+
+  // Seeks by a number of ticks from the current one, which can be negative to go backwards.
+  pub fn skip(&mut self, ticks: i64) {
+    let time: i64 = *self.world.get_time() as i64 + ticks;
+    self.seek(time.max(0) as u32);
+  }
+
+  pub fn set_paused(&mut self, paused: bool) {
+    self.paused = paused;
+  }
+
+  // Pauses or unpauses the viewer. Unpausing it at the end of the replay starts it over.
+  pub fn toggle_paused(&mut self) {
+    if self.paused && self.is_finished() {
+      self.seek(0);
+    }
+    self.paused = !self.paused;
+  }
+
+  // Picks the speed a number of steps away from the current one, stopping at either end.
+  pub fn change_speed(&mut self, steps: i32) {
+    self.speed = (self.speed as i32 + steps).clamp(0, SPEEDS.len() as i32 - 1) as usize;
+  }
+
+  // Returns how many ticks long the replay is.
+  pub fn get_length(&self) -> u32 {
+    return self.replay.get_frames().len() as u32;
+  }
+
+  pub fn is_finished(&self) -> bool {
+    return *self.world.get_time() >= self.get_length();
+  }
+
+  // Returns how far the real time is between ticks; see FixedTimestep. Paused viewers sit on one.
+  pub fn get_alpha(&self) -> f32 {
+    match self.paused {
+      true => return 0.0,
+      false => return self.timestep.get_alpha()
+    }
+  }
+
+  pub fn get_speed(&self) -> f32 {
+    return SPEEDS[self.speed];
+  }
+
+  pub fn get_replay(&self) -> &Replay {
+    return &self.replay;
+  }
+
+  pub fn get_world(&self) -> &WorldImpl {
+    return &self.world;
+  }
+
+  pub fn get_paused(&self) -> &bool {
+    return &self.paused;
+  }
+}
@@ -0,0 +1,254 @@
+use crate::{
+  ai::{Bots, Difficulty},
+  model::{
+    event::WorldEvent,
+    input::{self, InputFrame, PlayerId, MAX_PLAYERS},
+    rules::Rules,
+    stage::{Stage, StageImpl, STAGES},
+    world::{Outcome, World, WorldImpl}
+  },
+  replay::{Replay, REPLAY_EXTENSION}
+};
+use serde::Serialize;
+use std::{
+  fs,
+  path::PathBuf,
+  sync::atomic::{AtomicUsize, Ordering},
+  thread
+};
+
+pub mod report;
+
+/* A struct representing a run of bot-vs-bot matches, which are each a single round played without
+ * anything to draw them on.
+ *
+ * Bots are who plays, by player ID, so the first bot is player 0 in every match.
+ * Stages are indices into STAGES. Matches take turns between them, so match n is played on stage
+ * n modulo how many there are.
+ * Seed is what the first match is seeded with, and every match after it is seeded with one more
+ * than the last.
+ * Replays is the directory a replay of every match is saved to, if they're being saved at all.
+ * Match n is saved as match-n.
+ */
+#[derive(Clone, Debug)]
+pub struct Tournament {
+  rules: Rules,
+  bots: Vec<Difficulty>,
+  stages: Vec<usize>,
+  matches: usize,
+  seed: u64,
+  replays: Option<PathBuf>
+}
+
+/* A struct representing how a single match went.
+ *
+ * Kills and deaths are indexed by player ID. Kills only count catching someone else, while deaths
+ * count every time a player died, whoever's blast it was.
+ */
+#[derive(Clone, PartialEq, Debug, Serialize)]
+pub struct MatchResult {
+  index: usize,
+  stage: String,
+  seed: u64,
+  winner: Option<PlayerId>,
+  ticks: u32,
+  kills: Vec<u32>,
+  deaths: Vec<u32>
+}
+
+impl Tournament {
+  pub fn new(
+    rules: Rules, bots: Vec<Difficulty>, stages: Vec<usize>, matches: usize, seed: u64
+  ) -> Result<Tournament, String> {
+    if bots.len() < 2 || bots.len() > MAX_PLAYERS {
+      return Err(format!("Tournaments need between 2 and {} bots.", MAX_PLAYERS));
+    } else if stages.is_empty() {
+      return Err(String::from("Tournaments need at least 1 stage."));
+    }
+    for stage in stages.iter() {
+      let (name, layout): (&str, &str) = *STAGES.get(*stage)
+        .ok_or_else(|| format!("There's no stage {}.", stage))?;
+      let spawns: usize = StageImpl::from_layout(layout)?.get_spawns().len();
+      if bots.len() > spawns {
+        return Err(format!("{} only fits {} players.", name, spawns));
+      }
+    }
+    return Ok(
+      Tournament {
+        rules: rules,
+        bots: bots,
+        stages: stages,
+        matches: matches,
+        seed: seed,
+        replays: None
+      }
+    );
+  }
+
+  // Plays a single match of the tournament out to the end of its round, recording it as it goes.
+  pub fn play(&self, index: usize) -> (MatchResult, Replay) {
+    let (name, layout): (&str, &str) = STAGES[self.stages[index % self.stages.len()]];
+    let seed: u64 = self.seed.wrapping_add(index as u64);
+    let stage: StageImpl = StageImpl::from_layout(layout).expect("Built in stages are valid");
+    let mut world: WorldImpl = WorldImpl::start(self.rules, Box::new(stage), self.bots.len(), seed);
+    let lineup: Vec<(PlayerId, Difficulty)> = self.bots.iter().enumerate()
+      .map(|(id, difficulty)| (id as PlayerId, *difficulty))
+      .collect();
+    let mut bots: Bots = Bots::new(&lineup, seed);
+    let mut replay: Replay = Replay::new(&world).expect("Rounds are recorded from the start");
+    let mut kills: Vec<u32> = vec!(0; self.bots.len());
+    let mut deaths: Vec<u32> = vec!(0; self.bots.len());
+    while world.get_outcome().is_none() {
+      let mut frame: InputFrame = input::idle_frame();
+      bots.fill(&world, &mut frame);
+      replay.record(&world, &frame);
+      for event in world.tick_mut(&frame) {
+        if let WorldEvent::PlayerKilled { id, killer } = event {
+          deaths[id as usize] += 1;
+          if killer != id {
+            kills[killer as usize] += 1;
+          }
+        }
+      }
+    }
+    replay.finish(&world);
+    let result: MatchResult = MatchResult {
+      index: index,
+      stage: String::from(name),
+      seed: seed,
+      winner: match world.get_outcome() {
+        Some(Outcome::Win(id)) => Some(id),
+        _ => None
+      },
+      ticks: *world.get_time(),
+      kills: kills,
+      deaths: deaths
+    };
+    return (result, replay);
+  }
+
+  // Plays a single match, and saves its replay if replays are being saved.
+  fn play_and_save(&self, index: usize) -> Result<MatchResult, String> {
+    let (result, replay): (MatchResult, Replay) = self.play(index);
+    if let Some(directory) = &self.replays {
+      replay.save(&directory.join(format!("match-{}.{}", index, REPLAY_EXTENSION)))?;
+    }
+    return Ok(result);
+  }
+
+  /* Plays every match of the tournament, spread out over up to a number of threads. Threads pick
+   * up the next match that hasn't been started as soon as they're done with one, since some
+   * matches run a lot longer than others. Results are in the order of the matches.
+   * Fails if replays are being saved and one of them couldn't be.
+   */
+  pub fn run(&self, threads: usize) -> Result<Vec<MatchResult>, String> {
+    if let Some(directory) = &self.replays {
+      fs::create_dir_all(directory)
+        .map_err(|e| format!("Couldn't make {}: {}", directory.display(), e))?;
+    }
+    let next: AtomicUsize = AtomicUsize::new(0);
+    let results: Result<Vec<MatchResult>, String> = thread::scope(|scope| {
+      let handles: Vec<thread::ScopedJoinHandle<Result<Vec<MatchResult>, String>>> =
+        (0..threads.max(1))
+          .map(|_| {
+            return scope.spawn(|| {
+              let mut results: Vec<MatchResult> = vec!();
+              loop {
+                let index: usize = next.fetch_add(1, Ordering::Relaxed);
+                if index >= self.matches {
+                  return Ok(results);
+                }
+                results.push(self.play_and_save(index)?);
+              }
+            });
+          })
+          .collect();
+      let mut results: Vec<MatchResult> = vec!();
+      for handle in handles {
+        results.extend(handle.join().expect("A match panicked")?);
+      }
+      return Ok(results);
+    });
+    let mut results: Vec<MatchResult> = results?;
+    results.sort_by_key(|result| result.index);
+    return Ok(results);
+  }
+
+  pub fn get_rules(&self) -> &Rules {
+    return &self.rules;
+  }
+
+  pub fn get_bots(&self) -> &Vec<Difficulty> {
+    return &self.bots;
+  }
+
+  pub fn get_stages(&self) -> &Vec<usize> {
+    return &self.stages;
+  }
+
+  pub fn get_matches(&self) -> &usize {
+    return &self.matches;
+  }
+
+  pub fn get_seed(&self) -> &u64 {
+    return &self.seed;
+  }
+
+  pub fn get_replays(&self) -> &Option<PathBuf> {
+    return &self.replays;
+  }
+
+  pub fn set_replays(&self, replays: Option<PathBuf>) -> Tournament {
+    return Tournament {
+      replays: replays,
+      ..self.clone()
+    }
+  }
+}
+
+impl MatchResult {
+  pub fn get_index(&self) -> &usize {
+    return &self.index;
+  }
+
+  pub fn get_stage(&self) -> &String {
+    return &self.stage;
+  }
+
+  pub fn get_seed(&self) -> &u64 {
+    return &self.seed;
+  }
+
+  pub fn get_winner(&self) -> &Option<PlayerId> {
+    return &self.winner;
+  }
+
+  pub fn get_ticks(&self) -> &u32 {
+    return &self.ticks;
+  }
+
+  pub fn get_kills(&self) -> &Vec<u32> {
+    return &self.kills;
+  }
+
+  pub fn get_deaths(&self) -> &Vec<u32> {
+    return &self.deaths;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn reports_are_the_same_however_many_threads_play() {
+    let rules: Rules = Rules::default().set_round_time(300).set_sudden_death_time(300);
+    let bots: Vec<Difficulty> = vec!(Difficulty::Expert, Difficulty::Hard, Difficulty::Normal);
+    let tournament: Tournament = Tournament::new(rules, bots.clone(), vec!(0, 1, 2), 3, 3).unwrap();
+    let report = |threads: usize| -> String {
+      let results: Vec<MatchResult> = tournament.run(threads).unwrap();
+      return report::to_csv(&report::summarize(&bots, &results), &results);
+    };
+    assert_eq!(report(1), report(3));
+  }
+}
@@ -0,0 +1,174 @@
+use crate::{
+  ai::Difficulty,
+  model::{input::PlayerId, timestep::TICK_RATE},
+  tournament::MatchResult
+};
+use serde::Serialize;
+
+/* A struct representing how a player did over a whole tournament.
+ *
+ * Win rate is the share of matches they won, between 0 and 1.
+ * K/D is kills over deaths, or just kills for a player who never died.
+ */
+#[derive(Clone, PartialEq, Debug, Serialize)]
+pub struct PlayerSummary {
+  id: PlayerId,
+  bot: String,
+  wins: u32,
+  win_rate: f32,
+  kills: u32,
+  deaths: u32,
+  kd: f32
+}
+
+/* A struct representing how a whole tournament went. Average ticks is how long rounds lasted on
+ * average, which average seconds is the same as at the normal tick rate.
+ */
+#[derive(Clone, PartialEq, Debug, Serialize)]
+pub struct Summary {
+  matches: usize,
+  draws: usize,
+  average_ticks: f32,
+  average_seconds: f32,
+  players: Vec<PlayerSummary>
+}
+
+// Everything a report holds, in the shape JSON reports take.
+#[derive(Serialize)]
+struct Report<'a> {
+  summary: &'a Summary,
+  matches: &'a [MatchResult]
+}
+
+// Tallies up the results of a tournament played by some bots.
+pub fn summarize(bots: &[Difficulty], results: &[MatchResult]) -> Summary {
+  let matches: usize = results.len();
+  let players: Vec<PlayerSummary> = bots.iter().enumerate()
+    .map(|(id, difficulty)| {
+      let id: PlayerId = id as PlayerId;
+      let wins: u32 = results.iter()
+        .filter(|result| result.get_winner() == &Some(id))
+        .count() as u32;
+      let count = |tally: fn(&MatchResult) -> &Vec<u32>| -> u32 {
+        return results.iter().map(|result| tally(result).get(id as usize).unwrap_or(&0)).sum();
+      };
+      let kills: u32 = count(MatchResult::get_kills);
+      let deaths: u32 = count(MatchResult::get_deaths);
+      return PlayerSummary {
+        id: id,
+        bot: format!("{:?}", difficulty),
+        wins: wins,
+        win_rate: wins as f32 / matches.max(1) as f32,
+        kills: kills,
+        deaths: deaths,
+        kd: kills as f32 / deaths.max(1) as f32
+      }
+    })
+    .collect();
+  let average_ticks: f32 = results.iter().map(|result| *result.get_ticks() as f32).sum::<f32>()
+    / matches.max(1) as f32;
+  return Summary {
+    matches: matches,
+    draws: results.iter().filter(|result| result.get_winner().is_none()).count(),
+    average_ticks: average_ticks,
+    average_seconds: average_ticks / TICK_RATE as f32,
+    players: players
+  }
+}
+
+/* Writes a tournament out as CSV. There are three tables, one after the other with a blank line
+ * between them: every match, then every player, then the tournament as a whole.
+ *
+ * Matches have a kills and deaths column for each player, and draws have no winner.
+ */
+pub fn to_csv(summary: &Summary, results: &[MatchResult]) -> String {
+  let mut csv: String = String::from("match,stage,seed,winner,ticks");
+  for player in summary.players.iter() {
+    csv += &format!(",kills_{},deaths_{}", player.id, player.id);
+  }
+  csv += "\n";
+  for result in results.iter() {
+    let winner: String = result.get_winner().map_or(String::new(), |id| id.to_string());
+    csv += &format!(
+      "{},{},{},{},{}",
+      result.get_index(), result.get_stage(), result.get_seed(), winner, result.get_ticks()
+    );
+    for (kills, deaths) in result.get_kills().iter().zip(result.get_deaths().iter()) {
+      csv += &format!(",{},{}", kills, deaths);
+    }
+    csv += "\n";
+  }
+
+  csv += "\nplayer,bot,wins,win_rate,kills,deaths,kd\n";
+  for player in summary.players.iter() {
+    csv += &format!(
+      "{},{},{},{:.4},{},{},{:.4}\n",
+      player.id, player.bot, player.wins, player.win_rate, player.kills, player.deaths, player.kd
+    );
+  }
+
+  csv += "\nmatches,draws,average_ticks,average_seconds\n";
+  csv += &format!(
+    "{},{},{:.1},{:.2}\n",
+    summary.matches, summary.draws, summary.average_ticks, summary.average_seconds
+  );
+  return csv;
+}
+
+// Writes a tournament out as JSON, with the summary first and then every match.
+pub fn to_json(summary: &Summary, results: &[MatchResult]) -> Result<String, String> {
+  return serde_json::to_string_pretty(&Report { summary: summary, matches: results })
+    .map_err(|e| format!("Couldn't write the report as JSON: {}", e));
+}
+
+impl PlayerSummary {
+  pub fn get_id(&self) -> &PlayerId {
+    return &self.id;
+  }
+
+  pub fn get_bot(&self) -> &String {
+    return &self.bot;
+  }
+
+  pub fn get_wins(&self) -> &u32 {
+    return &self.wins;
+  }
+
+  pub fn get_win_rate(&self) -> &f32 {
+    return &self.win_rate;
+  }
+
+  pub fn get_kills(&self) -> &u32 {
+    return &self.kills;
+  }
+
+  pub fn get_deaths(&self) -> &u32 {
+    return &self.deaths;
+  }
+
+  pub fn get_kd(&self) -> &f32 {
+    return &self.kd;
+  }
+}
+
+impl Summary {
+  pub fn get_matches(&self) -> &usize {
+    return &self.matches;
+  }
+
+  pub fn get_draws(&self) -> &usize {
+    return &self.draws;
+  }
+
+  pub fn get_average_ticks(&self) -> &f32 {
+    return &self.average_ticks;
+  }
+
+  pub fn get_average_seconds(&self) -> &f32 {
+    return &self.average_seconds;
+  }
+
+  pub fn get_players(&self) -> &Vec<PlayerSummary> {
+    return &self.players;
+  }
+}
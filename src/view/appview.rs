@@ -0,0 +1,151 @@
+use crate::{
+  model::{score::Scoreboard, world::World},
+  view::{
+    capture::Frame,
+    hud,
+    scene::{Painter, Rgba, ScreenRect, TILE_SIZE},
+    theme::{AtlasRect, Theme},
+    View
+  }
+};
+use sdl2::{
+  pixels::{Color, PixelFormatEnum},
+  rect::Rect,
+  render::{BlendMode, Canvas, Texture},
+  video::Window,
+  VideoSubsystem
+};
+
+/* A struct representing a window that draws worlds with SDL2.
+ *
+ * Canvas is the renderer for the window. It can either be hardware accelerated, or SDL's software
+ * renderer for machines that don't have a GPU.
+ * Tile size is how big a tile is on screen, in pixels.
+ * Theme is what the window draws worlds with, if anything. Its atlas gets uploaded to the renderer
+ * as a texture once, when the window opens.
+ */
+pub struct AppView {
+  canvas: Canvas<Window>,
+  tile_size: u32,
+  theme: Option<(Theme, Texture)>
+}
+
+// A struct representing a canvas being painted, along with the texture of its theme's atlas.
+struct CanvasPainter<'a> {
+  canvas: &'a mut Canvas<Window>,
+  atlas: Option<&'a mut Texture>
+}
+
+impl AppView {
+  /* Opens a window of a certain size in pixels. Worlds and menus that don't fit it can still be
+   * drawn, but would be cut off.
+   */
+  pub fn new(
+    video: &VideoSubsystem, title: &str, size: (u32, u32), software: bool, theme: Option<Theme>
+  ) -> Result<AppView, String> {
+    let window: Window = video
+      .window(title, size.0, size.1)
+      .position_centered()
+      .build()
+      .map_err(|e| e.to_string())?;
+    let mut canvas: Canvas<Window> = match software {
+      true => window.into_canvas().software().build(),
+      false => window.into_canvas().accelerated().present_vsync().build()
+    }.map_err(|e| e.to_string())?;
+    canvas.set_blend_mode(BlendMode::Blend);
+
+    let mut view: AppView = AppView {
+      canvas: canvas,
+      tile_size: TILE_SIZE,
+      theme: None
+    };
+    view.set_theme(theme)?;
+    return Ok(view);
+  }
+
+  // Switches what worlds get drawn with, uploading the new theme's atlas if there is one.
+  pub fn set_theme(&mut self, theme: Option<Theme>) -> Result<(), String> {
+    self.theme = match theme {
+      Some(theme) => {
+        let texture: Texture = AppView::upload(&self.canvas, &theme)?;
+        Some((theme, texture))
+      },
+      None => None
+    };
+    return Ok(());
+  }
+
+  pub fn get_tile_size(&self) -> &u32 {
+    return &self.tile_size;
+  }
+
+  /* Clears the window, paints a frame onto it and shows it. The painting gets the window's theme
+   * too, for anything that draws worlds.
+   */
+  pub fn draw(
+    &mut self, paint: impl FnOnce(&mut dyn Painter, Option<&Theme>) -> Result<(), String>
+  ) -> Result<(), String> {
+    self.canvas.set_draw_color(Color::RGB(0, 0, 0));
+    self.canvas.clear();
+    let (theme, atlas): (Option<&Theme>, Option<&mut Texture>) = match &mut self.theme {
+      Some((theme, atlas)) => (Some(theme), Some(atlas)),
+      None => (None, None)
+    };
+    let mut painter: CanvasPainter = CanvasPainter {
+      canvas: &mut self.canvas,
+      atlas: atlas
+    };
+    paint(&mut painter, theme)?;
+    self.canvas.present();
+    return Ok(());
+  }
+
+  // Copies a theme's atlas into a texture the renderer can draw sprites from.
+  fn upload(canvas: &Canvas<Window>, theme: &Theme) -> Result<Texture, String> {
+    let atlas: &Frame = theme.get_atlas();
+    let mut texture: Texture = canvas.texture_creator()
+      .create_texture_static(PixelFormatEnum::RGBA32, *atlas.get_width(), *atlas.get_height())
+      .map_err(|e| format!("Couldn't make a texture for theme {}: {}", theme.get_name(), e))?;
+    texture.update(None, atlas.get_pixels(), *atlas.get_width() as usize * 4)
+      .map_err(|e| format!("Couldn't upload the atlas for theme {}: {}", theme.get_name(), e))?;
+    texture.set_blend_mode(BlendMode::Blend);
+    return Ok(texture);
+  }
+}
+
+impl<'a> Painter for CanvasPainter<'a> {
+  fn fill_rect(&mut self, rect: ScreenRect, color: Rgba) -> Result<(), String> {
+    self.canvas.set_draw_color(Color::RGBA(color.r, color.g, color.b, color.a));
+    return self.canvas.fill_rect(Rect::new(rect.0, rect.1, rect.2, rect.3));
+  }
+
+  fn draw_sprite(
+    &mut self, source: &AtlasRect, rect: ScreenRect, quarter_turns: u8, alpha: u8
+  ) -> Result<(), String> {
+    let atlas: &mut Texture = match &mut self.atlas {
+      Some(atlas) => atlas,
+      None => return Err(String::from("This window has no theme to draw sprites from."))
+    };
+    atlas.set_alpha_mod(alpha);
+    return self.canvas.copy_ex(
+      atlas,
+      Rect::new(source.x as i32, source.y as i32, source.w, source.h),
+      Rect::new(rect.0, rect.1, rect.2, rect.3),
+      (quarter_turns % 4) as f64 * 90.0,
+      None,
+      false,
+      false
+    );
+  }
+}
+
+impl View for AppView {
+  fn render(
+    &mut self, world: &dyn World, scoreboard: &Scoreboard, alpha: f32
+  ) -> Result<(), String> {
+    let tile_size: u32 = self.tile_size;
+    return self.draw(|painter, theme| {
+      return hud::draw_screen(painter, world, scoreboard, alpha, tile_size, theme, None);
+    });
+  }
+}
@@ -0,0 +1,270 @@
+use crate::{
+  model::{score::Scoreboard, world::World},
+  view::{
+    effects::Effects,
+    hud,
+    scene::{self, Painter, Rgba, ScreenRect},
+    theme::{AtlasRect, Theme},
+    View
+  }
+};
+use std::{
+  fs::{self, File},
+  io::BufWriter,
+  path::{Path, PathBuf}
+};
+
+/* A struct representing an image of a world, drawn entirely in memory.
+ * This doesn't need a window, a GPU or even SDL, so worlds can be captured anywhere the model runs,
+ * which is what the golden image checks rely on.
+ *
+ * Pixels are 8-bit RGBA, row by row from the top left.
+ */
+#[derive(Clone, PartialEq)]
+pub struct Frame {
+  width: u32,
+  height: u32,
+  pixels: Vec<u8>
+}
+
+// Blends one channel of a color over another, the same way SDL's blend mode does.
+fn blend(source: u8, destination: u8, alpha: u8) -> u8 {
+  let alpha: u32 = alpha as u32;
+  return ((source as u32 * alpha + destination as u32 * (255 - alpha) + 127) / 255) as u8;
+}
+
+impl Frame {
+  // Makes an opaque black frame.
+  pub fn new(width: u32, height: u32) -> Frame {
+    let mut pixels: Vec<u8> = vec![0; (width * height * 4) as usize];
+    for pixel in pixels.chunks_mut(4) {
+      pixel[3] = 255;
+    }
+    return Frame {
+      width: width,
+      height: height,
+      pixels: pixels
+    }
+  }
+
+  /* Makes a frame of a certain size and paints it, through a painter that can draw a theme's
+   * sprites if there is one.
+   */
+  fn paint(
+    size: (u32, u32), theme: Option<&Theme>,
+    draw: impl FnOnce(&mut dyn Painter) -> Result<(), String>
+  ) -> Result<Frame, String> {
+    let mut frame: Frame = Frame::new(size.0, size.1);
+    match theme {
+      Some(theme) => {
+        let mut painter: AtlasPainter = AtlasPainter {
+          frame: &mut frame,
+          atlas: theme.get_atlas()
+        };
+        draw(&mut painter)?;
+      },
+      None => draw(&mut frame)?
+    }
+    return Ok(frame);
+  }
+
+  /* Draws a world into a new frame just big enough to fit its stage, with a theme's sprites if
+   * there is one.
+   */
+  pub fn capture(
+    world: &dyn World, alpha: f32, tile_size: u32, theme: Option<&Theme>
+  ) -> Result<Frame, String> {
+    let size: (u32, u32) = scene::scene_size(world.get_stage().get_dimensions(), tile_size);
+    return Frame::paint(size, theme, |painter| {
+      return scene::draw_world(painter, world, alpha, tile_size, theme);
+    });
+  }
+
+  // Draws a world with the HUD above it, and any effects over it, just like a frontend would.
+  pub fn capture_screen(
+    world: &dyn World, scoreboard: &Scoreboard, alpha: f32, tile_size: u32, theme: Option<&Theme>,
+    effects: Option<&Effects>
+  ) -> Result<Frame, String> {
+    return Frame::paint(hud::screen_size(world, tile_size), theme, |painter| {
+      return hud::draw_screen(painter, world, scoreboard, alpha, tile_size, theme, effects);
+    });
+  }
+
+  pub fn save_png(&self, path: &Path) -> Result<(), String> {
+    let file: File = File::create(path)
+      .map_err(|e| format!("Couldn't create {}: {}", path.display(), e))?;
+    let mut encoder: png::Encoder<BufWriter<File>> =
+      png::Encoder::new(BufWriter::new(file), self.width, self.height);
+    encoder.set_color(png::ColorType::RGBA);
+    encoder.set_depth(png::BitDepth::Eight);
+    return encoder.write_header()
+      .and_then(|mut writer| writer.write_image_data(&self.pixels))
+      .map_err(|e| format!("Couldn't write {}: {}", path.display(), e));
+  }
+
+  // Loads a frame back from a PNG, which has to be 8-bit RGBA like the ones frames save.
+  pub fn load_png(path: &Path) -> Result<Frame, String> {
+    let file: File = File::open(path)
+      .map_err(|e| format!("Couldn't open {}: {}", path.display(), e))?;
+    let (info, mut reader) = png::Decoder::new(file).read_info()
+      .map_err(|e| format!("Couldn't read {}: {}", path.display(), e))?;
+    if info.color_type != png::ColorType::RGBA || info.bit_depth != png::BitDepth::Eight {
+      return Err(format!("{} isn't an 8-bit RGBA image.", path.display()));
+    }
+    let mut pixels: Vec<u8> = vec![0; info.buffer_size()];
+    reader.next_frame(&mut pixels)
+      .map_err(|e| format!("Couldn't read {}: {}", path.display(), e))?;
+    return Ok(
+      Frame {
+        width: info.width,
+        height: info.height,
+        pixels: pixels
+      }
+    )
+  }
+
+  /* Returns how many pixels differ between this frame and another.
+   * Frames of different sizes can't be compared at all, so that's an error instead.
+   */
+  pub fn diff(&self, other: &Frame) -> Result<usize, String> {
+    if self.width != other.width || self.height != other.height {
+      return Err(
+        format!(
+          "Frames are different sizes ({}x{} and {}x{}).",
+          self.width, self.height, other.width, other.height
+        )
+      );
+    }
+    return Ok(
+      self.pixels.chunks(4).zip(other.pixels.chunks(4)).filter(|(a, b)| a != b).count()
+    );
+  }
+
+  // Returns the part of a rectangle inside the frame, as its left, top, right and bottom edges.
+  fn clip(&self, rect: &ScreenRect) -> (u32, u32, u32, u32) {
+    return (
+      rect.0.max(0) as u32,
+      rect.1.max(0) as u32,
+      ((rect.0 + rect.2 as i32).max(0) as u32).min(self.width),
+      ((rect.1 + rect.3 as i32).max(0) as u32).min(self.height)
+    );
+  }
+
+  fn get_pixel(&self, x: u32, y: u32) -> Rgba {
+    let i: usize = ((y * self.width + x) * 4) as usize;
+    return Rgba::new(self.pixels[i], self.pixels[i + 1], self.pixels[i + 2], self.pixels[i + 3]);
+  }
+
+  fn blend_pixel(&mut self, x: u32, y: u32, color: Rgba) {
+    let i: usize = ((y * self.width + x) * 4) as usize;
+    self.pixels[i] = blend(color.r, self.pixels[i], color.a);
+    self.pixels[i + 1] = blend(color.g, self.pixels[i + 1], color.a);
+    self.pixels[i + 2] = blend(color.b, self.pixels[i + 2], color.a);
+  }
+
+  pub fn get_width(&self) -> &u32 {
+    return &self.width;
+  }
+
+  pub fn get_height(&self) -> &u32 {
+    return &self.height;
+  }
+
+  pub fn get_pixels(&self) -> &Vec<u8> {
+    return &self.pixels;
+  }
+}
+
+impl Painter for Frame {
+  // Fills a rectangle, clipping off whatever part of it is outside the frame.
+  fn fill_rect(&mut self, rect: ScreenRect, color: Rgba) -> Result<(), String> {
+    let (left, top, right, bottom): (u32, u32, u32, u32) = self.clip(&rect);
+    for y in top..bottom {
+      for x in left..right {
+        self.blend_pixel(x, y, color);
+      }
+    }
+    return Ok(());
+  }
+}
+
+/* A struct representing a frame being painted with a theme's sprites.
+ * Sprites are scaled with nearest neighbour sampling, which keeps pixel art crisp and matches what
+ * SDL does by default.
+ */
+struct AtlasPainter<'a> {
+  frame: &'a mut Frame,
+  atlas: &'a Frame
+}
+
+impl<'a> Painter for AtlasPainter<'a> {
+  fn fill_rect(&mut self, rect: ScreenRect, color: Rgba) -> Result<(), String> {
+    return self.frame.fill_rect(rect, color);
+  }
+
+  fn draw_sprite(
+    &mut self, source: &AtlasRect, rect: ScreenRect, quarter_turns: u8, alpha: u8
+  ) -> Result<(), String> {
+    let (left, top, right, bottom): (u32, u32, u32, u32) = self.frame.clip(&rect);
+    for y in top..bottom {
+      for x in left..right {
+        // Where this pixel falls in the sprite once it's turned, from 0 to 1 on each axis.
+        let u: f32 = ((x as i32 - rect.0) as f32 + 0.5) / rect.2 as f32;
+        let v: f32 = ((y as i32 - rect.1) as f32 + 0.5) / rect.3 as f32;
+        let (u, v): (f32, f32) = match quarter_turns % 4 {
+          0 => (u, v),
+          1 => (v, 1.0 - u),
+          2 => (1.0 - u, 1.0 - v),
+          _ => (1.0 - v, u)
+        };
+        let color: Rgba = self.atlas.get_pixel(
+          source.x + ((u * source.w as f32) as u32).min(source.w - 1),
+          source.y + ((v * source.h as f32) as u32).min(source.h - 1)
+        );
+        let alpha: u8 = ((color.a as u32 * alpha as u32 + 127) / 255) as u8;
+        self.frame.blend_pixel(x, y, Rgba { a: alpha, ..color });
+      }
+    }
+    return Ok(());
+  }
+}
+
+/* A struct representing a view that saves every frame it renders as a numbered PNG.
+ * This is handy for recording what a headless run looked like.
+ */
+pub struct CaptureView {
+  directory: PathBuf,
+  tile_size: u32,
+  theme: Option<Theme>,
+  frame_number: u32
+}
+
+impl CaptureView {
+  pub fn new(
+    directory: &Path, tile_size: u32, theme: Option<Theme>
+  ) -> Result<CaptureView, String> {
+    fs::create_dir_all(directory)
+      .map_err(|e| format!("Couldn't create {}: {}", directory.display(), e))?;
+    return Ok(
+      CaptureView {
+        directory: directory.to_path_buf(),
+        tile_size: tile_size,
+        theme: theme,
+        frame_number: 0
+      }
+    )
+  }
+}
+
+impl View for CaptureView {
+  fn render(
+    &mut self, world: &dyn World, scoreboard: &Scoreboard, alpha: f32
+  ) -> Result<(), String> {
+    let frame: Frame = Frame::capture_screen(
+      world, scoreboard, alpha, self.tile_size, self.theme.as_ref(), None
+    )?;
+    frame.save_png(&self.directory.join(format!("{:06}.png", self.frame_number)))?;
+    self.frame_number += 1;
+    return Ok(());
+  }
+}
@@ -0,0 +1,322 @@
+use crate::{
+  common::config,
+  model::{
+    bomb::Blast,
+    event::WorldEvent,
+    input::PlayerId,
+    position::SUBTILE,
+    rng::Rng,
+    world::World
+  },
+  view::scene::{self, Painter, Rgba, ScreenRect, PLAYER_COLORS}
+};
+use serde::{Deserialize, Serialize};
+use std::{f32::consts::PI, path::Path};
+
+// How many bits of debris a soft wall breaks into.
+const DEBRIS_PER_WALL: u32 = 10;
+
+// How many sparks fly out of a bomb as it goes off.
+const SPARKS_PER_BOMB: u32 = 6;
+
+// How many bits a player bursts into as they die.
+const BITS_PER_DEATH: u32 = 12;
+
+// How long particles last, in ticks.
+const PARTICLE_LIFETIME: u32 = 40;
+
+// How fast particles fall, in tiles per tick per tick.
+const GRAVITY: f32 = 0.004;
+
+// How long a death plays out for, in ticks.
+const DEATH_TIME: u32 = 45;
+
+// How much shake each bomb going off adds, where 1 is as much shake as there can be.
+const SHAKE_PER_DETONATION: f32 = 0.4;
+
+// How much shake wears off every tick.
+const SHAKE_DECAY: f32 = 0.02;
+
+// The furthest the screen gets shaken, as a fraction of a tile.
+const MAX_SHAKE: f32 = 0.25;
+
+const DEBRIS_COLORS: [Rgba; 2] = [Rgba::rgb(170, 120, 70), Rgba::rgb(110, 75, 40)];
+const SPARK_COLOR: Rgba = Rgba::rgb(255, 220, 90);
+
+/* A struct representing which effects are turned on. Either can be turned off for anyone who finds
+ * them distracting or uncomfortable, and neither changes anything about the game itself.
+ *
+ * Particles covers everything drawn on top of the world: debris, sparks, flickering flames and
+ * players dying.
+ */
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EffectSettings {
+  particles: bool,
+  screen_shake: bool
+}
+
+impl Default for EffectSettings {
+  fn default() -> EffectSettings {
+    return EffectSettings { particles: true, screen_shake: true };
+  }
+}
+
+impl EffectSettings {
+  pub fn from_file(path: &Path) -> Result<EffectSettings, String> {
+    return config::load(path, "effect settings");
+  }
+
+  pub fn save(&self, path: &Path) -> Result<(), String> {
+    return config::save(self, path, "effect settings");
+  }
+
+  pub fn get_particles(&self) -> &bool {
+    return &self.particles;
+  }
+
+  pub fn get_screen_shake(&self) -> &bool {
+    return &self.screen_shake;
+  }
+
+  pub fn set_particles(&self, particles: bool) -> EffectSettings {
+    return EffectSettings {
+      particles: particles,
+      ..*self
+    }
+  }
+
+  pub fn set_screen_shake(&self, screen_shake: bool) -> EffectSettings {
+    return EffectSettings {
+      screen_shake: screen_shake,
+      ..*self
+    }
+  }
+}
+
+/* A struct representing a single particle. Position and velocity are in tiles, with y pointing
+ * north like the model's. Age is how many ticks it's been around for.
+ */
+#[derive(Copy, Clone, PartialEq, Debug)]
+struct Particle {
+  position: (f32, f32),
+  velocity: (f32, f32),
+  color: Rgba,
+  size: f32,
+  age: u32
+}
+
+// A struct representing a player dying, where they died, in tiles.
+#[derive(Copy, Clone, PartialEq, Debug)]
+struct Death {
+  id: PlayerId,
+  position: (f32, f32),
+  age: u32
+}
+
+/* A struct representing the effects drawn over a world, which make things happening in it easier
+ * to feel. Effects only ever find out about a world from the events its ticks report, and never
+ * change it, so they can't have any effect on how the game plays out.
+ *
+ * Rng is the effects' own, for scattering particles. It has nothing to do with the world's.
+ * Shake is how shaken up the screen is, from 0 to 1, which wears off over time.
+ * Time is how many ticks the effects have run for, which animations go by.
+ */
+pub struct Effects {
+  settings: EffectSettings,
+  particles: Vec<Particle>,
+  deaths: Vec<Death>,
+  rng: Rng,
+  shake: f32,
+  time: u32
+}
+
+// Returns a number from -1 to 1.
+fn spread(rng: &mut Rng) -> f32 {
+  return rng.below(2001) as f32 / 1000.0 - 1.0;
+}
+
+// Returns the tiles a blast covers, from its center out along each of its flames.
+fn blast_tiles(blast: &dyn Blast) -> Vec<(i8, i8)> {
+  let mut tiles: Vec<(i8, i8)> = vec!(*blast.get_center());
+  for flame in blast.get_flames() {
+    let mut tile: (i8, i8) = *flame.get_start();
+    tiles.push(tile);
+    while &tile != flame.get_end() {
+      tile = flame.get_direction().step(&tile);
+      tiles.push(tile);
+    }
+  }
+  return tiles;
+}
+
+impl Effects {
+  pub fn new(settings: EffectSettings) -> Effects {
+    return Effects {
+      settings: settings,
+      particles: vec!(),
+      deaths: vec!(),
+      rng: Rng::new(0),
+      shake: 0.0,
+      time: 0
+    }
+  }
+
+  pub fn get_settings(&self) -> &EffectSettings {
+    return &self.settings;
+  }
+
+  // Switches to new settings, dropping anything that's been turned off.
+  pub fn set_settings(&mut self, settings: EffectSettings) {
+    self.settings = settings;
+    if !settings.particles {
+      self.particles.clear();
+      self.deaths.clear();
+    }
+    if !settings.screen_shake {
+      self.shake = 0.0;
+    }
+  }
+
+  // Throws out some particles from the middle of a tile.
+  fn burst(&mut self, tile: (f32, f32), count: u32, colors: &[Rgba], speed: f32, size: f32) {
+    for i in 0..count {
+      let velocity: (f32, f32) =
+        (spread(&mut self.rng) * speed, (spread(&mut self.rng) + 1.0) * speed);
+      self.particles.push(
+        Particle {
+          position: (tile.0 + spread(&mut self.rng) * 0.3, tile.1 + spread(&mut self.rng) * 0.3),
+          velocity: velocity,
+          color: colors[i as usize % colors.len()],
+          size: size * (0.6 + 0.4 * (spread(&mut self.rng) + 1.0) / 2.0),
+          age: 0
+        }
+      );
+    }
+  }
+
+  /* Starts whatever effects some events call for. The world is the one the events came from, for
+   * anything the events don't say, like where a player was standing when they died.
+   */
+  pub fn hear(&mut self, events: &[WorldEvent], world: &dyn World) {
+    let mut detonations: u32 = 0;
+    for event in events {
+      match event {
+        WorldEvent::RoundStarted => {
+          self.particles.clear();
+          self.deaths.clear();
+          self.shake = 0.0;
+        },
+        WorldEvent::BombDetonated { position, .. } => {
+          detonations += 1;
+          if self.settings.particles {
+            let tile: (f32, f32) = (position.0 as f32, position.1 as f32);
+            self.burst(tile, SPARKS_PER_BOMB, &[SPARK_COLOR], 0.08, 0.12);
+          }
+        },
+        WorldEvent::TileDestroyed { position } if self.settings.particles => {
+          let tile: (f32, f32) = (position.0 as f32, position.1 as f32);
+          self.burst(tile, DEBRIS_PER_WALL, &DEBRIS_COLORS, 0.05, 0.18);
+        },
+        WorldEvent::PlayerKilled { id, .. } if self.settings.particles => {
+          if let Some(player) = world.get_players().iter().find(|player| player.get_id() == id) {
+            let position: (f32, f32) = (
+              player.get_position().0 as f32 / SUBTILE as f32,
+              player.get_position().1 as f32 / SUBTILE as f32
+            );
+            let color: Rgba = PLAYER_COLORS[*id as usize % PLAYER_COLORS.len()];
+            self.burst(position, BITS_PER_DEATH, &[color], 0.06, 0.14);
+            self.deaths.push(Death { id: *id, position: position, age: 0 });
+          }
+        },
+        _ => {}
+      }
+    }
+    // Bombs going off together shake the screen harder than one going off by itself.
+    if self.settings.screen_shake {
+      self.shake = (self.shake + SHAKE_PER_DETONATION * detonations as f32).min(1.0);
+    }
+  }
+
+  // Moves every effect along by a number of ticks.
+  pub fn tick(&mut self, ticks: u32) {
+    for _ in 0..ticks {
+      for particle in self.particles.iter_mut() {
+        particle.position.0 += particle.velocity.0;
+        particle.position.1 += particle.velocity.1;
+        particle.velocity.1 -= GRAVITY;
+        particle.age += 1;
+      }
+      self.particles.retain(|particle| particle.age < PARTICLE_LIFETIME);
+      for death in self.deaths.iter_mut() {
+        death.age += 1;
+      }
+      self.deaths.retain(|death| death.age < DEATH_TIME);
+      self.shake = (self.shake - SHAKE_DECAY).max(0.0);
+      self.time = self.time.wrapping_add(1);
+    }
+  }
+
+  /* Returns how far the world should be drawn from where it belongs, in pixels, to shake it.
+   * Shake falls off quickly as it wears off, so small shakes are only just noticeable.
+   */
+  pub fn get_shake_offset(&self, tile_size: u32) -> (i32, i32) {
+    if !self.settings.screen_shake || self.shake <= 0.0 {
+      return (0, 0);
+    }
+    let magnitude: f32 = self.shake * self.shake * MAX_SHAKE * tile_size as f32;
+    let time: f32 = self.time as f32;
+    return (
+      (magnitude * (time * 1.9).sin()).round() as i32,
+      (magnitude * (time * 2.7 + PI / 3.0).cos()).round() as i32
+    );
+  }
+
+  /* Draws every effect over a world, which should have been drawn at the same place. Particles
+   * are moved along by alpha, like everything else in the world; see View.
+   */
+  pub fn draw(
+    &self, painter: &mut dyn Painter, world: &dyn World, alpha: f32, tile_size: u32
+  ) -> Result<(), String> {
+    if !self.settings.particles {
+      return Ok(());
+    }
+    let stage_height: i8 = world.get_stage().get_dimensions().1;
+    let square = |position: (f32, f32), scale: f32| -> ScreenRect {
+      return scene::to_screen_rect(position, scale, stage_height, tile_size);
+    };
+
+    // Flames flicker by having bright spots come and go over them.
+    for blast in world.get_blasts() {
+      for tile in blast_tiles(blast.as_ref()) {
+        let flicker: u32 =
+          (tile.0 as u32).wrapping_mul(31) ^ (tile.1 as u32).wrapping_mul(17) ^ (self.time / 3);
+        let brightness: u8 = (flicker.wrapping_mul(2654435761) >> 25) as u8;
+        painter.fill_rect(
+          square((tile.0 as f32, tile.1 as f32), 0.4), Rgba::new(255, 255, 200, brightness / 2)
+        )?;
+      }
+    }
+
+    // Dying players blink as they shrink away.
+    for death in self.deaths.iter() {
+      if (death.age / 4) % 2 == 1 {
+        continue;
+      }
+      let scale: f32 = 0.7 * (1.0 - death.age as f32 / DEATH_TIME as f32);
+      let color: Rgba = PLAYER_COLORS[death.id as usize % PLAYER_COLORS.len()];
+      painter.fill_rect(square(death.position, scale), color)?;
+    }
+
+    for particle in self.particles.iter() {
+      let position: (f32, f32) = (
+        particle.position.0 + particle.velocity.0 * alpha,
+        particle.position.1 + particle.velocity.1 * alpha
+      );
+      let fade: u8 = (255 * (PARTICLE_LIFETIME - particle.age) / PARTICLE_LIFETIME) as u8;
+      let color: Rgba = Rgba { a: fade, ..particle.color };
+      painter.fill_rect(square(position, particle.size), color)?;
+    }
+    return Ok(());
+  }
+}
@@ -0,0 +1,95 @@
+use crate::view::scene::{Painter, Rgba};
+
+// How big a glyph is, in font pixels. Characters are spaced a font pixel apart.
+pub const GLYPH_WIDTH: u32 = 3;
+pub const GLYPH_HEIGHT: u32 = 5;
+
+/* Returns the rows of a character's glyph from the top down, with each row's leftmost pixel as its
+ * highest bit. Lowercase letters are drawn as uppercase, and anything without a glyph is a '?'.
+ */
+fn glyph(character: char) -> [u8; 5] {
+  match character.to_ascii_uppercase() {
+    '0' => return [0b111, 0b101, 0b101, 0b101, 0b111],
+    '1' => return [0b010, 0b110, 0b010, 0b010, 0b111],
+    '2' => return [0b111, 0b001, 0b111, 0b100, 0b111],
+    '3' => return [0b111, 0b001, 0b111, 0b001, 0b111],
+    '4' => return [0b101, 0b101, 0b111, 0b001, 0b001],
+    '5' => return [0b111, 0b100, 0b111, 0b001, 0b111],
+    '6' => return [0b111, 0b100, 0b111, 0b101, 0b111],
+    '7' => return [0b111, 0b001, 0b001, 0b001, 0b001],
+    '8' => return [0b111, 0b101, 0b111, 0b101, 0b111],
+    '9' => return [0b111, 0b101, 0b111, 0b001, 0b111],
+    'A' => return [0b010, 0b101, 0b111, 0b101, 0b101],
+    'B' => return [0b110, 0b101, 0b110, 0b101, 0b110],
+    'C' => return [0b011, 0b100, 0b100, 0b100, 0b011],
+    'D' => return [0b110, 0b101, 0b101, 0b101, 0b110],
+    'E' => return [0b111, 0b100, 0b110, 0b100, 0b111],
+    'F' => return [0b111, 0b100, 0b110, 0b100, 0b100],
+    'G' => return [0b011, 0b100, 0b101, 0b101, 0b011],
+    'H' => return [0b101, 0b101, 0b111, 0b101, 0b101],
+    'I' => return [0b111, 0b010, 0b010, 0b010, 0b111],
+    'J' => return [0b001, 0b001, 0b001, 0b101, 0b010],
+    'K' => return [0b101, 0b101, 0b110, 0b101, 0b101],
+    'L' => return [0b100, 0b100, 0b100, 0b100, 0b111],
+    'M' => return [0b101, 0b111, 0b111, 0b101, 0b101],
+    'N' => return [0b110, 0b101, 0b101, 0b101, 0b101],
+    'O' => return [0b010, 0b101, 0b101, 0b101, 0b010],
+    'P' => return [0b110, 0b101, 0b110, 0b100, 0b100],
+    'Q' => return [0b010, 0b101, 0b101, 0b110, 0b011],
+    'R' => return [0b110, 0b101, 0b110, 0b101, 0b101],
+    'S' => return [0b011, 0b100, 0b010, 0b001, 0b110],
+    'T' => return [0b111, 0b010, 0b010, 0b010, 0b010],
+    'U' => return [0b101, 0b101, 0b101, 0b101, 0b111],
+    'V' => return [0b101, 0b101, 0b101, 0b101, 0b010],
+    'W' => return [0b101, 0b101, 0b111, 0b111, 0b101],
+    'X' => return [0b101, 0b101, 0b010, 0b101, 0b101],
+    'Y' => return [0b101, 0b101, 0b010, 0b010, 0b010],
+    'Z' => return [0b111, 0b001, 0b010, 0b100, 0b111],
+    ':' => return [0b000, 0b010, 0b000, 0b010, 0b000],
+    '/' => return [0b001, 0b001, 0b010, 0b100, 0b100],
+    '-' => return [0b000, 0b000, 0b111, 0b000, 0b000],
+    '.' => return [0b000, 0b000, 0b000, 0b000, 0b010],
+    ',' => return [0b000, 0b000, 0b000, 0b010, 0b100],
+    '+' => return [0b000, 0b010, 0b111, 0b010, 0b000],
+    '%' => return [0b101, 0b001, 0b010, 0b100, 0b101],
+    '<' => return [0b001, 0b010, 0b100, 0b010, 0b001],
+    '>' => return [0b100, 0b010, 0b001, 0b010, 0b100],
+    ' ' => return [0b000; 5],
+    _ => return [0b111, 0b001, 0b010, 0b000, 0b010]
+  }
+}
+
+// Returns how wide a line of text is on screen, in pixels.
+pub fn text_width(text: &str, scale: u32) -> u32 {
+  let count: u32 = text.chars().count() as u32;
+  return (count * (GLYPH_WIDTH + 1)).saturating_sub(1) * scale;
+}
+
+/* Draws a line of text with its top left corner at a point, using a tiny built in pixel font so
+ * that text can be drawn with nothing but filled rectangles.
+ * Scale is how many pixels on screen each pixel of the font takes up.
+ */
+pub fn draw_text(
+  painter: &mut dyn Painter, text: &str, position: (i32, i32), scale: u32, color: Rgba
+) -> Result<(), String> {
+  let mut x: i32 = position.0;
+  for character in text.chars() {
+    for (row, bits) in glyph(character).iter().enumerate() {
+      for column in 0..GLYPH_WIDTH {
+        if bits & (1 << (GLYPH_WIDTH - 1 - column)) != 0 {
+          painter.fill_rect(
+            (
+              x + (column * scale) as i32,
+              position.1 + (row as u32 * scale) as i32,
+              scale,
+              scale
+            ),
+            color
+          )?;
+        }
+      }
+    }
+    x += ((GLYPH_WIDTH + 1) * scale) as i32;
+  }
+  return Ok(());
+}
@@ -0,0 +1,221 @@
+use crate::{
+  model::{
+    input::MAX_PLAYERS,
+    player::Player,
+    score::Scoreboard,
+    stage::MAX_DIMENSIONS,
+    timestep::TICK_RATE,
+    world::{Outcome, World}
+  },
+  view::{
+    effects::Effects,
+    font::{self, GLYPH_HEIGHT},
+    scene::{self, Offset, Painter, Rgba, ScreenRect, PLAYER_COLORS},
+    theme::Theme
+  }
+};
+
+// How many player panels fit side by side in a single row of the HUD.
+const PANELS_PER_ROW: usize = 4;
+
+// How many tiles wide the clock is, on the left of the HUD.
+const CLOCK_TILES: u32 = 2;
+
+const BACKGROUND: Rgba = Rgba::rgb(24, 24, 32);
+// Swatches get outlined, since some players' colors are too dark to see against the background.
+const OUTLINE: Rgba = Rgba::rgb(90, 90, 100);
+const TEXT: Rgba = Rgba::rgb(230, 230, 230);
+const DEAD_TEXT: Rgba = Rgba::rgb(110, 110, 110);
+const SUDDEN_DEATH_TEXT: Rgba = Rgba::rgb(240, 70, 50);
+
+// Returns a number of ticks as minutes and seconds, rounding up so that 0:00 means time's up.
+pub fn format_clock(ticks: u32) -> String {
+  let seconds: u32 = ticks.div_ceil(TICK_RATE);
+  return format!("{}:{:02}", seconds / 60, seconds % 60);
+}
+
+pub fn format_outcome(outcome: &Outcome) -> String {
+  match outcome {
+    Outcome::Win(id) => return format!("P{} wins", id),
+    Outcome::Draw => return String::from("Draw")
+  }
+}
+
+/* Returns a player's stats in short, as the bombs they have left out of their total, then their
+ * fire range and speed.
+ */
+pub fn format_stats(world: &dyn World, player: &Player) -> String {
+  let left: usize =
+    (*player.get_bombs() as usize).saturating_sub(world.count_bombs(player.get_id()));
+  return format!(
+    "B{}/{} F{} S{}", left, player.get_bombs(), player.get_range(), player.get_speed()
+  );
+}
+
+/* Returns the abilities a player has in short, which is PB for piercing bombs, RC for remote
+ * control and LB for line bombs.
+ */
+pub fn format_abilities(player: &Player) -> String {
+  let mut abilities: Vec<&str> = vec!();
+  if *player.get_piercing() {
+    abilities.push("PB");
+  }
+  if *player.get_abilities().get_remote_control() {
+    abilities.push("RC");
+  }
+  if *player.get_abilities().get_line_bomb() {
+    abilities.push("LB");
+  }
+  return abilities.join(" ");
+}
+
+// Returns how tall the HUD is for a number of players, with a row of panels for every four.
+pub fn hud_height(player_count: usize, tile_size: u32) -> u32 {
+  let rows: usize = player_count.div_ceil(PANELS_PER_ROW).max(1);
+  return rows as u32 * tile_size;
+}
+
+// Returns the size in pixels of a world's stage with the HUD above it.
+pub fn screen_size(world: &dyn World, tile_size: u32) -> (u32, u32) {
+  let size: (u32, u32) = scene::scene_size(world.get_stage().get_dimensions(), tile_size);
+  return (size.0, size.1 + hud_height(world.get_players().len(), tile_size));
+}
+
+// Returns the size in pixels of the biggest stage there can be with the HUD for every player.
+pub fn max_screen_size(tile_size: u32) -> (u32, u32) {
+  let size: (u32, u32) = scene::scene_size(&MAX_DIMENSIONS, tile_size);
+  return (size.0, size.1 + hud_height(MAX_PLAYERS, tile_size));
+}
+
+/* A struct representing the drawing of the HUD, which is a strip along the top of the screen.
+ * The round clock is on the left, and is replaced by the outcome once the round is over. The rest
+ * of the strip is split into a panel for each player, with up to four panels in a row.
+ */
+struct Hud<'a> {
+  painter: &'a mut dyn Painter,
+  tile_size: u32,
+  scale: u32
+}
+
+impl<'a> Hud<'a> {
+  // Draws a line of text centered in a rectangle.
+  fn draw_centered(
+    &mut self, text: &str, rect: ScreenRect, scale: u32, color: Rgba
+  ) -> Result<(), String> {
+    let x: i32 = rect.0 + (rect.2 as i32 - font::text_width(text, scale) as i32) / 2;
+    let y: i32 = rect.1 + (rect.3 as i32 - (GLYPH_HEIGHT * scale) as i32) / 2;
+    return font::draw_text(self.painter, text, (x, y), scale, color);
+  }
+
+  fn draw_clock(&mut self, world: &dyn World, height: u32) -> Result<(), String> {
+    let rect: ScreenRect = (0, 0, CLOCK_TILES * self.tile_size, height);
+    match world.get_outcome() {
+      Some(outcome) => return self.draw_centered(&format_outcome(&outcome), rect, self.scale, TEXT),
+      None => {
+        let color: Rgba = match world.is_sudden_death() {
+          true => SUDDEN_DEATH_TEXT,
+          false => TEXT
+        };
+        let clock: String = format_clock(world.get_time_left());
+        return self.draw_centered(&clock, rect, self.scale * 2, color);
+      }
+    }
+  }
+
+  /* Draws a player's panel, which has a swatch of their color next to two lines of text. The first
+   * line has their ID, wins and abilities, and the second has their stats. Dead players are
+   * greyed out, with OUT in place of their stats.
+   */
+  fn draw_panel(
+    &mut self, world: &dyn World, scoreboard: &Scoreboard, player: &Player, rect: ScreenRect
+  ) -> Result<(), String> {
+    let margin: i32 = (self.tile_size / 8) as i32;
+    let swatch: u32 = rect.3 - 2 * margin as u32;
+    let color: Rgba = PLAYER_COLORS[*player.get_id() as usize % PLAYER_COLORS.len()];
+    let (swatch_color, text_color): (Rgba, Rgba) = match player.get_alive() {
+      true => (color, TEXT),
+      false => (Rgba { a: 64, ..color }, DEAD_TEXT)
+    };
+    let border: i32 = self.scale as i32;
+    self.painter.fill_rect(
+      (rect.0 + margin - border, rect.1 + margin - border, swatch + 2 * border as u32,
+        swatch + 2 * border as u32),
+      OUTLINE
+    )?;
+    self.painter.fill_rect((rect.0 + margin, rect.1 + margin, swatch, swatch), BACKGROUND)?;
+    self.painter.fill_rect((rect.0 + margin, rect.1 + margin, swatch, swatch), swatch_color)?;
+
+    let line_height: i32 = (GLYPH_HEIGHT * self.scale) as i32;
+    let gap: i32 = 3 * self.scale as i32;
+    let x: i32 = rect.0 + 2 * margin + swatch as i32;
+    let y: i32 = rect.1 + (rect.3 as i32 - 2 * line_height - gap) / 2;
+    let mut header: String =
+      format!("P{} W{}", player.get_id(), scoreboard.get_wins(player.get_id()));
+    let abilities: String = format_abilities(player);
+    if !abilities.is_empty() {
+      header = format!("{} {}", header, abilities);
+    }
+    font::draw_text(self.painter, &header, (x, y), self.scale, text_color)?;
+    let stats: String = match player.get_alive() {
+      true => format_stats(world, player),
+      false => String::from("OUT")
+    };
+    let position: (i32, i32) = (x, y + line_height + gap);
+    return font::draw_text(self.painter, &stats, position, self.scale, text_color);
+  }
+}
+
+/* Paints the HUD for a world across the top of the screen; see Hud.
+ * Width is how wide the screen is, which is usually the width of the stage.
+ */
+pub fn draw_hud(
+  painter: &mut dyn Painter, world: &dyn World, scoreboard: &Scoreboard, width: u32,
+  tile_size: u32
+) -> Result<(), String> {
+  let height: u32 = hud_height(world.get_players().len(), tile_size);
+  painter.fill_rect((0, 0, width, height), BACKGROUND)?;
+  let mut hud: Hud = Hud {
+    painter: painter,
+    tile_size: tile_size,
+    scale: (tile_size / 24).max(1)
+  };
+  hud.draw_clock(world, height)?;
+
+  let columns: usize = world.get_players().len().clamp(1, PANELS_PER_ROW);
+  let clock_width: u32 = CLOCK_TILES * tile_size;
+  let panel_width: u32 = width.saturating_sub(clock_width) / columns as u32;
+  for (i, player) in world.get_players().iter().enumerate() {
+    let rect: ScreenRect = (
+      (clock_width + (i % columns) as u32 * panel_width) as i32,
+      ((i / columns) as u32 * tile_size) as i32,
+      panel_width,
+      tile_size
+    );
+    hud.draw_panel(world, scoreboard, player, rect)?;
+  }
+  return Ok(());
+}
+
+/* Paints a world with the HUD above it, which is what frontends show while a match is being
+ * played. The screen has to be at least as big as screen size says.
+ * Effects are drawn over the world, and only ever shake the world, so the HUD stays readable. The
+ * HUD is drawn last so that the world can't be shaken over it.
+ */
+pub fn draw_screen(
+  painter: &mut dyn Painter, world: &dyn World, scoreboard: &Scoreboard, alpha: f32,
+  tile_size: u32, theme: Option<&Theme>, effects: Option<&Effects>
+) -> Result<(), String> {
+  let width: u32 = scene::scene_size(world.get_stage().get_dimensions(), tile_size).0;
+  let shake: (i32, i32) = match effects {
+    Some(effects) => effects.get_shake_offset(tile_size),
+    None => (0, 0)
+  };
+  let below: (i32, i32) =
+    (shake.0, hud_height(world.get_players().len(), tile_size) as i32 + shake.1);
+  let mut painter_below: Offset = Offset::new(painter, below);
+  scene::draw_world(&mut painter_below, world, alpha, tile_size, theme)?;
+  if let Some(effects) = effects {
+    effects.draw(&mut painter_below, world, alpha, tile_size)?;
+  }
+  return draw_hud(painter, world, scoreboard, width, tile_size);
+}
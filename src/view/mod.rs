@@ -0,0 +1,24 @@
+use crate::model::{score::Scoreboard, world::World};
+
+#[cfg(feature = "sdl")]
+pub mod appview;
+pub mod capture;
+pub mod effects;
+pub mod font;
+pub mod hud;
+pub mod scene;
+pub mod termview;
+pub mod theme;
+
+/* A trait representing a frontend that draws worlds.
+ * Views are strictly read-only consumers of a world. They can look at anything in it, but never
+ * change it, which keeps the model the same no matter what is (or isn't) drawing it.
+ *
+ * Scoreboard is the tally of the match the world's round is part of, for the HUD.
+ * Alpha is how far the real time is between the world's last tick and its next one; see
+ * FixedTimestep. Views should use it to interpolate anything that moves between ticks.
+ */
+pub trait View {
+  fn render(&mut self, world: &dyn World, scoreboard: &Scoreboard, alpha: f32)
+    -> Result<(), String>;
+}
@@ -0,0 +1,326 @@
+use crate::{
+  common::direction::Direction,
+  model::{
+    bomb::{Blast, Bomb, Flame},
+    item::Item,
+    player::Player,
+    position::SUBTILE,
+    stage::Tile,
+    world::World
+  },
+  view::theme::{AtlasRect, Theme}
+};
+use std::f32::consts::PI;
+
+// How big a tile is drawn, in pixels.
+pub const TILE_SIZE: u32 = 48;
+
+// A color, where an alpha of 255 is opaque.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Rgba {
+  pub r: u8,
+  pub g: u8,
+  pub b: u8,
+  pub a: u8
+}
+
+impl Rgba {
+  pub const fn rgb(r: u8, g: u8, b: u8) -> Rgba {
+    return Rgba { r: r, g: g, b: b, a: 255 };
+  }
+
+  pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Rgba {
+    return Rgba { r: r, g: g, b: b, a: a };
+  }
+}
+
+// A rectangle on screen, as its top left corner followed by its width and height.
+pub type ScreenRect = (i32, i32, u32, u32);
+
+/* A trait representing something a scene can be painted onto, like a window or an image.
+ * Colors that aren't opaque should be blended over whatever is already there.
+ */
+pub trait Painter {
+  fn fill_rect(&mut self, rect: ScreenRect, color: Rgba) -> Result<(), String>;
+
+  /* Copies a rectangle of the theme's atlas onto the screen, stretched to fit the screen rectangle.
+   * Quarter turns is how many times to turn it clockwise, and alpha is multiplied into the alpha
+   * of every pixel. Painters that weren't given an atlas can only fill rectangles.
+   */
+  fn draw_sprite(
+    &mut self, _source: &AtlasRect, _rect: ScreenRect, _quarter_turns: u8, _alpha: u8
+  ) -> Result<(), String> {
+    return Err(String::from("This painter has no atlas to draw sprites from."));
+  }
+}
+
+/* A struct representing a painter that paints everything somewhere else, by an offset in pixels.
+ * This lets something be drawn anywhere on screen without knowing it isn't at the top left.
+ */
+pub struct Offset<'a> {
+  painter: &'a mut dyn Painter,
+  offset: (i32, i32)
+}
+
+impl<'a> Offset<'a> {
+  pub fn new(painter: &'a mut dyn Painter, offset: (i32, i32)) -> Offset<'a> {
+    return Offset {
+      painter: painter,
+      offset: offset
+    }
+  }
+
+  fn shift(&self, rect: &ScreenRect) -> ScreenRect {
+    return (rect.0 + self.offset.0, rect.1 + self.offset.1, rect.2, rect.3);
+  }
+}
+
+impl<'a> Painter for Offset<'a> {
+  fn fill_rect(&mut self, rect: ScreenRect, color: Rgba) -> Result<(), String> {
+    let rect: ScreenRect = self.shift(&rect);
+    return self.painter.fill_rect(rect, color);
+  }
+
+  fn draw_sprite(
+    &mut self, source: &AtlasRect, rect: ScreenRect, quarter_turns: u8, alpha: u8
+  ) -> Result<(), String> {
+    let rect: ScreenRect = self.shift(&rect);
+    return self.painter.draw_sprite(source, rect, quarter_turns, alpha);
+  }
+}
+
+// The color each player is drawn in, indexed by player ID.
+pub const PLAYER_COLORS: [Rgba; 8] = [
+  Rgba::rgb(240, 240, 240),
+  Rgba::rgb(40, 40, 40),
+  Rgba::rgb(220, 50, 50),
+  Rgba::rgb(50, 90, 220),
+  Rgba::rgb(60, 180, 60),
+  Rgba::rgb(230, 200, 40),
+  Rgba::rgb(160, 70, 200),
+  Rgba::rgb(40, 200, 200)
+];
+
+fn tile_color(tile: &Tile) -> Rgba {
+  match tile {
+    Tile::Ground => return Rgba::rgb(56, 120, 56),
+    Tile::SoftWall => return Rgba::rgb(170, 120, 70),
+    Tile::HardWall => return Rgba::rgb(110, 110, 120)
+  }
+}
+
+fn item_color(item: &Item) -> Rgba {
+  match item {
+    Item::BombUp => return Rgba::rgb(40, 40, 160),
+    Item::FireUp => return Rgba::rgb(230, 110, 30),
+    Item::SpeedUp => return Rgba::rgb(60, 200, 220),
+    Item::PierceBomb => return Rgba::rgb(150, 60, 200),
+    Item::RemoteControl => return Rgba::rgb(200, 40, 40),
+    Item::LineBomb => return Rgba::rgb(220, 200, 40)
+  }
+}
+
+// Returns how many clockwise quarter turns take a sprite facing east to face a direction.
+fn quarter_turns(direction: &Direction) -> u8 {
+  match direction {
+    Direction::South => return 1,
+    Direction::West => return 2,
+    Direction::North => return 3,
+    _ => return 0
+  }
+}
+
+// Returns the size in pixels of a scene for a stage with the given dimensions.
+pub fn scene_size(dimensions: &(i8, i8), tile_size: u32) -> (u32, u32) {
+  return (dimensions.0 as u32 * tile_size, dimensions.1 as u32 * tile_size);
+}
+
+/* A struct representing the drawing of a single world.
+ * With a theme, everything is drawn with the theme's sprites. Without one, everything is drawn as
+ * plain shapes, with tiles as squares and players, bombs and flames as smaller squares on top.
+ */
+pub struct Scene<'a> {
+  painter: &'a mut dyn Painter,
+  theme: Option<&'a Theme>,
+  tile_size: u32,
+  stage_height: i8
+}
+
+/* Returns a square on screen for a position in tiles, scaled around the center of its tile.
+ * The model's y axis points north, while the screen's points down, so it has to be flipped, which
+ * is why this needs the height of the stage.
+ */
+pub fn to_screen_rect(
+  position: (f32, f32), scale: f32, stage_height: i8, tile_size: u32
+) -> ScreenRect {
+  let size: f32 = tile_size as f32 * scale;
+  let margin: f32 = (tile_size as f32 - size) / 2.0;
+  let x: f32 = position.0 * tile_size as f32 + margin;
+  let y: f32 = (stage_height as f32 - 1.0 - position.1) * tile_size as f32 + margin;
+  return (x.round() as i32, y.round() as i32, size.round() as u32, size.round() as u32);
+}
+
+impl<'a> Scene<'a> {
+  fn to_rect(&self, position: (f32, f32), scale: f32) -> ScreenRect {
+    return to_screen_rect(position, scale, self.stage_height, self.tile_size);
+  }
+
+  fn to_tile_rect(&self, tile: &(i8, i8), scale: f32) -> ScreenRect {
+    return self.to_rect((tile.0 as f32, tile.1 as f32), scale);
+  }
+
+  fn draw_stage(&mut self, world: &dyn World) -> Result<(), String> {
+    let dimensions: (i8, i8) = *world.get_stage().get_dimensions();
+    for y in 0..dimensions.1 {
+      for x in 0..dimensions.0 {
+        let tile: Tile = world.get_stage().get_tile(&(x, y))?;
+        let rect: ScreenRect = self.to_tile_rect(&(x, y), 1.0);
+        match self.theme {
+          Some(theme) => self.painter.draw_sprite(theme.tile(&tile).frame(0), rect, 0, 255)?,
+          None => self.painter.fill_rect(rect, tile_color(&tile))?
+        }
+      }
+    }
+    return Ok(());
+  }
+
+  // Draws an item lying on a tile, as a light square with the item's color inside it.
+  fn draw_item(&mut self, tile: &(i8, i8), item: &Item) -> Result<(), String> {
+    if let Some(theme) = self.theme {
+      let rect: ScreenRect = self.to_tile_rect(tile, 1.0);
+      return self.painter.draw_sprite(theme.item(item).frame(0), rect, 0, 255);
+    }
+    let rect: ScreenRect = self.to_tile_rect(tile, 0.6);
+    self.painter.fill_rect(rect, Rgba::rgb(240, 230, 200))?;
+    return self.painter.fill_rect(
+      (rect.0 + 4, rect.1 + 4, rect.2 - 8, rect.3 - 8), item_color(item)
+    );
+  }
+
+  /* Draws a bomb that pulses faster as its fuse runs out, and reddens as it gets close to going
+   * off. Remote bombs have no fuse to speak of, so they just sit there.
+   * Themed bombs animate from when they were placed instead.
+   */
+  fn draw_bomb(&mut self, bomb: &dyn Bomb, fuse: i16) -> Result<(), String> {
+    if let Some(theme) = self.theme {
+      let time: u32 = (fuse - *bomb.get_lifetime()).max(0) as u32;
+      let rect: ScreenRect = self.to_tile_rect(bomb.get_position(), 1.0);
+      return self.painter.draw_sprite(theme.bomb(bomb).frame(time), rect, 0, 255);
+    }
+    let lifetime: f32 = *bomb.get_lifetime() as f32;
+    let (scale, color): (f32, Rgba) = match bomb.get_remote() {
+      true => (0.7, Rgba::rgb(30, 30, 90)),
+      false => {
+        let period: f32 = 8.0 + lifetime / 10.0;
+        let heat: f32 = 1.0 - lifetime / fuse.max(1) as f32;
+        (
+          0.65 + 0.1 * (2.0 * PI * lifetime / period).sin(),
+          Rgba::rgb((30.0 + 200.0 * heat) as u8, 30, 30)
+        )
+      }
+    };
+    return self.painter.fill_rect(self.to_tile_rect(bomb.get_position(), scale), color);
+  }
+
+  // Draws a flame as a bar covering every tile from its start to its end.
+  fn draw_flame(&mut self, flame: &dyn Flame, color: Rgba) -> Result<(), String> {
+    let start: ScreenRect = self.to_tile_rect(flame.get_start(), 0.6);
+    let end: ScreenRect = self.to_tile_rect(flame.get_end(), 0.6);
+    let left: i32 = start.0.min(end.0);
+    let top: i32 = start.1.min(end.1);
+    let right: i32 = (start.0 + start.2 as i32).max(end.0 + end.2 as i32);
+    let bottom: i32 = (start.1 + start.3 as i32).max(end.1 + end.3 as i32);
+    return self.painter.fill_rect((left, top, (right - left) as u32, (bottom - top) as u32), color);
+  }
+
+  /* Draws a flame with a theme's sprites, as an arm on every tile but the last and a tip on the
+   * last, all turned to point the way the flame spreads.
+   */
+  fn draw_flame_sprites(
+    &mut self, flame: &dyn Flame, theme: &Theme, time: u32, fade: u8
+  ) -> Result<(), String> {
+    let turns: u8 = quarter_turns(flame.get_direction());
+    let mut tile: (i8, i8) = *flame.get_start();
+    while &tile != flame.get_end() {
+      let rect: ScreenRect = self.to_tile_rect(&tile, 1.0);
+      self.painter.draw_sprite(theme.get_flames().get_arm().frame(time), rect, turns, fade)?;
+      tile = flame.get_direction().step(&tile);
+    }
+    let rect: ScreenRect = self.to_tile_rect(&tile, 1.0);
+    return self.painter.draw_sprite(theme.get_flames().get_tip().frame(time), rect, turns, fade);
+  }
+
+  /* Draws a blast's center and flames, fading them out over the last part of its lifetime.
+   * Themed flames animate from when the blast finished spreading.
+   */
+  fn draw_blast(&mut self, blast: &dyn Blast, blast_lifetime: i8) -> Result<(), String> {
+    let fade: u8 = (*blast.get_lifetime() as u32 * 255 / 20).min(255) as u8;
+    if let Some(theme) = self.theme {
+      let time: u32 = (blast_lifetime - *blast.get_lifetime()).max(0) as u32;
+      for flame in blast.get_flames() {
+        self.draw_flame_sprites(flame.as_ref(), theme, time, fade)?;
+      }
+      let center: ScreenRect = self.to_tile_rect(blast.get_center(), 1.0);
+      return self.painter.draw_sprite(
+        theme.get_flames().get_center().frame(time), center, 0, fade
+      );
+    }
+    for flame in blast.get_flames() {
+      self.draw_flame(flame.as_ref(), Rgba::new(255, 160, 30, fade))?;
+    }
+    let center: ScreenRect = self.to_tile_rect(blast.get_center(), 0.8);
+    return self.painter.fill_rect(center, Rgba::new(255, 230, 90, fade));
+  }
+
+  /* Draws a player somewhere between where they were last tick and where they are now.
+   * Themed players animate as they walk, stepping through a frame every quarter of a tile.
+   */
+  fn draw_player(&mut self, player: &Player, alpha: f32) -> Result<(), String> {
+    let previous: &(i32, i32) = player.get_previous_position();
+    let current: &(i32, i32) = player.get_position();
+    let position: (f32, f32) = (
+      (previous.0 as f32 + (current.0 - previous.0) as f32 * alpha) / SUBTILE as f32,
+      (previous.1 as f32 + (current.1 - previous.1) as f32 * alpha) / SUBTILE as f32
+    );
+    if let Some(theme) = self.theme {
+      let time: u32 = ((current.0.abs() + current.1.abs()) / (SUBTILE / 4)) as u32;
+      let rect: ScreenRect = self.to_rect(position, 1.0);
+      return self.painter.draw_sprite(theme.player(player.get_id()).frame(time), rect, 0, 255);
+    }
+    let rect: ScreenRect = self.to_rect(position, 0.7);
+    self.painter.fill_rect(rect, Rgba::rgb(0, 0, 0))?;
+    return self.painter.fill_rect(
+      (rect.0 + 2, rect.1 + 2, rect.2 - 4, rect.3 - 4),
+      PLAYER_COLORS[*player.get_id() as usize % PLAYER_COLORS.len()]
+    );
+  }
+}
+
+/* Paints a world, interpolating anything that moves by alpha; see View.
+ * This is shared by every frontend that draws pixels, so a window and an image of the same world
+ * always look the same. Drawing with a theme needs a painter that has that theme's atlas.
+ */
+pub fn draw_world(
+  painter: &mut dyn Painter, world: &dyn World, alpha: f32, tile_size: u32, theme: Option<&Theme>
+) -> Result<(), String> {
+  let mut scene: Scene = Scene {
+    painter: painter,
+    theme: theme,
+    tile_size: tile_size,
+    stage_height: world.get_stage().get_dimensions().1
+  };
+  scene.draw_stage(world)?;
+  for (tile, item) in world.get_items() {
+    scene.draw_item(tile, item)?;
+  }
+  for bomb in world.get_bombs() {
+    scene.draw_bomb(bomb.as_ref(), *world.get_rules().get_bomb_fuse())?;
+  }
+  for blast in world.get_blasts() {
+    scene.draw_blast(blast.as_ref(), *world.get_rules().get_blast_lifetime())?;
+  }
+  for player in world.get_players().iter().filter(|player| *player.get_alive()) {
+    scene.draw_player(player, alpha)?;
+  }
+  return Ok(());
+}
@@ -0,0 +1,218 @@
+use crate::{
+  model::{
+    item::Item,
+    player::Player,
+    position::SUBTILE,
+    score::Scoreboard,
+    stage::Tile,
+    world::World
+  },
+  view::{hud, theme::BOMB_WARNING, View}
+};
+use std::io::Write;
+
+// The 256-color palette index each player is drawn in, indexed by player ID.
+const PLAYER_COLORS: [u8; 8] = [15, 0, 196, 27, 46, 226, 129, 51];
+
+// What's drawn in a single tile. Only the topmost thing in a tile gets drawn.
+#[derive(Copy, Clone, PartialEq)]
+enum Cell {
+  Tile(Tile),
+  Item(Item),
+  Bomb(i16),
+  Flame,
+  Player(u8)
+}
+
+// Works out what should be drawn in every tile of a world, row by row from the top down.
+fn cells(world: &dyn World) -> Vec<Vec<Cell>> {
+  let dimensions: (i8, i8) = *world.get_stage().get_dimensions();
+  let mut rows: Vec<Vec<Cell>> = vec!();
+  for y in (0..dimensions.1).rev() {
+    let mut row: Vec<Cell> = vec!();
+    for x in 0..dimensions.0 {
+      let tile: (i8, i8) = (x, y);
+      let mut cell: Cell = Cell::Tile(world.get_stage().get_tile(&tile).unwrap_or(Tile::HardWall));
+      for (_, item) in world.get_items().iter().filter(|(position, _)| position == &tile) {
+        cell = Cell::Item(*item);
+      }
+      for bomb in world.get_bombs().iter().filter(|bomb| bomb.get_position() == &tile) {
+        cell = Cell::Bomb(*bomb.get_lifetime());
+      }
+      for blast in world.get_blasts() {
+        if blast.covers(&tile) {
+          cell = Cell::Flame;
+        }
+      }
+      let standing_here = |player: &&Player| *player.get_alive() && player.get_tile() == tile;
+      for player in world.get_players().iter().filter(standing_here) {
+        cell = Cell::Player(*player.get_id());
+      }
+      row.push(cell);
+    }
+    rows.push(row);
+  }
+  return rows;
+}
+
+// Returns the letter an item is drawn as.
+fn item_letter(item: &Item) -> char {
+  match item {
+    Item::BombUp => return 'B',
+    Item::FireUp => return 'F',
+    Item::SpeedUp => return 'S',
+    Item::PierceBomb => return 'P',
+    Item::RemoteControl => return 'R',
+    Item::LineBomb => return 'L'
+  }
+}
+
+// Returns a cell as two characters wide, colored with ANSI escape codes.
+fn colored(cell: &Cell) -> String {
+  match cell {
+    Cell::Tile(Tile::Ground) => return String::from("\x1b[48;5;22m  "),
+    Cell::Tile(Tile::SoftWall) => return String::from("\x1b[48;5;94;38;5;136m▒▒"),
+    Cell::Tile(Tile::HardWall) => return String::from("\x1b[48;5;240;38;5;250m██"),
+    Cell::Item(item) => return format!("\x1b[48;5;58;38;5;229m {}", item_letter(item)),
+    Cell::Bomb(lifetime) if *lifetime < BOMB_WARNING => {
+      return String::from("\x1b[48;5;22;38;5;196m()");
+    },
+    Cell::Bomb(_) => return String::from("\x1b[48;5;22;38;5;16m()"),
+    Cell::Flame => return String::from("\x1b[48;5;208;38;5;226m**"),
+    Cell::Player(id) => {
+      return format!("\x1b[48;5;22;38;5;{}mP{}", PLAYER_COLORS[*id as usize % 8], id);
+    }
+  }
+}
+
+/* Returns a cell as a single plain character. Tiles and players use the same characters as stage
+ * layouts do, while bombs are 'B', flames are '*' and items are lowercase letters.
+ */
+fn plain(cell: &Cell) -> char {
+  match cell {
+    Cell::Tile(Tile::Ground) => return '.',
+    Cell::Tile(Tile::SoftWall) => return '+',
+    Cell::Tile(Tile::HardWall) => return '#',
+    Cell::Item(item) => return item_letter(item).to_ascii_lowercase(),
+    Cell::Bomb(_) => return 'B',
+    Cell::Flame => return '*',
+    Cell::Player(id) => return std::char::from_digit(*id as u32, 10).unwrap_or('?')
+  }
+}
+
+/* Draws the HUD as lines of text, with the round clock (or outcome) on the first line and a line
+ * for each player after it. See the hud module for what everything means.
+ */
+pub fn draw_hud(world: &dyn World, scoreboard: &Scoreboard, color: bool) -> Vec<String> {
+  let status: String = match world.get_outcome() {
+    Some(outcome) => hud::format_outcome(&outcome),
+    None if world.is_sudden_death() => {
+      format!("Sudden death {}", hud::format_clock(world.get_time_left()))
+    },
+    None => format!("Time {}", hud::format_clock(world.get_time_left()))
+  };
+  let mut lines: Vec<String> = vec!(status);
+  for player in world.get_players() {
+    let id: usize = *player.get_id() as usize;
+    let swatch: String = match color {
+      true => format!("\x1b[38;5;{}m██\x1b[0m", PLAYER_COLORS[id % PLAYER_COLORS.len()]),
+      false => String::from("-")
+    };
+    let stats: String = match player.get_alive() {
+      true => format!("{}  {}", hud::format_stats(world, player), hud::format_abilities(player)),
+      false => String::from("out")
+    };
+    lines.push(format!("{} P{} W{}  {}", swatch, id, scoreboard.get_wins(player.get_id()), stats));
+  }
+  return lines;
+}
+
+/* Draws a world as lines of text, either colored for a terminal or as plain characters.
+ * Under the stage is a line for every player, bomb and item with the exact state behind it, which
+ * makes this handy for debugging a world even outside of the terminal frontend.
+ */
+pub fn draw(world: &dyn World, color: bool) -> Vec<String> {
+  let mut lines: Vec<String> = vec!();
+  for row in cells(world) {
+    match color {
+      true => lines.push(row.iter().map(colored).collect::<String>() + "\x1b[0m"),
+      false => lines.push(row.iter().map(plain).collect())
+    }
+  }
+  for player in world.get_players() {
+    let position: &(i32, i32) = player.get_position();
+    lines.push(
+      format!(
+        "P{} at ({}, {}) + ({}, {})/{} facing {:?}, {} bombs, {} range{}",
+        player.get_id(),
+        position.0.div_euclid(SUBTILE), position.1.div_euclid(SUBTILE),
+        position.0.rem_euclid(SUBTILE), position.1.rem_euclid(SUBTILE), SUBTILE,
+        player.get_direction(), player.get_bombs(), player.get_range(),
+        if *player.get_alive() { "" } else { ", dead" }
+      )
+    );
+  }
+  for bomb in world.get_bombs() {
+    lines.push(
+      format!(
+        "Bomb at {:?} from P{}, {} frames left, {} range",
+        bomb.get_position(), bomb.get_owner(), bomb.get_lifetime(), bomb.get_range()
+      )
+    );
+  }
+  for (tile, item) in world.get_items() {
+    lines.push(format!("{} at {:?}", item.name(), tile));
+  }
+  return lines;
+}
+
+/* A struct representing a view that draws worlds as text, usually to a terminal.
+ *
+ * Out is wherever the text is written to.
+ * Color is whether to use ANSI escape codes for color, and to redraw over the last frame rather
+ * than writing each one after the other. Turning it off gives plain text, which is easier to read
+ * in logs.
+ */
+pub struct TermView<W: Write> {
+  out: W,
+  color: bool
+}
+
+impl<W: Write> TermView<W> {
+  pub fn new(out: W, color: bool) -> TermView<W> {
+    return TermView {
+      out: out,
+      color: color
+    }
+  }
+}
+
+impl<W: Write> View for TermView<W> {
+  // Terminals can only draw whole tiles, so there's nothing to interpolate.
+  fn render(
+    &mut self, world: &dyn World, scoreboard: &Scoreboard, _alpha: f32
+  ) -> Result<(), String> {
+    let mut frame: String = String::new();
+    if self.color {
+      // Moves the cursor back to the top left, so this frame replaces the last one.
+      frame.push_str("\x1b[H");
+    }
+    let mut lines: Vec<String> = draw_hud(world, scoreboard, self.color);
+    lines.extend(draw(world, self.color));
+    for line in lines {
+      frame.push_str(&line);
+      /* Lines could be shorter than last frame's, so clear whatever's left of them. Terminals in
+       * raw mode also won't go back to the start of the line on their own.
+       */
+      match self.color {
+        true => frame.push_str("\x1b[K\r\n"),
+        false => frame.push('\n')
+      }
+    }
+    if self.color {
+      frame.push_str("\x1b[J");
+    }
+    self.out.write_all(frame.as_bytes()).map_err(|e| e.to_string())?;
+    return self.out.flush().map_err(|e| e.to_string());
+  }
+}
@@ -0,0 +1,271 @@
+use crate::{
+  model::{
+    bomb::Bomb,
+    input::{PlayerId, MAX_PLAYERS},
+    item::Item,
+    stage::Tile
+  },
+  view::capture::Frame
+};
+use serde::{Deserialize, Serialize};
+use std::{
+  fs,
+  path::{Path, PathBuf}
+};
+
+// Where the themes that ship with the game live, each in a directory named after it.
+pub const THEMES_DIRECTORY: &str = "assets/themes";
+
+// The themes that ship with the game.
+pub const THEMES: [&str; 3] = ["classic", "ice", "desert"];
+
+// Bombs with less than this many frames left on their fuse are drawn as about to go off.
+pub const BOMB_WARNING: i16 = 60;
+
+// A rectangle of a theme's atlas, in pixels from its top left.
+#[derive(Copy, Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct AtlasRect {
+  pub x: u32,
+  pub y: u32,
+  pub w: u32,
+  pub h: u32
+}
+
+/* A struct representing an animated sprite.
+ * Frames are the rectangles of the atlas it cycles through, and frame time is how many ticks each
+ * one is shown for. A sprite that doesn't animate just has a single frame.
+ */
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Sprite {
+  frames: Vec<AtlasRect>,
+  #[serde(default = "default_frame_time")]
+  frame_time: u32
+}
+
+fn default_frame_time() -> u32 {
+  return 1;
+}
+
+impl Sprite {
+  // Returns the frame to show at a point in time, in ticks.
+  pub fn frame(&self, time: u32) -> &AtlasRect {
+    return &self.frames[(time / self.frame_time) as usize % self.frames.len()];
+  }
+
+  fn validate(&self, name: &str, atlas_size: (u32, u32)) -> Result<(), String> {
+    if self.frames.is_empty() {
+      return Err(format!("{} has no frames.", name));
+    } else if self.frame_time == 0 {
+      return Err(format!("{} has a frame time of 0.", name));
+    }
+    for (i, rect) in self.frames.iter().enumerate() {
+      if rect.w == 0 || rect.h == 0 || rect.x + rect.w > atlas_size.0
+        || rect.y + rect.h > atlas_size.1 {
+        return Err(
+          format!(
+            "Frame {} of {} isn't inside the {}x{} atlas.", i, name, atlas_size.0, atlas_size.1
+          )
+        );
+      }
+    }
+    return Ok(());
+  }
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct TileSprites {
+  ground: Sprite,
+  soft_wall: Sprite,
+  hard_wall: Sprite
+}
+
+/* Ticking is a bomb with plenty of fuse left, warning is one that's about to go off, and remote is
+ * one that's waiting for its owner to detonate it.
+ */
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct BombSprites {
+  ticking: Sprite,
+  warning: Sprite,
+  remote: Sprite
+}
+
+/* Center is drawn where the bomb was, arm along the middle of each flame, and tip at the end of
+ * each flame. Arms and tips should be drawn pointing east, and get rotated for other directions.
+ */
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct FlameSprites {
+  center: Sprite,
+  arm: Sprite,
+  tip: Sprite
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ItemSprites {
+  bomb_up: Sprite,
+  fire_up: Sprite,
+  speed_up: Sprite,
+  pierce_bomb: Sprite,
+  remote_control: Sprite,
+  line_bomb: Sprite
+}
+
+/* A struct representing a theme's manifest, which says where everything is in its atlas.
+ * Every field is required, so a theme missing a sprite fails to load rather than failing to draw.
+ *
+ * Atlas is the file name of the atlas image, relative to the manifest.
+ * Players are indexed by player ID, so there has to be one for every possible player.
+ */
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+  atlas: String,
+  tiles: TileSprites,
+  players: Vec<Sprite>,
+  bombs: BombSprites,
+  flames: FlameSprites,
+  items: ItemSprites
+}
+
+/* A struct representing a theme, which is everything needed to draw worlds with sprites.
+ * Themes live in their own directories, with a manifest (theme.ron) next to the atlas it describes.
+ */
+pub struct Theme {
+  name: String,
+  manifest: Manifest,
+  atlas: Frame
+}
+
+impl Theme {
+  /* Loads a theme from its directory, checking that the manifest has a sprite for everything and
+   * that every sprite is actually inside the atlas.
+   */
+  pub fn load(directory: &Path) -> Result<Theme, String> {
+    let name: String = directory.file_name().map(|name| name.to_string_lossy().into_owned())
+      .unwrap_or_else(|| directory.display().to_string());
+    let manifest_path: PathBuf = directory.join("theme.ron");
+    let contents: String = fs::read_to_string(&manifest_path)
+      .map_err(|e| format!("Theme {}: couldn't read {}: {}", name, manifest_path.display(), e))?;
+    let manifest: Manifest = ron::de::from_str(&contents)
+      .map_err(|e| format!("Theme {}: invalid manifest: {}", name, e))?;
+    let atlas: Frame = Frame::load_png(&directory.join(&manifest.atlas))
+      .map_err(|e| format!("Theme {}: {}", name, e))?;
+
+    let theme: Theme = Theme {
+      name: name,
+      manifest: manifest,
+      atlas: atlas
+    };
+    theme.validate().map_err(|e| format!("Theme {}: {}", theme.name, e))?;
+    return Ok(theme);
+  }
+
+  // Loads one of the themes that ship with the game by name.
+  pub fn load_builtin(name: &str) -> Result<Theme, String> {
+    if !THEMES.contains(&name) {
+      return Err(format!("There's no theme called {}. Try one of {}.", name, THEMES.join(", ")));
+    }
+    return Theme::load(&Path::new(THEMES_DIRECTORY).join(name));
+  }
+
+  fn validate(&self) -> Result<(), String> {
+    let size: (u32, u32) = (*self.atlas.get_width(), *self.atlas.get_height());
+    let manifest: &Manifest = &self.manifest;
+    if manifest.players.len() < MAX_PLAYERS {
+      return Err(
+        format!(
+          "players needs a sprite for each of the {} players, but only has {}.",
+          MAX_PLAYERS, manifest.players.len()
+        )
+      );
+    }
+    let mut sprites: Vec<(String, &Sprite)> = vec!(
+      (String::from("tiles.ground"), &manifest.tiles.ground),
+      (String::from("tiles.soft_wall"), &manifest.tiles.soft_wall),
+      (String::from("tiles.hard_wall"), &manifest.tiles.hard_wall),
+      (String::from("bombs.ticking"), &manifest.bombs.ticking),
+      (String::from("bombs.warning"), &manifest.bombs.warning),
+      (String::from("bombs.remote"), &manifest.bombs.remote),
+      (String::from("flames.center"), &manifest.flames.center),
+      (String::from("flames.arm"), &manifest.flames.arm),
+      (String::from("flames.tip"), &manifest.flames.tip),
+      (String::from("items.bomb_up"), &manifest.items.bomb_up),
+      (String::from("items.fire_up"), &manifest.items.fire_up),
+      (String::from("items.speed_up"), &manifest.items.speed_up),
+      (String::from("items.pierce_bomb"), &manifest.items.pierce_bomb),
+      (String::from("items.remote_control"), &manifest.items.remote_control),
+      (String::from("items.line_bomb"), &manifest.items.line_bomb)
+    );
+    for (id, sprite) in manifest.players.iter().enumerate() {
+      sprites.push((format!("players[{}]", id), sprite));
+    }
+    for (name, sprite) in sprites {
+      sprite.validate(&name, size)?;
+    }
+    return Ok(());
+  }
+
+  pub fn tile(&self, tile: &Tile) -> &Sprite {
+    match tile {
+      Tile::Ground => return &self.manifest.tiles.ground,
+      Tile::SoftWall => return &self.manifest.tiles.soft_wall,
+      Tile::HardWall => return &self.manifest.tiles.hard_wall
+    }
+  }
+
+  pub fn player(&self, id: &PlayerId) -> &Sprite {
+    return &self.manifest.players[*id as usize % self.manifest.players.len()];
+  }
+
+  pub fn bomb(&self, bomb: &dyn Bomb) -> &Sprite {
+    if *bomb.get_remote() {
+      return &self.manifest.bombs.remote;
+    } else if *bomb.get_lifetime() < BOMB_WARNING {
+      return &self.manifest.bombs.warning;
+    } else {
+      return &self.manifest.bombs.ticking;
+    }
+  }
+
+  pub fn item(&self, item: &Item) -> &Sprite {
+    let items: &ItemSprites = &self.manifest.items;
+    match item {
+      Item::BombUp => return &items.bomb_up,
+      Item::FireUp => return &items.fire_up,
+      Item::SpeedUp => return &items.speed_up,
+      Item::PierceBomb => return &items.pierce_bomb,
+      Item::RemoteControl => return &items.remote_control,
+      Item::LineBomb => return &items.line_bomb
+    }
+  }
+
+  pub fn get_flames(&self) -> &FlameSprites {
+    return &self.manifest.flames;
+  }
+
+  pub fn get_name(&self) -> &String {
+    return &self.name;
+  }
+
+  pub fn get_atlas(&self) -> &Frame {
+    return &self.atlas;
+  }
+}
+
+impl FlameSprites {
+  pub fn get_center(&self) -> &Sprite {
+    return &self.center;
+  }
+
+  pub fn get_arm(&self) -> &Sprite {
+    return &self.arm;
+  }
+
+  pub fn get_tip(&self) -> &Sprite {
+    return &self.tip;
+  }
+}
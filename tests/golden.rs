@@ -0,0 +1,42 @@
+/* Checks every golden scenario against its golden image, so that a render regression fails the
+ * build. Run open-bomberman-golden to see the renders side by side, or to re-bless the images.
+ */
+#[path = "../src/bin/golden/scenarios.rs"]
+mod scenarios;
+
+use open_bomberman::view::capture::Frame;
+use scenarios::{GOLDEN_DIRECTORY, OUTPUT_DIRECTORY};
+use std::{fs, path::Path};
+
+// Returns why a rendered scenario doesn't match its golden image, if it doesn't.
+fn compare(name: &str, frame: Result<Frame, String>) -> Option<String> {
+  let frame: Frame = match frame {
+    Ok(frame) => frame,
+    Err(e) => return Some(format!("{} couldn't be rendered: {}", name, e))
+  };
+  let file_name: String = format!("{}.png", name);
+  // The render is kept for looking at afterwards, but failing to keep it isn't a failure itself.
+  let _ = frame.save_png(&Path::new(OUTPUT_DIRECTORY).join(&file_name));
+  let golden: Frame = match Frame::load_png(&Path::new(GOLDEN_DIRECTORY).join(&file_name)) {
+    Ok(golden) => golden,
+    Err(e) => return Some(format!("{} has no golden image: {}", name, e))
+  };
+  match frame.diff(&golden) {
+    Ok(0) => return None,
+    Ok(pixels) => return Some(format!("{} has {} pixels different", name, pixels)),
+    Err(e) => return Some(format!("{} is different: {}", name, e))
+  }
+}
+
+#[test]
+fn renders_match_golden_images() {
+  let _ = fs::create_dir_all(OUTPUT_DIRECTORY);
+  let failures: Vec<String> = scenarios::render_all().into_iter()
+    .filter_map(|(name, frame)| compare(&name, frame))
+    .collect();
+  assert!(
+    failures.is_empty(),
+    "{} scenarios didn't match their golden images (renders are in {}):\n{}",
+    failures.len(), OUTPUT_DIRECTORY, failures.join("\n")
+  );
+}